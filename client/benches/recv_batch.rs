@@ -0,0 +1,94 @@
+// Compares draining queued datagrams from a loopback UDP socket one recv_from at a time with
+// reading them in batches, as the ICE agent does. Run with
+// `cargo bench --features internals --bench recv_batch`
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use socket2::SockRef;
+use tokio::{net::UdpSocket, time::timeout};
+use webrtc_unreliable_client::internals::recv_from_batch;
+
+const DATAGRAMS: usize = 2000;
+const DATAGRAM_SIZE: usize = 200;
+const BATCH_SIZE: usize = 16;
+const RUNS: usize = 20;
+// how long the socket has to stay empty for the drain to be over
+const IDLE: Duration = Duration::from_millis(50);
+
+// fill sends DATAGRAMS datagrams to `receiver` and lets them queue up
+async fn fill(sender: &UdpSocket, receiver: SocketAddr) {
+    let datagram = [7; DATAGRAM_SIZE];
+    for _ in 0..DATAGRAMS {
+        sender.send_to(&datagram, receiver).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+// drain_single reads datagrams with recv_from until the socket stays empty, and returns how
+// many were read and how long reading them took
+async fn drain_single(receiver: &UdpSocket) -> (usize, Duration) {
+    let mut buf = vec![0; 1500];
+    let started = Instant::now();
+    let mut last = started;
+    let mut count = 0;
+    while let Ok(result) = timeout(IDLE, receiver.recv_from(&mut buf)).await {
+        result.unwrap();
+        count += 1;
+        last = Instant::now();
+    }
+    (count, last - started)
+}
+
+// drain_batched is drain_single, reading in batches of BATCH_SIZE
+async fn drain_batched(receiver: &UdpSocket) -> (usize, Duration) {
+    let mut bufs = vec![vec![0; 1500]; BATCH_SIZE];
+    let mut received = vec![(0, SocketAddr::from(([0, 0, 0, 0], 0))); BATCH_SIZE];
+    let started = Instant::now();
+    let mut last = started;
+    let mut count = 0;
+    while let Ok(result) = timeout(IDLE, recv_from_batch(receiver, &mut bufs, &mut received)).await
+    {
+        count += result.unwrap();
+        last = Instant::now();
+    }
+    (count, last - started)
+}
+
+fn report(name: &str, mut samples: Vec<(usize, Duration)>) {
+    samples.sort_by_key(|&(_, elapsed)| elapsed);
+    let (count, median) = samples[samples.len() / 2];
+    let (_, min) = samples[0];
+    println!(
+        "{:<10} median {:>9.3?}  min {:>9.3?}  ({} datagrams per run, {:.0} ns each)",
+        name,
+        median,
+        min,
+        count,
+        median.as_nanos() as f64 / count.max(1) as f64
+    );
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    // room for the whole burst, as far as net.core.rmem_max allows
+    SockRef::from(&receiver)
+        .set_recv_buffer_size(4 << 20)
+        .unwrap();
+    let receiver_addr = receiver.local_addr().unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let mut single = Vec::new();
+    let mut batched = Vec::new();
+    for _ in 0..RUNS {
+        fill(&sender, receiver_addr).await;
+        single.push(drain_single(&receiver).await);
+        fill(&sender, receiver_addr).await;
+        batched.push(drain_batched(&receiver).await);
+    }
+    report("recv_from", single);
+    report("batched", batched);
+}
@@ -0,0 +1,63 @@
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use crate::event::{EventSender, SocketEvent};
+
+/// Aborts a connection attempt started with
+/// [`Socket::connect_abortable`](crate::Socket::connect_abortable), e.g. from a watchdog
+/// task or when the user stops waiting
+pub struct ConnectAbortHandle {
+    abort: CancellationToken,
+    // yields `None` once the ConnectAttempt has been dropped
+    finished: mpsc::Receiver<()>,
+    events: EventSender,
+}
+
+impl ConnectAbortHandle {
+    pub(crate) fn new() -> (Self, ConnectAttempt) {
+        let abort = CancellationToken::new();
+        let (finished_sender, finished) = mpsc::channel(1);
+        let events = EventSender::new();
+        (
+            ConnectAbortHandle {
+                abort: abort.clone(),
+                finished,
+                events: events.clone(),
+            },
+            ConnectAttempt {
+                abort,
+                _finished: finished_sender,
+                events,
+            },
+        )
+    }
+
+    /// Subscribes to the events of the connection being established, e.g. the
+    /// [`SocketEvent::HandshakePhaseStarted`] and [`SocketEvent::HandshakePhaseFinished`]
+    /// events for a progress indicator. These are the events of
+    /// [`SocketIo::events`](crate::SocketIo::events), but available before the connection
+    /// is. Only events emitted after subscribing are received
+    pub fn events(&self) -> broadcast::Receiver<SocketEvent> {
+        self.events.subscribe()
+    }
+
+    /// Aborts the connection attempt, which then resolves to
+    /// [`SocketConnectionError::Aborted`], and waits until the peer connection, its sockets
+    /// and its tasks have been shut down. Once the attempt has resolved, this returns right
+    /// away and has no effect.
+    ///
+    /// The connect future has to be polled, or dropped, for this to return.
+    ///
+    /// [`SocketConnectionError::Aborted`]: crate::SocketConnectionError::Aborted
+    pub async fn abort(mut self) {
+        self.abort.cancel();
+        let _ = self.finished.recv().await;
+    }
+}
+
+// ConnectAttempt is held by the connect future until it resolves
+pub(crate) struct ConnectAttempt {
+    pub(crate) abort: CancellationToken,
+    _finished: mpsc::Sender<()>,
+    pub(crate) events: EventSender,
+}
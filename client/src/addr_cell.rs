@@ -1,9 +1,8 @@
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    net::{IpAddr, SocketAddr},
     sync::Arc,
 };
 
-use regex::Regex;
 use tokio::sync::Mutex;
 
 // MaybeAddr
@@ -46,19 +45,18 @@ pub enum ServerAddr {
     Finding,
 }
 
+// candidate_to_addr reads the address of an ICE candidate, which is still being found if the
+// candidate can't be read
 pub(crate) fn candidate_to_addr(candidate_str: &str) -> ServerAddr {
-    let pattern =
-        Regex::new(r"\b(?P<ip_addr>(?:[0-9]{1,3}\.){3}[0-9]{1,3}) (?P<port>[0-9]{1,5})\b")
-            .expect("failed to compile regex pattern");
-
-    let captures = pattern
-        .captures(candidate_str)
-        .expect("regex failed to find SocketAddr string");
-
-    let ip_addr = captures["ip_addr"]
-        .parse::<Ipv4Addr>()
-        .expect("not a valid ip address..");
-    let port = &captures["port"].parse::<u16>().expect("not a valid port..");
-
-    ServerAddr::Found(SocketAddr::new(IpAddr::V4(ip_addr), *port))
+    // foundation, component, transport and priority come before the address and port
+    let mut fields = candidate_str.split_whitespace().skip(4);
+    let ip_addr = fields
+        .next()
+        .and_then(|ip_addr| ip_addr.parse::<IpAddr>().ok());
+    let port = fields.next().and_then(|port| port.parse::<u16>().ok());
+
+    match (ip_addr, port) {
+        (Some(ip_addr), Some(port)) => ServerAddr::Found(SocketAddr::new(ip_addr, port)),
+        _ => ServerAddr::Finding,
+    }
 }
@@ -0,0 +1,171 @@
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc as std_mpsc, Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use tokio::{
+    runtime,
+    sync::{mpsc, oneshot},
+    time::timeout,
+};
+
+use super::{
+    addr_cell::{AddrCell, ServerAddr},
+    config::SocketConfig,
+    error::SocketConnectionError,
+    socket::{check_message_size, Socket, SocketIo},
+};
+
+// SHUTDOWN_MARGIN is how long closing the connection may take on top of the drain timeout,
+// for the SCTP and DTLS teardown
+const SHUTDOWN_MARGIN: Duration = Duration::from_secs(1);
+
+/// A connection for synchronous callers. It owns a single-threaded runtime which drives
+/// the connection on a background thread
+///
+/// Dropping it closes the connection like [`SocketIo::close`], blocking until the messages
+/// still queued have been sent or [`SocketConfig::set_drain_timeout`] has passed, and stops
+/// the background thread.
+///
+/// As the runtime is private to the connection, this is also the way to connect from an
+/// application which isn't built on tokio, e.g. on async-std, without nesting runtimes.
+pub struct BlockingSocket {
+    addr_cell: AddrCell,
+    to_server_sender: mpsc::Sender<Box<[u8]>>,
+    to_client_receiver: mpsc::Receiver<Box<[u8]>>,
+    max_message_size: Arc<AtomicUsize>,
+    shutdown_sender: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BlockingSocket {
+    /// Connects like [`Socket::connect`], blocking until the server has answered
+    pub fn connect(server_url: &str) -> Result<Self, SocketConnectionError> {
+        Self::connect_with_config(server_url, SocketConfig::default())
+    }
+
+    /// Connects like [`Socket::connect_with_config`], blocking until the server has
+    /// answered. Fails with [`SocketConnectionError::Runtime`] if the runtime of the
+    /// connection can't be created
+    pub fn connect_with_config(
+        server_url: &str,
+        mut config: SocketConfig,
+    ) -> Result<Self, SocketConnectionError> {
+        // messages are only received through to_client_receiver
+        config.source_addrs = false;
+        config.payload_types = false;
+        let shutdown_timeout = config.drain_timeout() + SHUTDOWN_MARGIN;
+
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| SocketConnectionError::Runtime {
+                reason: err.to_string(),
+            })?;
+
+        let server_url = server_url.to_owned();
+        let (connected_sender, connected_receiver) = std_mpsc::sync_channel(1);
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let thread = thread::spawn(move || {
+            runtime.block_on(async move {
+                let (addr_cell, socket_io) =
+                    match Socket::connect_with_config(&server_url, config).await {
+                        Ok(connected) => connected,
+                        Err(error) => {
+                            let _ = connected_sender.send(Err(error));
+                            return;
+                        }
+                    };
+                let session = socket_io.session();
+                let SocketIo {
+                    to_server_sender,
+                    to_client_receiver,
+                    ..
+                } = socket_io;
+                let _ = connected_sender.send(Ok((
+                    addr_cell,
+                    to_server_sender,
+                    to_client_receiver,
+                    session.max_message_size_ref(),
+                )));
+
+                // drive the connection until the BlockingSocket is dropped
+                let _ = shutdown_receiver.await;
+                if timeout(shutdown_timeout, session.close()).await.is_err() {
+                    tracing::warn!("Timed out closing the connection");
+                }
+            });
+            // dropping the runtime here cancels the remaining tasks
+        });
+
+        let (addr_cell, to_server_sender, to_client_receiver, max_message_size) =
+            match connected_receiver.recv() {
+                Ok(connected) => connected?,
+                // the background thread panicked while connecting
+                Err(_) => match thread.join() {
+                    Err(payload) => panic::resume_unwind(payload),
+                    Ok(()) => unreachable!("the background thread exited without connecting"),
+                },
+            };
+
+        Ok(BlockingSocket {
+            addr_cell,
+            to_server_sender,
+            to_client_receiver,
+            max_message_size,
+            shutdown_sender: Some(shutdown_sender),
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the server's socket address, once it has been found
+    pub fn server_addr(&self) -> ServerAddr {
+        self.addr_cell.get()
+    }
+
+    /// Returns the largest message which can be sent, like [`SocketIo::max_message_size`]
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size.load(Ordering::SeqCst)
+    }
+
+    /// Sends a message, blocking while the outgoing queue is full. A message larger than
+    /// [`max_message_size`](Self::max_message_size) is rejected with
+    /// [`SocketConnectionError::MessageTooLarge`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if called within an asynchronous execution context.
+    pub fn send(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        check_message_size(message.len(), self.max_message_size())?;
+        self.to_server_sender
+            .blocking_send(message.into())
+            .map_err(|_| SocketConnectionError::Closed)
+    }
+
+    /// Blocks until a message is received. Returns `None` once the data channel has closed
+    ///
+    /// # Panics
+    ///
+    /// Panics if called within an asynchronous execution context.
+    pub fn recv(&mut self) -> Option<Box<[u8]>> {
+        self.to_client_receiver.blocking_recv()
+    }
+}
+
+impl Drop for BlockingSocket {
+    fn drop(&mut self) {
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                tracing::warn!("The background thread of BlockingSocket panicked");
+            }
+        }
+    }
+}
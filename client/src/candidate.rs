@@ -0,0 +1,198 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    error::CandidateError,
+    webrtc::{
+        api::setting_engine::CandidateRewriteFn,
+        ice::agent::agent_config::CandidatePreferenceFn,
+        ice_transport::{ice_candidate::RTCIceCandidate, ice_gatherer::OnLocalCandidateHdlrFn},
+        peer_connection::RTCPeerConnection,
+    },
+};
+
+// CandidateSender feeds a CandidateStream, until it is taken once gathering has completed
+pub(crate) type CandidateSender = Arc<Mutex<Option<mpsc::UnboundedSender<CandidateInfo>>>>;
+
+// RemoteCandidateFn is told how each candidate of the server was applied
+pub(crate) type RemoteCandidateFn =
+    Arc<Mutex<dyn FnMut(&str, Result<(), CandidateError>) + Send + 'static>>;
+
+/// A local ICE candidate, as it is about to be written into the offer
+///
+/// See [`SocketConfig::on_gathered_candidate`](crate::SocketConfig::on_gathered_candidate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CandidateInfo {
+    /// Transport protocol, e.g. `udp`. Changing it has no effect
+    pub protocol: String,
+    /// IP address, or mDNS host name, offered to the server
+    pub address: String,
+    /// Port offered to the server
+    pub port: u16,
+    /// Priority of the candidate, higher is preferred
+    pub priority: u32,
+    /// Foundation of the candidate, shared by candidates of the same base and type
+    pub foundation: String,
+}
+
+impl CandidateInfo {
+    fn from_rtc(candidate: &RTCIceCandidate) -> Self {
+        CandidateInfo {
+            protocol: candidate.protocol.to_string(),
+            address: candidate.address.clone(),
+            port: candidate.port,
+            priority: candidate.priority,
+            foundation: candidate.foundation.clone(),
+        }
+    }
+
+    fn apply_to(self, candidate: &mut RTCIceCandidate) {
+        candidate.address = self.address;
+        candidate.port = self.port;
+        candidate.priority = self.priority;
+        candidate.foundation = self.foundation;
+    }
+}
+
+/// The preferences the priority of a local ICE candidate is computed from, see
+/// [`SocketConfig::set_candidate_preference`](crate::SocketConfig::set_candidate_preference)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandidatePreference {
+    type_preference: u8,
+    local_preference: u16,
+}
+
+impl CandidatePreference {
+    /// The largest type preference, which host candidates have by default
+    pub const MAX_TYPE_PREFERENCE: u8 = 126;
+
+    /// Makes the preferences of a candidate, higher is preferred. The type preference
+    /// weighs more than any local preference: it defaults to 126 for host candidates, and is
+    /// 0 for relayed candidates, while the local preference defaults to 65535.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `type_preference` exceeds [`MAX_TYPE_PREFERENCE`](Self::MAX_TYPE_PREFERENCE),
+    /// which would take the priority out of the range ICE allows.
+    pub fn new(type_preference: u8, local_preference: u16) -> Self {
+        assert!(
+            type_preference <= Self::MAX_TYPE_PREFERENCE,
+            "type preference {} exceeds {}",
+            type_preference,
+            Self::MAX_TYPE_PREFERENCE
+        );
+        CandidatePreference {
+            type_preference,
+            local_preference,
+        }
+    }
+}
+
+// candidate_preference adapts a user callback to the ICE agent, which computes the priority
+// of each host candidate from the preferences returned for its IP
+pub(crate) fn candidate_preference<F>(f: F) -> CandidatePreferenceFn
+where
+    F: Fn(IpAddr) -> Option<CandidatePreference> + Send + Sync + 'static,
+{
+    Arc::new(move |ip| {
+        f(ip).map(|preference| (preference.type_preference, preference.local_preference))
+    })
+}
+
+/// The addresses of the candidate pair a connection goes through, see
+/// [`SocketIo::selected_candidate_pair`](crate::SocketIo::selected_candidate_pair)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct CandidatePair {
+    /// Address of the local candidate, which its ICE socket is bound to unless the address
+    /// is replaced with [`SocketConfig::set_nat_1to1_ips`](crate::SocketConfig::set_nat_1to1_ips)
+    pub local: SocketAddr,
+    /// Address of the server's candidate
+    pub remote: SocketAddr,
+}
+
+impl CandidatePair {
+    /// Makes a pair of the addresses, e.g. for one saved by an earlier run
+    pub fn new(local: SocketAddr, remote: SocketAddr) -> Self {
+        CandidatePair { local, remote }
+    }
+}
+
+/// The round trip time of an ICE connectivity check answered on the selected candidate
+/// pair, see [`SocketIo::rtt_history`](crate::SocketIo::rtt_history)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RttSample {
+    /// When the response to the check arrived
+    pub at: Instant,
+    /// Time from sending the check to its response
+    pub rtt: Duration,
+}
+
+// candidate_rewrite adapts a user callback to the ICE gatherer, which keeps the candidates
+// the callback returns
+pub(crate) fn candidate_rewrite<F>(f: F) -> CandidateRewriteFn
+where
+    F: FnMut(CandidateInfo) -> Option<CandidateInfo> + Send + 'static,
+{
+    let f = Mutex::new(f);
+    Arc::new(move |candidate: &mut RTCIceCandidate| {
+        let mut f = f.lock().expect("candidate rewrite lock poisoned");
+        match f(CandidateInfo::from_rtc(candidate)) {
+            Some(info) => {
+                info.apply_to(candidate);
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+// gathered_candidates adapts a CandidateSender to the ICE gatherer, ending the stream once
+// gathering has completed
+pub(crate) fn gathered_candidates(sender: CandidateSender) -> OnLocalCandidateHdlrFn {
+    Box::new(move |candidate: Option<RTCIceCandidate>| {
+        let mut sender = sender.lock().expect("candidate stream lock poisoned");
+        match candidate {
+            Some(candidate) => {
+                if let Some(sender) = &*sender {
+                    // the stream may have been dropped
+                    let _ = sender.send(CandidateInfo::from_rtc(&candidate));
+                }
+            }
+            None => *sender = None,
+        }
+        Box::pin(async {})
+    })
+}
+
+// add_remote_candidate adds a candidate signaled by the server to the peer connection. An
+// unreachable candidate is added all the same, as it does no harm
+pub(crate) async fn add_remote_candidate(
+    peer_connection: &RTCPeerConnection,
+    candidate: &str,
+) -> Result<(), CandidateError> {
+    let can_pair = peer_connection
+        .can_pair_ice_candidate(candidate)
+        .await
+        .map_err(|err| CandidateError::Invalid {
+            reason: err.to_string(),
+        })?;
+    peer_connection
+        .add_ice_candidate(candidate.to_owned())
+        .await
+        .map_err(|err| CandidateError::Rejected {
+            reason: err.to_string(),
+        })?;
+    if can_pair {
+        Ok(())
+    } else {
+        Err(CandidateError::Unreachable)
+    }
+}
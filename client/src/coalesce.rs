@@ -0,0 +1,214 @@
+// Coalesced datagrams use the framing documented on `SocketConfig::set_send_coalescing`
+
+use std::time::Duration;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use tokio::{
+    sync::mpsc,
+    time::{sleep_until, Instant},
+};
+use tracing::warn;
+
+use crate::queued::Queued;
+
+pub(crate) const FRAME_HEADER_SIZE: usize = 2;
+
+/// SendCoalescing holds how long and up to which size outgoing messages are gathered
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SendCoalescing {
+    pub(crate) window: Duration,
+    pub(crate) max_datagram_bytes: usize,
+}
+
+impl SendCoalescing {
+    /// Caps the datagrams at `max_message_size`, the largest message the data channel
+    /// takes, so that a datagram is sent before it grows too large to be written and is
+    /// dropped along with every message in it
+    pub(crate) fn within(self, max_message_size: usize) -> Self {
+        SendCoalescing {
+            max_datagram_bytes: self.max_datagram_bytes.min(max_message_size),
+            ..self
+        }
+    }
+}
+
+/// Frames `first`, then keeps framing messages from `receiver` until the window has passed
+/// or the next message would not fit. Returns the datagram and the message which did not
+/// fit, if any, which hasn't been taken yet. Cancelled messages are skipped, and a message
+/// with a deadline is left over, to be sent on its own with [`frame`]
+pub(crate) async fn coalesce<M: Queued>(
+    first: Box<[u8]>,
+    receiver: &mut mpsc::Receiver<M>,
+    coalescing: SendCoalescing,
+) -> (Option<Bytes>, Option<M>) {
+    let deadline = Instant::now() + coalescing.window;
+    let mut datagram = BytesMut::with_capacity(coalescing.max_datagram_bytes);
+    push_frame(&mut datagram, &first);
+
+    loop {
+        let message = tokio::select! {
+            message = receiver.recv() => message,
+            _ = sleep_until(deadline) => None,
+        };
+        let message = match message {
+            Some(message) => message,
+            None => break,
+        };
+
+        if message.deadline().is_some()
+            || !datagram.is_empty()
+                && datagram.len() + FRAME_HEADER_SIZE + message.size()
+                    > coalescing.max_datagram_bytes
+        {
+            return (Some(datagram.freeze()), Some(message));
+        }
+        if let Some(message) = message.take() {
+            push_frame(&mut datagram, &message);
+        }
+    }
+
+    if datagram.is_empty() {
+        (None, None)
+    } else {
+        (Some(datagram.freeze()), None)
+    }
+}
+
+/// Frames `message` in a datagram of its own, for a message SCTP may give up without
+/// taking others along. Returns `None` if the message is too large to be framed
+pub(crate) fn frame(message: &[u8]) -> Option<Bytes> {
+    let mut datagram = BytesMut::with_capacity(FRAME_HEADER_SIZE + message.len());
+    push_frame(&mut datagram, message);
+    (!datagram.is_empty()).then(|| datagram.freeze())
+}
+
+fn push_frame(datagram: &mut BytesMut, message: &[u8]) {
+    match u16::try_from(message.len()) {
+        Ok(length) => {
+            datagram.put_u16(length);
+            datagram.put_slice(message);
+        }
+        Err(_) => warn!(
+            "Dropping a message of {} bytes, which is too large to be coalesced",
+            message.len()
+        ),
+    }
+}
+
+/// Splits a coalesced datagram into its messages. A truncated trailing frame is dropped
+pub(crate) fn deframe(mut datagram: &[u8]) -> Vec<Box<[u8]>> {
+    let mut messages = Vec::new();
+    while datagram.len() >= FRAME_HEADER_SIZE {
+        let length = u16::from_be_bytes([datagram[0], datagram[1]]) as usize;
+        let frame = &datagram[FRAME_HEADER_SIZE..];
+        if frame.len() < length {
+            break;
+        }
+        messages.push(frame[..length].into());
+        datagram = &frame[length..];
+    }
+    if !datagram.is_empty() {
+        warn!(
+            "Dropping {} trailing bytes of a malformed coalesced datagram",
+            datagram.len()
+        );
+    }
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coalescing(max_datagram_bytes: usize) -> SendCoalescing {
+        SendCoalescing {
+            window: Duration::from_millis(50),
+            max_datagram_bytes,
+        }
+    }
+
+    fn queue(messages: &[&[u8]]) -> mpsc::Receiver<Box<[u8]>> {
+        let (sender, receiver) = mpsc::channel(messages.len().max(1));
+        for message in messages {
+            sender.try_send(Box::from(*message)).unwrap();
+        }
+        receiver
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let mut datagram = BytesMut::new();
+        for message in [&b"one"[..], b"", b"three"] {
+            push_frame(&mut datagram, message);
+        }
+        assert_eq!(datagram.len(), 3 * FRAME_HEADER_SIZE + 8);
+        let messages = deframe(&datagram);
+        assert_eq!(messages, [&b"one"[..], b"", b"three"].map(Box::from));
+    }
+
+    #[test]
+    fn zero_length_message_is_a_bare_header() {
+        assert_eq!(frame(b"").unwrap(), Bytes::from_static(&[0, 0]));
+        assert_eq!(
+            deframe(&[0, 0, 0, 0]),
+            [Box::from(&b""[..]), Box::from(&b""[..])]
+        );
+    }
+
+    #[test]
+    fn truncated_frame_is_dropped() {
+        // a length of 5 with only 3 bytes of the message
+        assert_eq!(
+            deframe(&[0, 2, b'o', b'k', 0, 5, 1, 2, 3]),
+            [Box::from(&b"ok"[..])]
+        );
+        // a lone byte of a header
+        assert_eq!(deframe(&[0, 2, b'o', b'k', 0]), [Box::from(&b"ok"[..])]);
+        assert!(deframe(&[0]).is_empty());
+        assert!(deframe(&[]).is_empty());
+    }
+
+    #[test]
+    fn u16_boundary() {
+        let largest = vec![7; u16::MAX as usize];
+        let datagram = frame(&largest).unwrap();
+        assert_eq!(datagram[..FRAME_HEADER_SIZE], [0xff, 0xff]);
+        assert_eq!(deframe(&datagram), [largest.into_boxed_slice()]);
+
+        assert_eq!(frame(&vec![7; u16::MAX as usize + 1]), None);
+        let mut datagram = BytesMut::new();
+        push_frame(&mut datagram, &vec![7; u16::MAX as usize + 1]);
+        assert!(datagram.is_empty());
+    }
+
+    #[tokio::test]
+    async fn datagram_is_sent_before_it_exceeds_the_max() {
+        let mut receiver = queue(&[b"bb", b"ccc"]);
+        // the first two frames take 7 bytes, and the third one would make it 12
+        let (datagram, leftover) =
+            coalesce(Box::from(&b"a"[..]), &mut receiver, coalescing(11)).await;
+        assert_eq!(
+            deframe(&datagram.unwrap()),
+            [&b"a"[..], b"bb"].map(Box::from)
+        );
+        assert_eq!(leftover.as_deref(), Some(&b"ccc"[..]));
+    }
+
+    #[tokio::test]
+    async fn datagram_fills_up_to_the_max() {
+        let mut receiver = queue(&[b"bb", b"ccc"]);
+        let (datagram, leftover) =
+            coalesce(Box::from(&b"a"[..]), &mut receiver, coalescing(12)).await;
+        assert_eq!(datagram.unwrap().len(), 12);
+        assert!(leftover.is_none());
+    }
+
+    #[tokio::test]
+    async fn max_message_size_caps_the_datagram() {
+        let mut receiver = queue(&[b"bb", b"ccc"]);
+        let coalescing = coalescing(1200).within(11);
+        let (datagram, leftover) = coalesce(Box::from(&b"a"[..]), &mut receiver, coalescing).await;
+        assert!(datagram.unwrap().len() <= 11);
+        assert_eq!(leftover.as_deref(), Some(&b"ccc"[..]));
+    }
+}
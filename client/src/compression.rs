@@ -0,0 +1,87 @@
+// Compressed messages use the framing documented on `SocketConfig::set_compression`
+
+use std::sync::Arc;
+
+use tracing::warn;
+
+const UNCOMPRESSED: u8 = 0;
+const COMPRESSED: u8 = 1;
+
+/// The size of the header byte prefixed to every message when compression is enabled
+pub(crate) const COMPRESSION_HEADER_SIZE: usize = 1;
+
+/// Compresses the payloads of data channel messages, e.g. with LZ4 or zstd
+///
+/// Set one with [`SocketConfig::set_compression`](crate::SocketConfig::set_compression).
+/// The server has to decompress with the same scheme. The `lz4` feature adds `Lz4`, an
+/// implementation in the LZ4 block format.
+///
+/// ```
+/// use webrtc_unreliable_client::Compression;
+///
+/// // stands in for a real codec, like lz4_flex's compress_prepend_size
+/// struct Identity;
+///
+/// impl Compression for Identity {
+///     fn compress(&self, input: &[u8]) -> Vec<u8> {
+///         input.to_vec()
+///     }
+///
+///     fn decompress(&self, input: &[u8]) -> Option<Vec<u8>> {
+///         Some(input.to_vec())
+///     }
+/// }
+/// ```
+pub trait Compression: Send + Sync {
+    /// Compresses `input`
+    fn compress(&self, input: &[u8]) -> Vec<u8>;
+    /// Decompresses what [`compress`](Self::compress) produced, or returns `None` if
+    /// `input` is malformed
+    fn decompress(&self, input: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Applies a [`Compression`] to messages of at least `min_size` bytes, and marks every
+/// message with whether it was compressed
+#[derive(Clone)]
+pub(crate) struct PayloadCompression {
+    pub(crate) codec: Arc<dyn Compression>,
+    pub(crate) min_size: usize,
+}
+
+impl PayloadCompression {
+    pub(crate) fn compress(&self, message: &[u8]) -> Vec<u8> {
+        if message.len() >= self.min_size {
+            let compressed = self.codec.compress(message);
+            // keep messages which don't compress as they are
+            if compressed.len() < message.len() {
+                return [&[COMPRESSED][..], &compressed].concat();
+            }
+        }
+        [&[UNCOMPRESSED][..], message].concat()
+    }
+
+    /// Returns the original message, or `None` if `message` is malformed
+    pub(crate) fn decompress(&self, message: &[u8]) -> Option<Vec<u8>> {
+        match message.split_first() {
+            Some((&UNCOMPRESSED, payload)) => Some(payload.to_vec()),
+            Some((&COMPRESSED, payload)) => {
+                let decompressed = self.codec.decompress(payload);
+                if decompressed.is_none() {
+                    warn!("Dropping a message which failed to decompress");
+                }
+                decompressed
+            }
+            Some((header, _)) => {
+                warn!(
+                    "Dropping a message with unknown compression header {}",
+                    header
+                );
+                None
+            }
+            None => {
+                warn!("Dropping an empty message without compression header");
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tokio::{
+    sync::mpsc,
+    time::{sleep_until, Instant},
+};
+
+use crate::webrtc::util::conn::conn_filtered::SendFilterFn;
+
+/// Simulates an unreliable network by dropping, duplicating and delaying messages between
+/// the application and the data channel, and by dropping sent packets. Meant for testing
+/// how an application copes with loss, and only available with the `network-conditioner`
+/// feature
+///
+/// Decisions are drawn from an RNG seeded with `seed`, so the same sequence of messages is
+/// dropped and duplicated on every run. Set it with
+/// [`SocketConfig::set_network_conditioner`](crate::SocketConfig::set_network_conditioner).
+#[derive(Debug, Clone, Copy)]
+pub struct NetworkConditioner {
+    pub(crate) seed: u64,
+    pub(crate) loss: f64,
+    pub(crate) duplication: f64,
+    pub(crate) latency: Duration,
+    pub(crate) packet_loss: f64,
+}
+
+// the DTLS content type of records carrying SCTP packets
+const APPLICATION_DATA: u8 = 23;
+
+impl NetworkConditioner {
+    /// Creates a conditioner which passes every message through unchanged
+    pub fn new(seed: u64) -> Self {
+        NetworkConditioner {
+            seed,
+            loss: 0.0,
+            duplication: 0.0,
+            latency: Duration::ZERO,
+            packet_loss: 0.0,
+        }
+    }
+
+    /// set_loss sets the probability, between 0 and 1, that a message is dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is outside of 0..=1.
+    pub fn set_loss(&mut self, probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "loss probability must be within 0..=1"
+        );
+        self.loss = probability;
+    }
+
+    /// set_duplication sets the probability, between 0 and 1, that a message which wasn't
+    /// dropped is delivered twice
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is outside of 0..=1.
+    pub fn set_duplication(&mut self, probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "duplication probability must be within 0..=1"
+        );
+        self.duplication = probability;
+    }
+
+    /// set_latency delays every message by `latency`, keeping their order
+    pub fn set_latency(&mut self, latency: Duration) {
+        self.latency = latency;
+    }
+
+    /// set_packet_loss sets the probability, between 0 and 1, that a UDP datagram carrying
+    /// data channel traffic is dropped on its way to the server. Unlike [`set_loss`](Self::set_loss),
+    /// which drops messages before they reach the data channel, this loses the SCTP packets
+    /// themselves, so messages sent with
+    /// [`SocketIo::send_reliable`](crate::SocketIo::send_reliable) are retransmitted and
+    /// still arrive, while unreliable ones may not. The handshakes aren't affected
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is outside of 0..=1.
+    pub fn set_packet_loss(&mut self, probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "packet loss probability must be within 0..=1"
+        );
+        self.packet_loss = probability;
+    }
+
+    // packet_filter returns whether to send a datagram, dropping DTLS application data
+    // records with the packet loss probability
+    pub(crate) fn packet_filter(self) -> SendFilterFn {
+        let rng = Mutex::new(StdRng::seed_from_u64(self.seed.wrapping_add(2)));
+        Arc::new(move |datagram| {
+            datagram.first() != Some(&APPLICATION_DATA)
+                || !rng
+                    .lock()
+                    .expect("packet loss RNG lock poisoned")
+                    .gen_bool(self.packet_loss)
+        })
+    }
+
+    // spawn_stage forwards messages from `receiver` to `sender` under the conditions, until
+    // either side has closed. Each direction gets its own stage, with a distinct `stream`
+    // so the two don't draw the same decisions
+    pub(crate) fn spawn_stage<T: Clone + Send + 'static>(
+        self,
+        stream: u64,
+        mut receiver: mpsc::Receiver<T>,
+        sender: mpsc::Sender<T>,
+    ) {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(stream));
+        tokio::spawn(async move {
+            let mut delayed: VecDeque<(Instant, T)> = VecDeque::new();
+            let mut receiver_open = true;
+            while receiver_open || !delayed.is_empty() {
+                let due = delayed.front().map(|(due, _)| *due);
+                tokio::select! {
+                    message = receiver.recv(), if receiver_open => {
+                        let message = match message {
+                            Some(message) => message,
+                            None => {
+                                receiver_open = false;
+                                continue;
+                            }
+                        };
+                        if rng.gen_bool(self.loss) {
+                            continue;
+                        }
+                        let due = Instant::now() + self.latency;
+                        if rng.gen_bool(self.duplication) {
+                            delayed.push_back((due, message.clone()));
+                        }
+                        delayed.push_back((due, message));
+                    }
+                    _ = sleep_until(due.unwrap_or_else(Instant::now)), if due.is_some() => {
+                        while let Some((due, _)) = delayed.front() {
+                            if *due > Instant::now() {
+                                break;
+                            }
+                            let (_, message) = delayed.pop_front().unwrap();
+                            if sender.send(message).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use crate::webrtc::sctp::association::Association;
+
+/// The congestion control state of the SCTP association carrying a connection, at the
+/// time it was taken
+///
+/// The unreliable and the reliable data channel share the association, so this covers the
+/// messages of both. See [`SocketIo::congestion`](crate::SocketIo::congestion).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CongestionInfo {
+    /// Congestion window, the most bytes allowed in flight
+    pub cwnd: usize,
+    /// Slow start threshold. While `cwnd` is below it, the window grows quickly
+    pub ssthresh: usize,
+    /// Bytes sent but not yet acknowledged by the server
+    pub flight_size: usize,
+    /// Bytes written to the data channels but not yet sent
+    pub pending: usize,
+    /// Receiver window of the server, the most bytes it can take in
+    pub peer_rwnd: usize,
+    /// Whether lost packets are being retransmitted, after which `cwnd` was halved
+    pub in_fast_recovery: bool,
+    /// Retransmission timeout, after which a packet still unacknowledged is sent again.
+    /// It follows the measured round trip time, within the bounds set with
+    /// [`SocketConfig::set_sctp_rto`](crate::SocketConfig::set_sctp_rto)
+    pub rto: Duration,
+    mtu: usize,
+}
+
+impl CongestionInfo {
+    pub(crate) async fn from_association(association: &Association) -> Self {
+        let ai = association.association_internal.lock().await;
+        CongestionInfo {
+            cwnd: ai.cwnd as usize,
+            ssthresh: ai.ssthresh as usize,
+            flight_size: ai.flight_size(),
+            pending: ai.pending_bytes(),
+            peer_rwnd: ai.peer_rwnd() as usize,
+            in_fast_recovery: ai.in_fast_recovery,
+            rto: Duration::from_millis(ai.rto_mgr.get_rto()),
+            mtu: ai.mtu as usize,
+        }
+    }
+
+    /// Returns whether sending is limited by congestion control: either data is waiting
+    /// while the congestion window can't take another full packet, or lost packets are
+    /// being recovered. A connection that stays congested while the application keeps
+    /// writing is sending faster than the path allows, so lowering the send rate keeps
+    /// the messages from queueing up
+    pub fn is_congested(&self) -> bool {
+        self.in_fast_recovery || (self.pending > 0 && self.flight_size + self.mtu > self.cwnd)
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::{Duration, Instant};
+
+use crate::dtls_certificate::DtlsCertificate;
+
+/// The DTLS certificate and ICE credentials a connection was offered with, for a
+/// reconnection to the same server to offer them again
+///
+/// See [`SocketIo::session_credentials`](crate::SocketIo::session_credentials) and
+/// [`SocketConfig::set_session_credentials`](crate::SocketConfig::set_session_credentials).
+/// They include the certificate's private key, so keep them in memory only.
+#[derive(Clone)]
+pub struct SessionCredentials {
+    pub(crate) certificate: DtlsCertificate,
+    pub(crate) ice_ufrag: String,
+    pub(crate) ice_pwd: String,
+    // when the credentials were first offered, which reusing them doesn't change
+    pub(crate) issued: Instant,
+}
+
+impl SessionCredentials {
+    /// The certificate presented in the DTLS handshake
+    pub fn certificate(&self) -> &DtlsCertificate {
+        &self.certificate
+    }
+
+    /// The ICE username fragment, as in the offer's `a=ice-ufrag` attribute
+    pub fn ice_ufrag(&self) -> &str {
+        &self.ice_ufrag
+    }
+
+    /// Time since the credentials were first offered, however often they were reused since
+    pub fn age(&self) -> Duration {
+        self.issued.elapsed()
+    }
+}
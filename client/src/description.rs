@@ -0,0 +1,166 @@
+use crate::webrtc::{
+    error::Error,
+    peer_connection::sdp::{
+        extract_fingerprint, get_mid_value, session_description::RTCSessionDescription,
+    },
+    sdp::description::session::SessionDescription,
+};
+
+/// The offer signaled to the server for a connection, with the parts a custom signaling
+/// format needs parsed out of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalDescription {
+    /// The SDP as sent to the server
+    pub sdp: String,
+    /// Value of `a=ice-ufrag`
+    pub ice_ufrag: String,
+    /// Value of `a=ice-pwd`
+    pub ice_pwd: String,
+    /// Value of `a=fingerprint`, e.g. `sha-256 3A:9F:...`
+    pub fingerprint: String,
+    /// The media sections, in order
+    pub media: Vec<MediaSection>,
+    /// Values of the `a=candidate` attributes of every media section
+    pub candidates: Vec<String>,
+}
+
+/// A media section of a [`LocalDescription`], the `m=` line and its `a=mid`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaSection {
+    /// Media type, `application` for the data channel
+    pub kind: String,
+    /// Value of `a=mid`
+    pub mid: Option<String>,
+    /// Transport port
+    pub port: isize,
+    /// Transport protocol, e.g. `UDP/DTLS/SCTP`
+    pub protocol: String,
+    /// Media formats, e.g. `webrtc-datachannel`
+    pub formats: Vec<String>,
+}
+
+impl LocalDescription {
+    pub(crate) fn parse(description: &RTCSessionDescription) -> Result<Self, Error> {
+        let parsed = parsed(description)?;
+
+        let (fingerprint, hash) = extract_fingerprint(&parsed)?;
+        let ice_ufrag =
+            attribute(&parsed, "ice-ufrag").ok_or(Error::ErrSessionDescriptionMissingIceUfrag)?;
+        let ice_pwd =
+            attribute(&parsed, "ice-pwd").ok_or(Error::ErrSessionDescriptionMissingIcePwd)?;
+
+        let media = parsed
+            .media_descriptions
+            .iter()
+            .map(|media| MediaSection {
+                kind: media.media_name.media.clone(),
+                mid: get_mid_value(media).cloned(),
+                port: media.media_name.port.value,
+                protocol: media.media_name.protos.join("/"),
+                formats: media.media_name.formats.clone(),
+            })
+            .collect();
+        let candidates = parsed
+            .media_descriptions
+            .iter()
+            .flat_map(|media| &media.attributes)
+            .filter(|attribute| attribute.is_ice_candidate())
+            .filter_map(|attribute| attribute.value.clone())
+            .collect();
+
+        Ok(LocalDescription {
+            sdp: description.sdp.clone(),
+            ice_ufrag,
+            ice_pwd,
+            fingerprint: format!("{} {}", hash, fingerprint),
+            media,
+            candidates,
+        })
+    }
+}
+
+// attribute returns the value of a session level attribute, or else of the first media
+// section which has it
+fn attribute(description: &SessionDescription, key: &str) -> Option<String> {
+    if let Some(value) = description.attribute(key) {
+        return Some(value.clone());
+    }
+    description
+        .media_descriptions
+        .iter()
+        .find_map(|media| media.attribute(key).flatten())
+        .map(str::to_owned)
+}
+
+// parsed returns the parsed SDP of a description, parsing it unless that's been done
+fn parsed(description: &RTCSessionDescription) -> Result<SessionDescription, Error> {
+    match &description.parsed {
+        Some(parsed) => Ok(parsed.clone()),
+        None => description.unmarshal(),
+    }
+}
+
+// check_answer compares the server's answer with the offer, for the mismatches which would
+// stall the handshake rather than fail it: its media sections have to answer those of the
+// offer, with a DTLS setup role answering the offer's `actpass`, and it has to carry a
+// fingerprint and ICE credentials. Returns the first mismatch found
+pub(crate) fn check_answer(
+    offer: &RTCSessionDescription,
+    answer: &RTCSessionDescription,
+) -> Result<(), String> {
+    let offer = parsed(offer).map_err(|err| format!("the offer can't be parsed: {}", err))?;
+    let answer = parsed(answer).map_err(|err| format!("the answer can't be parsed: {}", err))?;
+
+    if answer.media_descriptions.len() != offer.media_descriptions.len() {
+        return Err(format!(
+            "the answer has {} media sections, the offer {}",
+            answer.media_descriptions.len(),
+            offer.media_descriptions.len()
+        ));
+    }
+    let session_setup = answer.attribute("setup").map(String::as_str);
+    for (i, (offered, answered)) in offer
+        .media_descriptions
+        .iter()
+        .zip(&answer.media_descriptions)
+        .enumerate()
+    {
+        if answered.media_name.media != offered.media_name.media {
+            return Err(format!(
+                "media section {} is `{}` in the answer and `{}` in the offer",
+                i, answered.media_name.media, offered.media_name.media
+            ));
+        }
+        if answered.media_name.port.value == 0 {
+            return Err(format!("media section {} is rejected by the answer", i));
+        }
+        if let (Some(offered_mid), Some(answered_mid)) =
+            (get_mid_value(offered), get_mid_value(answered))
+        {
+            if answered_mid != offered_mid {
+                return Err(format!(
+                    "media section {} has mid `{}` in the answer and `{}` in the offer",
+                    i, answered_mid, offered_mid
+                ));
+            }
+        }
+        // a missing role defaults to active
+        match answered.attribute("setup").flatten().or(session_setup) {
+            None | Some("active") | Some("passive") => {}
+            Some(setup) => {
+                return Err(format!(
+                    "media section {} has DTLS setup role `{}`, which doesn't answer `actpass`",
+                    i, setup
+                ))
+            }
+        }
+    }
+
+    extract_fingerprint(&answer).map_err(|err| format!("the answer's fingerprint: {}", err))?;
+    for key in ["ice-ufrag", "ice-pwd"] {
+        if attribute(&answer, key).is_none() {
+            return Err(format!("the answer has no a={}", key));
+        }
+    }
+    Ok(())
+}
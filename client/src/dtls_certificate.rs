@@ -0,0 +1,60 @@
+use rcgen::KeyPair;
+
+use crate::{
+    error::CertificateError,
+    webrtc::{
+        dtls_transport::dtls_fingerprint::RTCDtlsFingerprint,
+        peer_connection::certificate::RTCCertificate,
+    },
+};
+
+/// A DTLS certificate and its private key, presented to the server instead of a certificate
+/// generated for each connection
+///
+/// See [`SocketConfig::set_certificate`](crate::SocketConfig::set_certificate).
+#[derive(Clone)]
+pub struct DtlsCertificate {
+    pub(crate) certificate: RTCCertificate,
+    fingerprint: String,
+}
+
+impl DtlsCertificate {
+    /// Reads an X.509 certificate and its PKCS#8 private key, both DER encoded. The key
+    /// may be ECDSA P-256, Ed25519 or RSA.
+    ///
+    /// Fails if either can't be parsed, or the certificate isn't issued for the key.
+    pub fn from_der(certificate: &[u8], private_key: &[u8]) -> Result<Self, CertificateError> {
+        let key_pair = KeyPair::from_der(private_key)
+            .map_err(|err| CertificateError::InvalidPrivateKey(err.to_string()))?;
+        let (_, x509_certificate) = x509_parser::parse_x509_certificate(certificate)
+            .map_err(|err| CertificateError::InvalidCertificate(err.to_string()))?;
+        if x509_certificate
+            .tbs_certificate
+            .subject_pki
+            .subject_public_key
+            .data
+            != key_pair.public_key_raw()
+        {
+            return Err(CertificateError::KeyMismatch);
+        }
+
+        let certificate = RTCCertificate::from_existing(&key_pair, certificate.to_vec())
+            .map_err(|err| CertificateError::InvalidPrivateKey(err.to_string()))?;
+        Ok(Self::from_rtc(certificate))
+    }
+
+    // from_rtc wraps the certificate a connection presented, e.g. one generated for it
+    pub(crate) fn from_rtc(certificate: RTCCertificate) -> Self {
+        DtlsCertificate {
+            fingerprint: RTCDtlsFingerprint::from_der(&certificate.certificate.certificate[0].0)
+                .to_string(),
+            certificate,
+        }
+    }
+
+    /// The fingerprint of the certificate as it appears in the offer, formatted like an SDP
+    /// `a=fingerprint` value, e.g. `sha-256 3A:9F:...`
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+
+use crate::webrtc::{
+    dtls::{
+        conn::DTLSConn,
+        record_layer::record_layer_header::{PROTOCOL_VERSION1_0, PROTOCOL_VERSION1_2},
+    },
+    dtls_transport::dtls_fingerprint::RTCDtlsFingerprint,
+};
+
+/// Parameters negotiated by the DTLS handshake of a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DtlsInfo {
+    /// Protocol version, e.g. `DTLS 1.2`
+    pub protocol_version: String,
+    /// IANA name of the cipher suite, e.g. `TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256`
+    pub cipher_suite: String,
+    /// Fingerprint of the certificate presented by the server, formatted like an SDP
+    /// `a=fingerprint` value, e.g. `sha-256 3A:9F:...`
+    pub remote_fingerprint: Option<String>,
+}
+
+impl DtlsInfo {
+    pub(crate) async fn from_conn(conn: &DTLSConn) -> Option<Self> {
+        let cipher_suite_id = conn.cipher_suite_id().await?;
+        let protocol_version = match conn.protocol_version() {
+            PROTOCOL_VERSION1_0 => "DTLS 1.0".to_owned(),
+            PROTOCOL_VERSION1_2 => "DTLS 1.2".to_owned(),
+            version => format!("{:?}", version),
+        };
+        Some(DtlsInfo {
+            protocol_version,
+            cipher_suite: cipher_suite_id.to_string(),
+            remote_fingerprint: conn
+                .peer_certificates()
+                .first()
+                .map(|certificate| RTCDtlsFingerprint::from_der(certificate).to_string()),
+        })
+    }
+}
+
+// DtlsInfoCell
+#[derive(Clone, Default)]
+pub(crate) struct DtlsInfoCell {
+    inner: Arc<Mutex<Option<DtlsInfo>>>,
+    // of the certificate generated for the current peer connection
+    local_fingerprint: Arc<Mutex<String>>,
+}
+
+impl DtlsInfoCell {
+    pub(crate) fn set_local_fingerprint(&self, fingerprint: String) {
+        *self
+            .local_fingerprint
+            .lock()
+            .expect("local fingerprint lock poisoned") = fingerprint;
+    }
+
+    pub(crate) fn local_fingerprint(&self) -> String {
+        self.local_fingerprint
+            .lock()
+            .expect("local fingerprint lock poisoned")
+            .clone()
+    }
+
+    pub(crate) fn set(&self, info: DtlsInfo) {
+        *self.inner.lock().expect("DTLS info lock poisoned") = Some(info);
+    }
+
+    pub(crate) fn get(&self) -> Option<DtlsInfo> {
+        self.inner.lock().expect("DTLS info lock poisoned").clone()
+    }
+}
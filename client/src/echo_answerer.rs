@@ -0,0 +1,509 @@
+// A minimal ICE-lite answerer in the manner of webrtc-unreliable, built from the crate's own
+// DTLS and SCTP stacks. It answers every offer with the same credentials and certificate,
+// and tells its sessions apart by the peer's address
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use rcgen::KeyPair;
+use tinyjson::JsonValue;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, UdpSocket},
+    sync::{mpsc, Mutex},
+};
+use tokio_util::sync::{CancellationToken, DropGuard};
+use tracing::{debug, warn};
+
+use crate::webrtc::{
+    data_channel::internal::message::{
+        message_channel_ack::DataChannelAck, message_type::MessageType, Message,
+    },
+    dtls::{cipher_suite::PSK_CIPHER_SUITES, config::Config as DtlsConfig, conn::DTLSConn},
+    peer_connection::{certificate::RTCCertificate, math_rand_alpha},
+    sctp::{
+        association::{Association, Config as SctpConfig, DEFAULT_SCTP_PORT},
+        chunk::chunk_payload_data::PayloadProtocolIdentifier,
+        stream::Stream,
+        timer::rtx_timer::RtoConfig,
+    },
+    stun::{
+        fingerprint::FINGERPRINT,
+        integrity::MessageIntegrity,
+        message::{is_message, Message as StunMessage, BINDING_REQUEST, BINDING_SUCCESS},
+        xoraddr::XorMappedAddress,
+    },
+    util::{marshal::*, Conn},
+};
+
+const RECEIVE_MTU: usize = 8192;
+const MAX_MESSAGE_SIZE: usize = 65536;
+const SESSION_CHANNEL_SIZE: usize = 64;
+
+/// A server which echoes every data channel message back to the client, for testing a
+/// [`Socket`](crate::Socket) without a webrtc-unreliable server. Only available with the
+/// `echo-answerer` feature
+///
+/// It listens on the loopback interface and serves the signaling endpoint at
+/// [`url`](Self::url). Dropping it stops the server.
+pub struct EchoAnswerer {
+    url: String,
+    received_messages: Arc<AtomicU64>,
+    _shutdown: DropGuard,
+}
+
+impl EchoAnswerer {
+    /// Starts the server on the current tokio runtime
+    pub async fn start() -> io::Result<Self> {
+        Self::start_with(None).await
+    }
+
+    /// Starts the server like [`start`](Self::start), authenticating the DTLS handshake
+    /// with `key` for clients presenting `identity`, as set with
+    /// [`SocketConfig::set_dtls_psk`](crate::SocketConfig::set_dtls_psk), instead of its
+    /// certificate. Clients which don't are refused
+    pub async fn start_with_psk(
+        identity: impl Into<Vec<u8>>,
+        key: impl Into<Vec<u8>>,
+    ) -> io::Result<Self> {
+        Self::start_with(Some((identity.into(), key.into()))).await
+    }
+
+    async fn start_with(psk: Option<(Vec<u8>, Vec<u8>)>) -> io::Result<Self> {
+        let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let http_listener = TcpListener::bind((localhost, 0)).await?;
+        let udp_socket = Arc::new(UdpSocket::bind((localhost, 0)).await?);
+
+        let certificate = KeyPair::generate(&rcgen::PKCS_ECDSA_P256_SHA256)
+            .map_err(crate::webrtc::error::Error::from)
+            .and_then(RTCCertificate::from_key_pair)
+            .map_err(|err| io::Error::other(err.to_string()))?;
+        let fingerprint = certificate
+            .get_fingerprints()
+            .ok()
+            .and_then(|fingerprints| fingerprints.into_iter().next())
+            .map(|fingerprint| fingerprint.value)
+            .unwrap_or_default();
+
+        let answerer = Arc::new(Answerer {
+            udp_addr: udp_socket.local_addr()?,
+            ufrag: math_rand_alpha(16),
+            pwd: math_rand_alpha(32),
+            fingerprint,
+        });
+        let url = format!("http://{}/rtc_session", http_listener.local_addr()?);
+
+        let shutdown = CancellationToken::new();
+        tokio::spawn(http_loop(
+            http_listener,
+            Arc::clone(&answerer),
+            shutdown.clone(),
+        ));
+        let dtls_config = match psk {
+            // the answer still carries the fingerprint, as SDP requires one
+            Some((identity, key)) => DtlsConfig {
+                cipher_suites: PSK_CIPHER_SUITES.to_vec(),
+                psk: Some(Arc::new(move |client_identity: &[u8]| {
+                    if client_identity == identity {
+                        Ok(key.clone())
+                    } else {
+                        Err(crate::webrtc::dtls::Error::Other(
+                            "unknown PSK identity".to_owned(),
+                        ))
+                    }
+                })),
+                ..Default::default()
+            },
+            None => DtlsConfig {
+                certificates: vec![certificate.certificate],
+                ..Default::default()
+            },
+        };
+        let received_messages = Arc::new(AtomicU64::new(0));
+        tokio::spawn(udp_loop(
+            udp_socket,
+            answerer,
+            dtls_config,
+            Arc::clone(&received_messages),
+            shutdown.clone(),
+        ));
+
+        Ok(EchoAnswerer {
+            url,
+            received_messages,
+            _shutdown: shutdown.drop_guard(),
+        })
+    }
+
+    /// Returns the url to connect a [`Socket`](crate::Socket) to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns how many data channel messages the server has received, over all sessions
+    pub fn received_messages(&self) -> u64 {
+        self.received_messages.load(Ordering::SeqCst)
+    }
+}
+
+// Answerer holds what goes into every answer
+struct Answerer {
+    udp_addr: SocketAddr,
+    ufrag: String,
+    pwd: String,
+    fingerprint: String,
+}
+
+impl Answerer {
+    fn answer(&self, offer: &str) -> String {
+        let mid = offer
+            .lines()
+            .find_map(|line| line.strip_prefix("a=mid:"))
+            .unwrap_or("0")
+            .trim();
+        let ip = self.udp_addr.ip();
+        let port = self.udp_addr.port();
+
+        let sdp = [
+            "v=0".to_owned(),
+            format!("o=- {} 1 IN IP4 {}", rand::random::<u32>(), ip),
+            "s=-".to_owned(),
+            format!("c=IN IP4 {}", ip),
+            "t=0 0".to_owned(),
+            "a=ice-lite".to_owned(),
+            format!("a=ice-ufrag:{}", self.ufrag),
+            format!("a=ice-pwd:{}", self.pwd),
+            format!("m=application {} UDP/DTLS/SCTP webrtc-datachannel", port),
+            format!("a=fingerprint:sha-256 {}", self.fingerprint),
+            "a=ice-options:trickle".to_owned(),
+            "a=setup:passive".to_owned(),
+            format!("a=mid:{}", mid),
+            format!("a=sctp-port:{}", DEFAULT_SCTP_PORT),
+            String::new(),
+        ]
+        .join("\r\n");
+        let candidate = format!(
+            "candidate:1 1 UDP {} {} {} typ host",
+            2_130_706_431u32, ip, port
+        );
+
+        let object = |fields: Vec<(&str, JsonValue)>| {
+            JsonValue::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key.to_owned(), value))
+                    .collect::<HashMap<_, _>>(),
+            )
+        };
+        let response = object(vec![
+            (
+                "answer",
+                object(vec![
+                    ("sdp", JsonValue::String(sdp)),
+                    ("type", JsonValue::String("answer".to_owned())),
+                ]),
+            ),
+            (
+                "candidate",
+                object(vec![
+                    ("candidate", JsonValue::String(candidate)),
+                    ("sdpMLineIndex", JsonValue::Number(0.0)),
+                    ("sdpMid", JsonValue::String(mid.to_owned())),
+                ]),
+            ),
+        ]);
+        response
+            .stringify()
+            .expect("the answer contains no unrepresentable numbers")
+    }
+}
+
+async fn http_loop(listener: TcpListener, answerer: Arc<Answerer>, shutdown: CancellationToken) {
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    warn!("echo answerer: failed to accept a signaling connection: {}", err);
+                    continue;
+                }
+            },
+            _ = shutdown.cancelled() => return,
+        };
+        let answerer = Arc::clone(&answerer);
+        tokio::spawn(async move {
+            if let Err(err) = handle_offer(stream, &answerer).await {
+                warn!("echo answerer: failed to answer an offer: {}", err);
+            }
+        });
+    }
+}
+
+// handle_offer reads a single HTTP request carrying the offer and responds with the answer
+async fn handle_offer(mut stream: TcpStream, answerer: &Answerer) -> io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        request.extend_from_slice(&buf[..n]);
+        if let Some(position) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&request[..header_end]);
+    let content_length = headers
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while request.len() < header_end + content_length {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let offer = String::from_utf8_lossy(&request[header_end..header_end + content_length]);
+    let body = answerer.answer(&offer);
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+// udp_loop answers STUN binding requests itself and hands every other packet to the
+// session of the peer it came from, starting a session for a new peer
+async fn udp_loop(
+    socket: Arc<UdpSocket>,
+    answerer: Arc<Answerer>,
+    dtls_config: DtlsConfig,
+    received_messages: Arc<AtomicU64>,
+    shutdown: CancellationToken,
+) {
+    let mut sessions: HashMap<SocketAddr, mpsc::Sender<Vec<u8>>> = HashMap::new();
+    let mut buf = vec![0u8; RECEIVE_MTU];
+    loop {
+        let (n, remote) = tokio::select! {
+            received = socket.recv_from(&mut buf) => match received {
+                Ok(received) => received,
+                Err(err) => {
+                    warn!("echo answerer: failed to receive: {}", err);
+                    continue;
+                }
+            },
+            _ = shutdown.cancelled() => return,
+        };
+        let packet = &buf[..n];
+
+        if is_message(packet) {
+            answer_binding_request(&socket, &answerer.pwd, packet, remote).await;
+            continue;
+        }
+
+        if let Some(sender) = sessions.get(&remote) {
+            match sender.try_send(packet.to_vec()) {
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    sessions.remove(&remote);
+                }
+                // dropped when the session falls behind, as the network would
+                _ => continue,
+            }
+        }
+
+        let (sender, receiver) = mpsc::channel(SESSION_CHANNEL_SIZE);
+        let _ = sender.try_send(packet.to_vec());
+        sessions.insert(remote, sender);
+        let conn = Arc::new(SessionConn {
+            socket: Arc::clone(&socket),
+            remote,
+            packets: Mutex::new(receiver),
+            closed: CancellationToken::new(),
+        });
+        let dtls_config = dtls_config.clone();
+        let received_messages = Arc::clone(&received_messages);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                result = run_session(conn, dtls_config, received_messages) => {
+                    if let Err(err) = result {
+                        debug!("echo answerer: session with {} ended: {}", remote, err);
+                    }
+                }
+                _ = shutdown.cancelled() => {}
+            }
+        });
+    }
+}
+
+async fn answer_binding_request(socket: &UdpSocket, pwd: &str, packet: &[u8], remote: SocketAddr) {
+    let mut request = StunMessage {
+        raw: packet.to_vec(),
+        ..StunMessage::default()
+    };
+    if request.decode().is_err() || request.typ != BINDING_REQUEST {
+        return;
+    }
+    let integrity = MessageIntegrity::new_short_term_integrity(pwd.to_owned());
+    if integrity.check(&mut request).is_err() {
+        debug!(
+            "echo answerer: binding request from {} failed integrity check",
+            remote
+        );
+        return;
+    }
+
+    let mut response = StunMessage::new();
+    let result = response.build(&[
+        Box::new(request.clone()),
+        Box::new(BINDING_SUCCESS),
+        Box::new(XorMappedAddress {
+            ip: remote.ip(),
+            port: remote.port(),
+        }),
+        Box::new(integrity),
+        Box::new(FINGERPRINT),
+    ]);
+    if result.is_ok() {
+        let _ = socket.send_to(&response.raw, remote).await;
+    }
+}
+
+// run_session accepts the DTLS and SCTP handshakes of one peer and echoes its streams
+async fn run_session(
+    conn: Arc<SessionConn>,
+    dtls_config: DtlsConfig,
+    received_messages: Arc<AtomicU64>,
+) -> crate::webrtc::error::Result<()> {
+    let dtls_conn = DTLSConn::new(conn, dtls_config, false, None).await?;
+    let association = Association::server(SctpConfig {
+        net_conn: Arc::new(dtls_conn),
+        max_receive_buffer_size: 0,
+        max_message_size: 0,
+        name: "echo-answerer".to_owned(),
+        // a server association takes its ports from the client's INIT
+        local_port: DEFAULT_SCTP_PORT,
+        remote_port: DEFAULT_SCTP_PORT,
+        rto: RtoConfig::default(),
+        loss_events: None,
+        up_events: None,
+        heartbeat_acks: None,
+    })
+    .await?;
+
+    while let Some(stream) = association.accept_stream().await {
+        let received_messages = Arc::clone(&received_messages);
+        tokio::spawn(async move {
+            if let Err(err) = echo_stream(stream, &received_messages).await {
+                debug!("echo answerer: stream closed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+// echo_stream acknowledges the data channel opened on the stream and echoes its messages
+async fn echo_stream(
+    stream: Arc<Stream>,
+    received_messages: &AtomicU64,
+) -> crate::webrtc::error::Result<()> {
+    let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
+    loop {
+        let (n, ppi) = match stream.read_sctp(&mut buf).await {
+            Ok(read) => read,
+            Err(err) => {
+                // when the client resets its stream, reset ours too, as data channel peers do
+                if stream.reset_by_peer.load(Ordering::SeqCst) {
+                    stream.shutdown_write().await?;
+                }
+                return Err(err.into());
+            }
+        };
+        if ppi == PayloadProtocolIdentifier::Dcep {
+            if MessageType::unmarshal(&mut &buf[..n])? == MessageType::DataChannelOpen {
+                let ack = Message::DataChannelAck(DataChannelAck {}).marshal()?;
+                stream
+                    .write_sctp(&ack, PayloadProtocolIdentifier::Dcep)
+                    .await?;
+            }
+            continue;
+        }
+        received_messages.fetch_add(1, Ordering::SeqCst);
+        stream
+            .write_sctp(&Bytes::copy_from_slice(&buf[..n]), ppi)
+            .await?;
+    }
+}
+
+// SessionConn is the connection to one peer over the shared UDP socket
+struct SessionConn {
+    socket: Arc<UdpSocket>,
+    remote: SocketAddr,
+    packets: Mutex<mpsc::Receiver<Vec<u8>>>,
+    closed: CancellationToken,
+}
+
+type UtilResult<T> = std::result::Result<T, crate::webrtc::util::Error>;
+
+#[async_trait]
+impl Conn for SessionConn {
+    async fn connect(&self, _addr: SocketAddr) -> UtilResult<()> {
+        Err(io::Error::other("Not applicable").into())
+    }
+
+    async fn recv(&self, buf: &mut [u8]) -> UtilResult<usize> {
+        let packet = tokio::select! {
+            packet = async { self.packets.lock().await.recv().await } => packet,
+            _ = self.closed.cancelled() => None,
+        }
+        .ok_or(crate::webrtc::util::Error::ErrClosedListener)?;
+        if packet.len() > buf.len() {
+            return Err(crate::webrtc::util::Error::ErrBufferShort);
+        }
+        buf[..packet.len()].copy_from_slice(&packet);
+        Ok(packet.len())
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> UtilResult<(usize, SocketAddr)> {
+        let n = self.recv(buf).await?;
+        Ok((n, self.remote))
+    }
+
+    async fn send(&self, buf: &[u8]) -> UtilResult<usize> {
+        Ok(self.socket.send_to(buf, self.remote).await?)
+    }
+
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> UtilResult<usize> {
+        Ok(self.socket.send_to(buf, target).await?)
+    }
+
+    async fn local_addr(&self) -> UtilResult<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        Some(self.remote)
+    }
+
+    async fn close(&self) -> UtilResult<()> {
+        self.closed.cancel();
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use std::net::IpAddr;
+
+use thiserror::Error;
+
+use crate::{
+    timings::HandshakePhase,
+    webrtc::{data_channel::internal::Error as DataChannelInternalError, sctp::Error as SctpError},
+};
+
+/// An error establishing or using a connection
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SocketConnectionError {
+    /// The server url can't be used for signaling
+    #[error("invalid server url {url:?}: {reason}")]
+    InvalidServerUrl { url: String, reason: String },
+    /// The configured bind address can't be used for the ICE UDP sockets
+    #[error("invalid bind address {addr}: {reason}")]
+    InvalidBindAddress { addr: IpAddr, reason: String },
+    /// The server refused the offer, or its answer can't be read
+    #[error("signaling failed: {reason}")]
+    Signaling { reason: String },
+    /// The server's answer doesn't match the offer, e.g. it lacks the data channel's media
+    /// section, a DTLS fingerprint or ICE credentials, so the handshake could never
+    /// complete. `reason` describes the mismatch
+    #[error("incompatible answer: {reason}")]
+    IncompatibleAnswer { reason: String },
+    /// The external IPs set with
+    /// [`SocketConfig::set_nat_1to1_ips`](crate::SocketConfig::set_nat_1to1_ips) can't be
+    /// used
+    #[error("invalid 1:1 NAT IPs: {reason}")]
+    InvalidNat1to1Ips { reason: String },
+    /// The proxy set with
+    /// [`SocketConfig::set_signaling_proxy`](crate::SocketConfig::set_signaling_proxy) can't
+    /// be used
+    #[error("invalid signaling proxy {url:?}: {reason}")]
+    InvalidSignalingProxy { url: String, reason: String },
+    /// The `User-Agent` set with
+    /// [`SocketConfig::set_user_agent`](crate::SocketConfig::set_user_agent) isn't a valid
+    /// header value, e.g. because it contains a line break
+    #[error("invalid user agent {user_agent:?}: {reason}")]
+    InvalidUserAgent { user_agent: String, reason: String },
+    /// The method set with
+    /// [`SocketConfig::set_signaling_request`](crate::SocketConfig::set_signaling_request)
+    /// isn't a valid HTTP method
+    #[error("invalid signaling method {method:?}: {reason}")]
+    InvalidSignalingMethod { method: String, reason: String },
+    /// The pacing set with
+    /// [`SocketConfig::set_ice_checks`](crate::SocketConfig::set_ice_checks) is out of range
+    #[error("invalid ICE checks: {reason}")]
+    InvalidIceChecks { reason: String },
+    /// The RTO bounds set with
+    /// [`SocketConfig::set_sctp_rto`](crate::SocketConfig::set_sctp_rto) are out of order
+    #[error("invalid SCTP RTO: {reason}")]
+    InvalidSctpRto { reason: String },
+    /// The MTU set with [`SocketConfig::set_dtls_mtu`](crate::SocketConfig::set_dtls_mtu)
+    /// is out of range
+    #[error("invalid DTLS MTU of {mtu} bytes: {reason}")]
+    InvalidDtlsMtu { mtu: usize, reason: String },
+    /// The value set with [`SocketConfig::set_dscp`](crate::SocketConfig::set_dscp) doesn't
+    /// fit in the six bits of a DSCP
+    #[error("invalid DSCP {dscp}: DSCP values are six bits wide")]
+    InvalidDscp { dscp: u8 },
+    /// The port set with [`SocketConfig::set_sctp_port`](crate::SocketConfig::set_sctp_port)
+    /// is reserved
+    #[error("invalid SCTP port {port}: SCTP reserves port 0")]
+    InvalidSctpPort { port: u16 },
+    /// The cap set with
+    /// [`SocketConfig::set_reliable_max_in_flight`](crate::SocketConfig::set_reliable_max_in_flight)
+    /// allows no message in flight
+    #[error("invalid reliable messages in flight: at least one must be allowed")]
+    InvalidReliableMaxInFlight,
+    /// A size set with
+    /// [`SocketConfig::set_udp_recv_buffer_size`](crate::SocketConfig::set_udp_recv_buffer_size)
+    /// or
+    /// [`SocketConfig::set_udp_send_buffer_size`](crate::SocketConfig::set_udp_send_buffer_size)
+    /// is zero
+    #[error("invalid UDP buffer size: {reason}")]
+    InvalidUdpBufferSize { reason: String },
+    /// The bounds set with
+    /// [`SocketConfig::set_signaling_retry`](crate::SocketConfig::set_signaling_retry) leave
+    /// no attempt or are out of order, or a status set with
+    /// [`SocketConfig::set_signaling_retry_statuses`](crate::SocketConfig::set_signaling_retry_statuses)
+    /// isn't a valid HTTP status code
+    #[error("invalid signaling retry: {reason}")]
+    InvalidSignalingRetry { reason: String },
+    /// The runtime driving a [`BlockingSocket`](crate::BlockingSocket) couldn't be created
+    #[error("cannot build the runtime: {reason}")]
+    Runtime { reason: String },
+    /// The connection has shut down for good, so it cannot be re-established
+    #[error("the connection has been closed")]
+    Closed,
+    /// The send direction was closed with [`SocketIo::close_send`](crate::SocketIo::close_send)
+    #[error("the send direction has been closed")]
+    SendClosed,
+    /// The message is larger than the maximum message size of the connection
+    #[error("message of {size} bytes exceeds the maximum message size of {max} bytes")]
+    MessageTooLarge { size: usize, max: usize },
+    /// More bytes are buffered for sending than the send buffer threshold allows, or the
+    /// outgoing queue is full, so the message was not sent
+    #[error("the send buffer is full")]
+    WouldBlock,
+    /// [`SocketIo::send_reliable`](crate::SocketIo::send_reliable) was called without
+    /// [`SocketConfig::set_reliable_channel`](crate::SocketConfig::set_reliable_channel)
+    #[error("the reliable data channel is not enabled")]
+    NoReliableChannel,
+    /// [`SocketIo::send_large`](crate::SocketIo::send_large) or
+    /// [`SocketIo::recv_large`](crate::SocketIo::recv_large) was called without
+    /// [`SocketConfig::set_large_transfers`](crate::SocketConfig::set_large_transfers)
+    #[error("the large transfer data channel is not enabled")]
+    NoLargeTransfers,
+    /// The connection attempt was aborted through a
+    /// [`ConnectAbortHandle`](crate::ConnectAbortHandle)
+    #[error("the connection attempt was aborted")]
+    Aborted,
+    /// The WebRTC stack failed to set up the connection, in `phase` if it is known
+    #[error("webrtc: {source}")]
+    WebrtcError {
+        phase: Option<HandshakePhase>,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl SocketConnectionError {
+    /// The phase of connection establishment which failed, if it is known
+    ///
+    /// Signaling failures are always reported in [`HandshakePhase::Signaling`].
+    pub fn phase(&self) -> Option<HandshakePhase> {
+        match self {
+            SocketConnectionError::Signaling { .. }
+            | SocketConnectionError::IncompatibleAnswer { .. } => Some(HandshakePhase::Signaling),
+            SocketConnectionError::WebrtcError { phase, .. } => *phase,
+            _ => None,
+        }
+    }
+
+    // in_phase tags WebRTC errors with the phase they occurred in, for use with `map_err`
+    pub(crate) fn in_phase(
+        phase: HandshakePhase,
+    ) -> impl FnOnce(crate::webrtc::error::Error) -> SocketConnectionError {
+        move |err| SocketConnectionError::WebrtcError {
+            phase: Some(phase),
+            source: Box::new(err),
+        }
+    }
+}
+
+impl From<crate::webrtc::error::Error> for SocketConnectionError {
+    fn from(err: crate::webrtc::error::Error) -> Self {
+        SocketConnectionError::WebrtcError {
+            phase: None,
+            source: Box::new(err),
+        }
+    }
+}
+
+/// [`DtlsCertificate::from_der`](crate::DtlsCertificate::from_der) can't use the
+/// certificate
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum CertificateError {
+    /// The private key isn't PKCS#8 of a supported algorithm
+    #[error("invalid private key: {0}")]
+    InvalidPrivateKey(String),
+    /// The certificate isn't an X.509 certificate
+    #[error("invalid certificate: {0}")]
+    InvalidCertificate(String),
+    /// The certificate is issued for a different key
+    #[error("the certificate doesn't belong to the private key")]
+    KeyMismatch,
+}
+
+/// [`ExportedSession::from_bytes`](crate::ExportedSession::from_bytes) can't read the blob
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum SessionImportError {
+    /// The blob was exported by a version of this crate with a layout this one can't read
+    #[error("unsupported session version {0}")]
+    UnsupportedVersion(u8),
+    /// The blob is cut short or isn't an exported session
+    #[error("malformed session")]
+    Malformed,
+    /// The certificate in the blob can't be used
+    #[error(transparent)]
+    Certificate(#[from] CertificateError),
+}
+
+/// A candidate signaled by the server can't be used, see
+/// [`SocketConfig::on_remote_candidate`](crate::SocketConfig::on_remote_candidate)
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum CandidateError {
+    /// The candidate can't be parsed
+    #[error("invalid candidate: {reason}")]
+    Invalid { reason: String },
+    /// The ICE agent refused the candidate, e.g. because the connection has closed
+    #[error("the candidate was rejected: {reason}")]
+    Rejected { reason: String },
+    /// No local candidate is on the network of the candidate, e.g. it's an IPv6 candidate
+    /// while only IPv4 addresses were gathered, so ICE has nothing to pair it with
+    #[error("no local candidate can reach the candidate's network")]
+    Unreachable,
+}
+
+/// A data channel stopped reading or writing, see [`SocketEvent::DataChannelFailed`]
+///
+/// [`SocketEvent::DataChannelFailed`]: crate::SocketEvent::DataChannelFailed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum DataChannelError {
+    /// The SCTP stream of the data channel was reset, or the association closed
+    #[error("the data channel is closed")]
+    Closed,
+    /// A received message is larger than the read buffer
+    #[error("a received message is larger than the read buffer")]
+    ShortBuffer,
+    /// A message is larger than the maximum message size of the association
+    #[error("the message exceeds the maximum message size of the association")]
+    MessageTooLarge,
+    /// The SCTP association failed otherwise
+    #[error("sctp: {reason}")]
+    Sctp { reason: String },
+}
+
+impl From<DataChannelInternalError> for DataChannelError {
+    fn from(err: DataChannelInternalError) -> Self {
+        match err {
+            DataChannelInternalError::Sctp(err) => err.into(),
+            err => DataChannelError::Sctp {
+                reason: err.to_string(),
+            },
+        }
+    }
+}
+
+impl From<SctpError> for DataChannelError {
+    fn from(err: SctpError) -> Self {
+        match err {
+            SctpError::ErrStreamClosed | SctpError::ErrEof => DataChannelError::Closed,
+            SctpError::ErrShortBuffer => DataChannelError::ShortBuffer,
+            SctpError::ErrOutboundPacketTooLarge => DataChannelError::MessageTooLarge,
+            err => DataChannelError::Sctp {
+                reason: err.to_string(),
+            },
+        }
+    }
+}
+
+/// [`check_ice_server`](crate::check_ice_server) couldn't get an answer from the server
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum IceServerError {
+    /// The url isn't a valid STUN url
+    #[error("invalid ice server url: {reason}")]
+    InvalidUrl { reason: String },
+    /// The url is a valid ICE server url of a scheme this client has no support for
+    #[error("{scheme}: urls are not supported")]
+    Unsupported { scheme: String },
+    /// The host of the url has no address
+    #[error("could not resolve {host}")]
+    Resolve { host: String },
+    /// The request couldn't be sent or the response couldn't be received, e.g. because
+    /// nothing listens on the server's port
+    #[error("io: {reason}")]
+    Io { reason: String },
+    /// The server answered the binding request with an error
+    #[error("the server responded with error {code}: {reason}")]
+    ErrorResponse { code: u16, reason: String },
+    /// The server's response has no mapped address
+    #[error("invalid response: {reason}")]
+    InvalidResponse { reason: String },
+    /// No response was received before the timeout. Networks which block UDP end up here
+    #[error("no response was received before the timeout")]
+    Timeout,
+}
+
+/// [`probe`](crate::probe) couldn't complete a connection to the server
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ProbeError {
+    /// Establishing the connection failed
+    #[error(transparent)]
+    Connect(#[from] SocketConnectionError),
+    /// The data channel didn't open before the timeout. `phase` is the phase of
+    /// establishing the connection which was in progress, if one was
+    #[error("the data channel did not open before the timeout")]
+    Timeout { phase: Option<HandshakePhase> },
+    /// The data channel opened, but the ping got no echo
+    #[error("ping: {0}")]
+    Ping(#[from] PingError),
+}
+
+/// [`SocketIo::recv_large`](crate::SocketIo::recv_large) couldn't receive a payload
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LargeTransferError {
+    /// The connection can't carry large transfers
+    #[error(transparent)]
+    Connection(#[from] SocketConnectionError),
+    /// The connection was lost while a payload was being received, so that only the first
+    /// `transferred` of its `total` bytes arrived. The next payload is received as usual
+    #[error("the transfer was cut short after {transferred} of {total} bytes")]
+    Partial { transferred: usize, total: usize },
+}
+
+/// [`SocketIo::recv_timeout`](crate::SocketIo::recv_timeout) received no message in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("no message was received before the timeout")]
+pub struct RecvTimeout;
+
+/// [`SocketIo::ping`](crate::SocketIo::ping) got no echo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum PingError {
+    /// The data channel isn't open, e.g. while the connection is being re-established
+    #[error("the data channel is not open")]
+    NotConnected,
+    /// No echo was received before the timeout, e.g. because the path to the server has
+    /// stopped carrying packets
+    #[error("no echo was received before the timeout")]
+    Timeout,
+}
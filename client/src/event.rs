@@ -0,0 +1,99 @@
+use std::time::Instant;
+
+use tokio::sync::broadcast;
+
+use crate::{DataChannelError, HandshakePhase, OverflowPolicy};
+
+const EVENT_CHANNEL_SIZE: usize = 32;
+
+/// Events emitted by a connection, see [`SocketIo::events`](crate::SocketIo::events)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SocketEvent {
+    /// Buffered inbound messages exceeded the `max_buffered_bytes` budget and `policy` was
+    /// applied
+    InboundBudgetExceeded {
+        /// Payload bytes buffered after applying the policy
+        buffered_bytes: usize,
+        /// Payload bytes discarded by the policy
+        dropped_bytes: usize,
+        policy: OverflowPolicy,
+    },
+    /// A phase of establishing the connection started at `at`. The phases are those of
+    /// [`HandshakeTimings`](crate::HandshakeTimings), and start again after
+    /// [`SocketIo::restart_ice`](crate::SocketIo::restart_ice)
+    HandshakePhaseStarted { phase: HandshakePhase, at: Instant },
+    /// A phase of establishing the connection finished at `at`. Once
+    /// [`HandshakePhase::SctpAssociation`] has finished, the data channel is open
+    HandshakePhaseFinished { phase: HandshakePhase, at: Instant },
+    /// The SCTP association beneath the data channels was established at `at`, just before
+    /// [`HandshakePhase::SctpAssociation`] finishes
+    SctpAssociationUp { at: Instant },
+    /// The established SCTP association closed at `at`, because the connection was closed
+    /// locally, the server shut it down or aborted it, or the DTLS connection beneath it
+    /// closed. When the connection drops, whether this comes before ICE or DTLS report
+    /// failure tells a protocol error apart from a network one
+    SctpAssociationDown { at: Instant },
+    /// The data channel on SCTP stream `stream_id` stopped reading or writing because of
+    /// `error`, e.g. [`DataChannelError::Closed`] once the server has reset the stream. The
+    /// stream of the unreliable data channel is
+    /// [`SocketIo::channel_stream_id`](crate::SocketIo::channel_stream_id)
+    DataChannelFailed {
+        stream_id: u16,
+        error: DataChannelError,
+    },
+    /// The server closed its send direction of the data channel on SCTP stream `stream_id`,
+    /// so no more messages arrive on it. Messages can still be sent on it, unless closed
+    /// with [`SocketIo::close_send`](crate::SocketIo::close_send) as well
+    RecvClosed { stream_id: u16 },
+    /// Establishing a connection started with
+    /// [`Socket::connect_background`](crate::Socket::connect_background) failed in `phase`,
+    /// if it is known, because of `reason`. Messages queued meanwhile have been dropped
+    ConnectFailed {
+        phase: Option<HandshakePhase>,
+        reason: String,
+    },
+    /// The [maximum lifetime](crate::SocketConfig::set_max_connection_lifetime) of the
+    /// connection has passed, and it is being closed
+    LifetimeExpired,
+    /// No message was sent or received for the
+    /// [idle timeout](crate::SocketConfig::set_idle_timeout), and the connection is being
+    /// closed
+    IdleTimedOut,
+    /// SCTP sent the DATA chunk `tsn` again, carrying `size` bytes of a message. With
+    /// `fast`, the server's acknowledgements reported it missing; otherwise its
+    /// retransmission timer expired
+    #[cfg(feature = "transport-events")]
+    SctpRetransmit { tsn: u32, size: usize, fast: bool },
+    /// The DATA chunk `tsn`, carrying `size` bytes of a message, arrived while the `missing`
+    /// chunks sent before it haven't, so they were lost or reordered
+    #[cfg(feature = "transport-events")]
+    SctpGap { tsn: u32, size: usize, missing: u32 },
+    /// A flight of the DTLS handshake got no answer in time and was sent again.
+    /// `message_sequence` is that of its first handshake message, and `size` the bytes of
+    /// its records before encryption
+    #[cfg(feature = "transport-events")]
+    DtlsRetransmit { message_sequence: u16, size: usize },
+}
+
+// EventSender
+#[derive(Clone)]
+pub(crate) struct EventSender {
+    sender: broadcast::Sender<SocketEvent>,
+}
+
+impl EventSender {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_SIZE);
+        EventSender { sender }
+    }
+
+    pub(crate) fn emit(&self, event: SocketEvent) {
+        // no subscribers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<SocketEvent> {
+        self.sender.subscribe()
+    }
+}
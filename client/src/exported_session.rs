@@ -0,0 +1,175 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Buf, BufMut};
+
+use crate::{
+    candidate::CandidatePair, credentials::SessionCredentials, dtls_certificate::DtlsCertificate,
+    error::SessionImportError,
+};
+
+// VERSION is the first byte of the blob, raised whenever its layout changes
+const VERSION: u8 = 1;
+
+/// The state of a connection needed to reconnect to the same server quickly, possibly from
+/// another process: the selected candidate pair, and the DTLS certificate and ICE
+/// credentials the connection was offered with
+///
+/// It is returned by [`SocketIo::export_session`](crate::SocketIo::export_session), moved
+/// around as the opaque blob of [`to_bytes`](Self::to_bytes), and used for a new connection
+/// with [`SocketConfig::set_exported_session`](crate::SocketConfig::set_exported_session).
+/// The new connection still signals the server and goes through the ICE, DTLS and SCTP
+/// handshakes, but skips gathering on every interface and offers the same credentials, as
+/// set by [`set_cached_candidate_pair`](crate::SocketConfig::set_cached_candidate_pair) and
+/// [`set_session_credentials`](crate::SocketConfig::set_session_credentials). The keys of
+/// the DTLS session and the SCTP sequence numbers aren't exported, so messages in flight
+/// are lost, and the server has to accept the new connection as it would any other.
+///
+/// # Security
+///
+/// The blob holds the private key of the certificate and the ICE password in the clear.
+/// Whoever reads it can impersonate the client to the server for as long as the server
+/// trusts the certificate, and forge connectivity checks the new connection accepts. Treat
+/// it like the key itself: move it over an authenticated and encrypted channel, don't log
+/// or persist it, and keep the `max_age` it is imported with short.
+#[derive(Clone)]
+pub struct ExportedSession {
+    pub(crate) candidate_pair: CandidatePair,
+    pub(crate) credentials: SessionCredentials,
+}
+
+impl ExportedSession {
+    /// The candidate pair the exported connection went through
+    pub fn candidate_pair(&self) -> CandidatePair {
+        self.candidate_pair
+    }
+
+    /// The DTLS certificate and ICE credentials the exported connection was offered with
+    pub fn credentials(&self) -> &SessionCredentials {
+        &self.credentials
+    }
+
+    /// Encodes the session into a blob for [`from_bytes`](Self::from_bytes), which begins
+    /// with a version byte. The credentials keep their age, carried over as the wall-clock
+    /// time they were first offered at, so the clocks of both hosts have to agree
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let credentials = &self.credentials;
+        let certificate = &credentials.certificate.certificate.certificate;
+        let mut buf = vec![VERSION];
+        put_addr(&mut buf, self.candidate_pair.local);
+        put_addr(&mut buf, self.candidate_pair.remote);
+        put_bytes(&mut buf, credentials.ice_ufrag.as_bytes());
+        put_bytes(&mut buf, credentials.ice_pwd.as_bytes());
+        put_bytes(&mut buf, &certificate.certificate[0].0);
+        put_bytes(&mut buf, &certificate.private_key.serialized_der);
+        let issued = SystemTime::now() - credentials.age();
+        let issued = issued.duration_since(UNIX_EPOCH).unwrap_or_default();
+        buf.put_u64(issued.as_millis() as u64);
+        buf
+    }
+
+    /// Decodes a blob made by [`to_bytes`](Self::to_bytes), possibly by another version of
+    /// this crate. Fails if its version isn't supported, it is cut short or malformed, or
+    /// its certificate can't be used
+    pub fn from_bytes(mut buf: &[u8]) -> Result<Self, SessionImportError> {
+        let version = get_u8(&mut buf)?;
+        if version != VERSION {
+            return Err(SessionImportError::UnsupportedVersion(version));
+        }
+        let local = get_addr(&mut buf)?;
+        let remote = get_addr(&mut buf)?;
+        let ice_ufrag = get_string(&mut buf)?;
+        let ice_pwd = get_string(&mut buf)?;
+        let certificate = get_bytes(&mut buf)?;
+        let private_key = get_bytes(&mut buf)?;
+        let issued = UNIX_EPOCH + Duration::from_millis(get_u64(&mut buf)?);
+        if buf.has_remaining() {
+            return Err(SessionImportError::Malformed);
+        }
+
+        let certificate = DtlsCertificate::from_der(certificate, private_key)?;
+        // credentials issued ahead of this host's clock are taken as just issued, and ones
+        // older than it can represent are malformed
+        let age = SystemTime::now().duration_since(issued).unwrap_or_default();
+        let issued = Instant::now()
+            .checked_sub(age)
+            .ok_or(SessionImportError::Malformed)?;
+        Ok(ExportedSession {
+            candidate_pair: CandidatePair::new(local, remote),
+            credentials: SessionCredentials {
+                certificate,
+                ice_ufrag,
+                ice_pwd,
+                issued,
+            },
+        })
+    }
+}
+
+fn put_addr(buf: &mut Vec<u8>, addr: SocketAddr) {
+    match addr.ip() {
+        IpAddr::V4(ip) => {
+            buf.put_u8(4);
+            buf.put_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            buf.put_u8(6);
+            buf.put_slice(&ip.octets());
+        }
+    }
+    buf.put_u16(addr.port());
+}
+
+// put_bytes writes a field prefixed with its length
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.put_u32(bytes.len() as u32);
+    buf.put_slice(bytes);
+}
+
+fn get_u8(buf: &mut &[u8]) -> Result<u8, SessionImportError> {
+    if buf.remaining() < 1 {
+        return Err(SessionImportError::Malformed);
+    }
+    Ok(buf.get_u8())
+}
+
+fn get_u64(buf: &mut &[u8]) -> Result<u64, SessionImportError> {
+    if buf.remaining() < 8 {
+        return Err(SessionImportError::Malformed);
+    }
+    Ok(buf.get_u64())
+}
+
+fn get_addr(buf: &mut &[u8]) -> Result<SocketAddr, SessionImportError> {
+    let ip = match get_u8(buf)? {
+        4 => IpAddr::V4(Ipv4Addr::from(
+            <[u8; 4]>::try_from(get_slice(buf, 4)?).unwrap(),
+        )),
+        6 => IpAddr::V6(Ipv6Addr::from(
+            <[u8; 16]>::try_from(get_slice(buf, 16)?).unwrap(),
+        )),
+        _ => return Err(SessionImportError::Malformed),
+    };
+    let port = u16::from_be_bytes(get_slice(buf, 2)?.try_into().unwrap());
+    Ok(SocketAddr::new(ip, port))
+}
+
+fn get_bytes<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], SessionImportError> {
+    let len = u32::from_be_bytes(get_slice(buf, 4)?.try_into().unwrap());
+    get_slice(buf, len as usize)
+}
+
+fn get_string(buf: &mut &[u8]) -> Result<String, SessionImportError> {
+    String::from_utf8(get_bytes(buf)?.to_vec()).map_err(|_| SessionImportError::Malformed)
+}
+
+fn get_slice<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], SessionImportError> {
+    if buf.len() < len {
+        return Err(SessionImportError::Malformed);
+    }
+    let (slice, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(slice)
+}
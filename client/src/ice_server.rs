@@ -0,0 +1,218 @@
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use tokio::net::{lookup_host, UdpSocket};
+
+use crate::{
+    error::IceServerError,
+    webrtc::stun::{
+        addr::MappedAddress,
+        agent::TransactionId,
+        error_code::ErrorCodeAttribute,
+        fingerprint::FINGERPRINT,
+        message::{
+            is_message, Getter, Message, BINDING_REQUEST, BINDING_SUCCESS, CLASS_ERROR_RESPONSE,
+            METHOD_BINDING,
+        },
+        xoraddr::XorMappedAddress,
+    },
+};
+
+// DEFAULT_STUN_PORT is the port of stun: urls which don't name one, RFC 7064 Section 3.2
+const DEFAULT_STUN_PORT: u16 = 3478;
+
+// INITIAL_RTO is the delay before the binding request is first sent again, doubling with
+// every retransmission, RFC 5389 Section 7.2.1
+const INITIAL_RTO: Duration = Duration::from_millis(500);
+
+/// A STUN server answered [`check_ice_server`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IceServerReachability {
+    /// Address the server was reached at
+    pub server_addr: SocketAddr,
+    /// Time from sending the binding request to receiving the response
+    pub latency: Duration,
+    /// Address of this host as seen by the server. It differs from the local address when
+    /// the host is behind a NAT
+    pub mapped_addr: SocketAddr,
+}
+
+/// Checks that the STUN server at `url`, e.g. `stun:stun.example.com:3478`, can be reached
+/// by sending it a binding request over UDP, e.g. for warning the user about a network
+/// which blocks UDP before matchmaking. No connection is made, and the outcome doesn't
+/// depend on the signaling server.
+///
+/// The request is sent again while there's no response, and the check fails with
+/// [`IceServerError::Timeout`] once `timeout` has passed, including resolving the host.
+///
+/// Only `stun:` urls are supported: this client has no TURN or STUN over TLS client, so
+/// `turn:`, `turns:` and `stuns:` urls fail with [`IceServerError::Unsupported`].
+pub async fn check_ice_server(
+    url: &str,
+    timeout: Duration,
+) -> Result<IceServerReachability, IceServerError> {
+    let (host, port) = parse_stun_url(url)?;
+    tokio::time::timeout(timeout, binding(&host, port))
+        .await
+        .map_err(|_| IceServerError::Timeout)?
+}
+
+// parse_stun_url returns the host and port of a stun: url
+fn parse_stun_url(url: &str) -> Result<(String, u16), IceServerError> {
+    let invalid = |reason: &str| IceServerError::InvalidUrl {
+        reason: reason.to_owned(),
+    };
+
+    let (scheme, rest) = url
+        .split_once(':')
+        .ok_or_else(|| invalid("missing scheme"))?;
+    match scheme {
+        "stun" => {}
+        "stuns" | "turn" | "turns" => {
+            return Err(IceServerError::Unsupported {
+                scheme: scheme.to_owned(),
+            })
+        }
+        _ => return Err(invalid("the scheme isn't stun")),
+    }
+    if rest.contains(['?', '/', '@']) {
+        return Err(invalid("stun urls only consist of a host and a port"));
+    }
+
+    let (host, port) = match rest.strip_prefix('[') {
+        Some(rest) => {
+            let (host, port) = rest
+                .split_once(']')
+                .ok_or_else(|| invalid("unterminated IPv6 address"))?;
+            match port {
+                "" => (host, None),
+                port => (
+                    host,
+                    Some(
+                        port.strip_prefix(':')
+                            .ok_or_else(|| invalid("unexpected text after the IPv6 address"))?,
+                    ),
+                ),
+            }
+        }
+        None => match rest.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (rest, None),
+        },
+    };
+    if host.is_empty() {
+        return Err(invalid("missing host"));
+    }
+    let port = match port {
+        Some(port) => port.parse().map_err(|_| invalid("invalid port"))?,
+        None => DEFAULT_STUN_PORT,
+    };
+    Ok((host.to_owned(), port))
+}
+
+// binding sends binding requests to the server until one of them is answered. Every
+// retransmission gets its own transaction id, so that the latency is measured from the
+// request that was answered.
+async fn binding(host: &str, port: u16) -> Result<IceServerReachability, IceServerError> {
+    let server_addr = lookup_host((host, port))
+        .await
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| IceServerError::Resolve {
+            host: host.to_owned(),
+        })?;
+    let local_addr: SocketAddr = if server_addr.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let io = |err: std::io::Error| IceServerError::Io {
+        reason: err.to_string(),
+    };
+    let socket = UdpSocket::bind(local_addr).await.map_err(io)?;
+    socket.connect(server_addr).await.map_err(io)?;
+
+    let mut sent: Vec<(TransactionId, Instant)> = Vec::new();
+    let mut rto = INITIAL_RTO;
+    let mut buf = vec![0u8; 1500];
+    loop {
+        let mut request = Message::new();
+        request
+            .build(&[
+                Box::new(BINDING_REQUEST),
+                Box::new(TransactionId::new()),
+                Box::new(FINGERPRINT),
+            ])
+            .map_err(|err| IceServerError::Io {
+                reason: err.to_string(),
+            })?;
+        socket.send(&request.raw).await.map_err(io)?;
+        sent.push((request.transaction_id, Instant::now()));
+
+        let retransmit = tokio::time::sleep(rto);
+        tokio::pin!(retransmit);
+        loop {
+            let n = tokio::select! {
+                _ = &mut retransmit => break,
+                n = socket.recv(&mut buf) => n.map_err(io)?,
+            };
+            if !is_message(&buf[..n]) {
+                continue;
+            }
+            let mut response = Message {
+                raw: buf[..n].to_vec(),
+                ..Message::default()
+            };
+            if response.decode().is_err() {
+                continue;
+            }
+            let sent_at = match sent
+                .iter()
+                .find(|(transaction_id, _)| *transaction_id == response.transaction_id)
+            {
+                Some((_, sent_at)) => *sent_at,
+                None => continue,
+            };
+            if response.typ.method != METHOD_BINDING {
+                continue;
+            }
+            if response.typ.class == CLASS_ERROR_RESPONSE {
+                let mut error_code = ErrorCodeAttribute::default();
+                let _ = error_code.get_from(&response);
+                return Err(IceServerError::ErrorResponse {
+                    code: error_code.code.0,
+                    reason: String::from_utf8_lossy(&error_code.reason).into_owned(),
+                });
+            }
+            if response.typ != BINDING_SUCCESS {
+                continue;
+            }
+
+            let latency = sent_at.elapsed();
+            return Ok(IceServerReachability {
+                server_addr,
+                latency,
+                mapped_addr: mapped_addr(&response)?,
+            });
+        }
+        rto *= 2;
+    }
+}
+
+// mapped_addr reads the XOR-MAPPED-ADDRESS of a binding response, or the MAPPED-ADDRESS
+// of servers that predate RFC 5389
+fn mapped_addr(response: &Message) -> Result<SocketAddr, IceServerError> {
+    let mut xor_addr = XorMappedAddress::default();
+    if xor_addr.get_from(response).is_ok() {
+        return Ok((xor_addr.ip, xor_addr.port).into());
+    }
+    let mut addr = MappedAddress::default();
+    addr.get_from(response)
+        .map_err(|err| IceServerError::InvalidResponse {
+            reason: err.to_string(),
+        })?;
+    Ok((addr.ip, addr.port).into())
+}
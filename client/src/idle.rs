@@ -0,0 +1,36 @@
+use std::{sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+// Activity tracks when data was last sent or received on a connection, for its idle timeout.
+// Pings only count as activity with `pings`
+pub(crate) struct Activity {
+    last: Mutex<Instant>,
+    pings: bool,
+}
+
+impl Activity {
+    pub(crate) fn new(pings: bool) -> Self {
+        Activity {
+            last: Mutex::new(Instant::now()),
+            pings,
+        }
+    }
+
+    // touch records that a message has been sent or received
+    pub(crate) fn touch(&self) {
+        *self.last.lock().expect("activity lock poisoned") = Instant::now();
+    }
+
+    // touch_ping records that a ping or its echo has been sent or received
+    pub(crate) fn touch_ping(&self) {
+        if self.pings {
+            self.touch();
+        }
+    }
+
+    // deadline is when the connection times out after `timeout` without activity
+    pub(crate) fn deadline(&self, timeout: Duration) -> Instant {
+        *self.last.lock().expect("activity lock poisoned") + timeout
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    collections::VecDeque,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use tokio::sync::{mpsc, Notify};
+
+#[cfg(feature = "network-conditioner")]
+use crate::conditioner::NetworkConditioner;
+use crate::{
+    config::{InboundBudget, OverflowPolicy, PayloadType},
+    event::{EventSender, SocketEvent},
+};
+
+/// Where the read loops deliver inbound messages to: either the plain `to_client_receiver`,
+/// or the receiver of messages tagged with their source address or their payload type
+#[derive(Clone)]
+pub(crate) enum InboundSender {
+    Plain(mpsc::Sender<Box<[u8]>>),
+    Addressed(mpsc::Sender<(Box<[u8]>, SocketAddr)>),
+    Typed(mpsc::Sender<(Box<[u8]>, PayloadType)>),
+}
+
+impl InboundSender {
+    pub(crate) async fn send(
+        &self,
+        message: Box<[u8]>,
+        payload_type: PayloadType,
+        source: &SourceAddr,
+    ) -> Result<()> {
+        match self {
+            InboundSender::Plain(sender) => sender.send(message).await?,
+            InboundSender::Addressed(sender) => sender.send((message, source.get())).await?,
+            InboundSender::Typed(sender) => sender.send((message, payload_type)).await?,
+        }
+        Ok(())
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakInboundSender {
+        match self {
+            InboundSender::Plain(sender) => WeakInboundSender::Plain(sender.downgrade()),
+            InboundSender::Addressed(sender) => WeakInboundSender::Addressed(sender.downgrade()),
+            InboundSender::Typed(sender) => WeakInboundSender::Typed(sender.downgrade()),
+        }
+    }
+
+    /// Puts a conditioner stage in front of the sender
+    #[cfg(feature = "network-conditioner")]
+    pub(crate) fn conditioned(self, conditioner: NetworkConditioner, stream: u64) -> Self {
+        match self {
+            InboundSender::Plain(sender) => {
+                let (conditioned_sender, receiver) = mpsc::channel(sender.max_capacity());
+                conditioner.spawn_stage(stream, receiver, sender);
+                InboundSender::Plain(conditioned_sender)
+            }
+            InboundSender::Addressed(sender) => {
+                let (conditioned_sender, receiver) = mpsc::channel(sender.max_capacity());
+                conditioner.spawn_stage(stream, receiver, sender);
+                InboundSender::Addressed(conditioned_sender)
+            }
+            InboundSender::Typed(sender) => {
+                let (conditioned_sender, receiver) = mpsc::channel(sender.max_capacity());
+                conditioner.spawn_stage(stream, receiver, sender);
+                InboundSender::Typed(conditioned_sender)
+            }
+        }
+    }
+}
+
+/// An [`InboundSender`] which doesn't keep the receiver open
+pub(crate) enum WeakInboundSender {
+    Plain(mpsc::WeakSender<Box<[u8]>>),
+    Addressed(mpsc::WeakSender<(Box<[u8]>, SocketAddr)>),
+    Typed(mpsc::WeakSender<(Box<[u8]>, PayloadType)>),
+}
+
+impl WeakInboundSender {
+    pub(crate) fn upgrade(&self) -> Option<InboundSender> {
+        match self {
+            WeakInboundSender::Plain(sender) => sender.upgrade().map(InboundSender::Plain),
+            WeakInboundSender::Addressed(sender) => sender.upgrade().map(InboundSender::Addressed),
+            WeakInboundSender::Typed(sender) => sender.upgrade().map(InboundSender::Typed),
+        }
+    }
+}
+
+/// The remote address of the candidate pair selected for a connection
+#[derive(Clone, Default)]
+pub(crate) struct SourceAddr(Arc<Mutex<Option<SocketAddr>>>);
+
+impl SourceAddr {
+    pub(crate) fn set(&self, addr: SocketAddr) {
+        *self.0.lock().expect("source address lock poisoned") = Some(addr);
+    }
+
+    pub(crate) fn get(&self) -> SocketAddr {
+        // data only flows once a candidate pair has been selected
+        self.0
+            .lock()
+            .expect("source address lock poisoned")
+            .unwrap_or_else(|| SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+    }
+}
+
+/// Why [`InboundStaging::push`] refused a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PushError {
+    /// The staging area had already been closed
+    Closed,
+    /// The budget was exceeded with [`OverflowPolicy::Disconnect`], which has closed the
+    /// staging area
+    BudgetExceeded,
+}
+
+#[derive(Default)]
+struct StagingState {
+    queue: VecDeque<(Box<[u8]>, PayloadType)>,
+    buffered_bytes: usize,
+    closed: bool,
+}
+
+/// Inbound messages read off the data channel, waiting to be delivered to the receiver.
+/// Holds at most `max_bytes` of payload, applying the configured [`OverflowPolicy`] beyond
+/// that
+pub(crate) struct InboundStaging {
+    budget: InboundBudget,
+    events: EventSender,
+    state: Mutex<StagingState>,
+    notify: Notify,
+}
+
+impl InboundStaging {
+    pub(crate) fn new(budget: InboundBudget, events: EventSender) -> Arc<Self> {
+        Arc::new(InboundStaging {
+            budget,
+            events,
+            state: Mutex::new(StagingState::default()),
+            notify: Notify::new(),
+        })
+    }
+
+    /// Stages a message. Fails if the staging area is closed, or if the budget was exceeded
+    /// with [`OverflowPolicy::Disconnect`]
+    pub(crate) fn push(
+        &self,
+        message: Box<[u8]>,
+        payload_type: PayloadType,
+    ) -> Result<(), PushError> {
+        let mut state = self.state.lock().expect("staging lock poisoned");
+        if state.closed {
+            return Err(PushError::Closed);
+        }
+
+        let max_bytes = self.budget.max_bytes;
+        if state.buffered_bytes + message.len() <= max_bytes {
+            state.buffered_bytes += message.len();
+            state.queue.push_back((message, payload_type));
+            drop(state);
+            self.notify.notify_one();
+            return Ok(());
+        }
+
+        let mut dropped_bytes = 0;
+        let pushed = match self.budget.policy {
+            OverflowPolicy::DropOldest => {
+                while state.buffered_bytes + message.len() > max_bytes {
+                    match state.queue.pop_front() {
+                        Some((oldest, _)) => {
+                            state.buffered_bytes -= oldest.len();
+                            dropped_bytes += oldest.len();
+                        }
+                        None => break,
+                    }
+                }
+                if message.len() <= max_bytes {
+                    state.buffered_bytes += message.len();
+                    state.queue.push_back((message, payload_type));
+                } else {
+                    // doesn't fit even into an empty buffer
+                    dropped_bytes += message.len();
+                }
+                Ok(())
+            }
+            OverflowPolicy::DropNewest => {
+                dropped_bytes += message.len();
+                Ok(())
+            }
+            OverflowPolicy::Disconnect => {
+                dropped_bytes += state.buffered_bytes + message.len();
+                state.queue.clear();
+                state.buffered_bytes = 0;
+                state.closed = true;
+                Err(PushError::BudgetExceeded)
+            }
+        };
+        let buffered_bytes = state.buffered_bytes;
+        drop(state);
+
+        self.notify.notify_one();
+        self.events.emit(SocketEvent::InboundBudgetExceeded {
+            buffered_bytes,
+            dropped_bytes,
+            policy: self.budget.policy,
+        });
+
+        pushed
+    }
+
+    /// Closes the staging area. Messages staged so far are still delivered
+    pub(crate) fn close(&self) {
+        let mut state = self.state.lock().expect("staging lock poisoned");
+        state.closed = true;
+        drop(state);
+        self.notify.notify_one();
+    }
+
+    /// Waits for the next staged message, or `None` once closed and drained
+    pub(crate) async fn pop(&self) -> Option<(Box<[u8]>, PayloadType)> {
+        loop {
+            {
+                let mut state = self.state.lock().expect("staging lock poisoned");
+                if let Some((message, payload_type)) = state.queue.pop_front() {
+                    state.buffered_bytes -= message.len();
+                    return Some((message, payload_type));
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::broadcast;
+
+    use super::*;
+
+    fn staging(
+        max_bytes: usize,
+        policy: OverflowPolicy,
+    ) -> (Arc<InboundStaging>, broadcast::Receiver<SocketEvent>) {
+        let events = EventSender::new();
+        let receiver = events.subscribe();
+        let budget = InboundBudget { max_bytes, policy };
+        (InboundStaging::new(budget, events), receiver)
+    }
+
+    fn message(len: usize, fill: u8) -> Box<[u8]> {
+        vec![fill; len].into_boxed_slice()
+    }
+
+    // drain pops until the staging area is empty and closed, and returns the first byte of
+    // every message
+    async fn drain(staging: &InboundStaging) -> Vec<u8> {
+        staging.close();
+        let mut messages = Vec::new();
+        while let Some((message, _)) = staging.pop().await {
+            messages.push(message[0]);
+        }
+        messages
+    }
+
+    fn budget_exceeded(
+        buffered_bytes: usize,
+        dropped_bytes: usize,
+        policy: OverflowPolicy,
+    ) -> SocketEvent {
+        SocketEvent::InboundBudgetExceeded {
+            buffered_bytes,
+            dropped_bytes,
+            policy,
+        }
+    }
+
+    #[tokio::test]
+    async fn within_the_budget_everything_is_delivered_in_order() {
+        let (staging, mut events) = staging(10, OverflowPolicy::DropNewest);
+        for fill in 0..5 {
+            staging.push(message(2, fill), PayloadType::Binary).unwrap();
+        }
+        assert_eq!(drain(&staging).await, [0, 1, 2, 3, 4]);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn payload_type_is_kept() {
+        let (staging, _events) = staging(10, OverflowPolicy::DropNewest);
+        staging.push(message(1, 0), PayloadType::String).unwrap();
+        staging.close();
+        assert_eq!(staging.pop().await.unwrap().1, PayloadType::String);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_makes_room_for_the_new_message() {
+        let (staging, mut events) = staging(10, OverflowPolicy::DropOldest);
+        for fill in 0..3 {
+            staging.push(message(4, fill), PayloadType::Binary).unwrap();
+        }
+        // 4 + 4 fit, so the third message dropped only the first one
+        assert_eq!(
+            events.try_recv().unwrap(),
+            budget_exceeded(8, 4, OverflowPolicy::DropOldest)
+        );
+        assert_eq!(drain(&staging).await, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_drops_a_message_larger_than_the_budget() {
+        let (staging, mut events) = staging(10, OverflowPolicy::DropOldest);
+        staging.push(message(4, 0), PayloadType::Binary).unwrap();
+        staging.push(message(11, 1), PayloadType::Binary).unwrap();
+        assert_eq!(
+            events.try_recv().unwrap(),
+            budget_exceeded(0, 15, OverflowPolicy::DropOldest)
+        );
+        assert!(drain(&staging).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drop_newest_keeps_what_is_buffered() {
+        let (staging, mut events) = staging(10, OverflowPolicy::DropNewest);
+        for fill in 0..3 {
+            staging.push(message(4, fill), PayloadType::Binary).unwrap();
+        }
+        assert_eq!(
+            events.try_recv().unwrap(),
+            budget_exceeded(8, 4, OverflowPolicy::DropNewest)
+        );
+        // a message which fits again is staged
+        staging.push(message(2, 3), PayloadType::Binary).unwrap();
+        assert_eq!(drain(&staging).await, [0, 1, 3]);
+    }
+
+    #[tokio::test]
+    async fn disconnect_discards_everything_and_closes() {
+        let (staging, mut events) = staging(10, OverflowPolicy::Disconnect);
+        staging.push(message(4, 0), PayloadType::Binary).unwrap();
+        staging.push(message(4, 1), PayloadType::Binary).unwrap();
+        assert_eq!(
+            staging.push(message(4, 2), PayloadType::Binary),
+            Err(PushError::BudgetExceeded)
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            budget_exceeded(0, 12, OverflowPolicy::Disconnect)
+        );
+        assert_eq!(staging.pop().await, None);
+        assert_eq!(
+            staging.push(message(1, 3), PayloadType::Binary),
+            Err(PushError::Closed)
+        );
+    }
+
+    #[tokio::test]
+    async fn push_after_close_is_refused() {
+        let (staging, mut events) = staging(10, OverflowPolicy::DropOldest);
+        staging.push(message(2, 0), PayloadType::Binary).unwrap();
+        staging.close();
+        assert_eq!(
+            staging.push(message(2, 1), PayloadType::Binary),
+            Err(PushError::Closed)
+        );
+        assert!(events.try_recv().is_err());
+        // what was staged before closing is still delivered
+        assert_eq!(drain(&staging).await, [0]);
+    }
+
+    #[tokio::test]
+    async fn pop_waits_for_a_push() {
+        let (staging, _events) = staging(10, OverflowPolicy::DropNewest);
+        let popped = tokio::spawn({
+            let staging = staging.clone();
+            async move { staging.pop().await }
+        });
+        tokio::task::yield_now().await;
+        staging.push(message(1, 7), PayloadType::Binary).unwrap();
+        let (message, _) = popped.await.unwrap().unwrap();
+        assert_eq!(&*message, [7]);
+    }
+}
@@ -0,0 +1,43 @@
+use std::net::IpAddr;
+
+use crate::webrtc::util::ifaces::ifaces;
+
+/// A network interface of this host, as listed by [`list_interfaces`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InterfaceInfo {
+    /// Name of the interface, as matched by
+    /// [`SocketConfig::set_interface_filter`](crate::SocketConfig::set_interface_filter)
+    pub name: String,
+    /// IPv4 and IPv6 addresses assigned to the interface
+    pub addresses: Vec<IpAddr>,
+    /// Whether the interface is up
+    pub up: bool,
+    /// Whether the interface is a loopback interface
+    pub loopback: bool,
+}
+
+/// Lists the network interfaces of this host with their addresses, e.g. for letting the user
+/// pick the adapter to connect through. Nothing is sent over the network.
+///
+/// Interfaces without an IP address are left out. If the interfaces can't be queried, the
+/// list is empty.
+pub fn list_interfaces() -> Vec<InterfaceInfo> {
+    let mut interfaces: Vec<InterfaceInfo> = Vec::new();
+    for iface in ifaces().unwrap_or_default() {
+        let address = match iface.addr {
+            Some(addr) => addr.ip(),
+            None => continue,
+        };
+        match interfaces.iter_mut().find(|info| info.name == iface.name) {
+            Some(info) => info.addresses.push(address),
+            None => interfaces.push(InterfaceInfo {
+                name: iface.name,
+                addresses: vec![address],
+                up: iface.up,
+                loopback: iface.loopback,
+            }),
+        }
+    }
+    interfaces
+}
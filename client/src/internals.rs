@@ -0,0 +1,42 @@
+// Internals exposed to the benchmarks and tests of the crate, which aren't part of its API
+
+use std::{io, net::SocketAddr};
+
+use tokio::net::UdpSocket;
+
+use crate::{session_response, webrtc::util::Conn};
+
+/// Reads the datagrams queued on `socket` the way the ICE agent does, each into the next of
+/// `bufs` with its length and source recorded in `received`, and returns how many were
+/// read. On Linux and Android, this is a single recvmmsg call, elsewhere a single recv_from
+pub async fn recv_from_batch(
+    socket: &UdpSocket,
+    bufs: &mut [Vec<u8>],
+    received: &mut [(usize, SocketAddr)],
+) -> io::Result<usize> {
+    Conn::recv_from_batch(socket, bufs, received)
+        .await
+        .map_err(io::Error::other)
+}
+
+/// Reads the JSON answer of a webrtc-unreliable server with tinyjson, the default parser,
+/// into its sdp and candidates
+pub fn tinyjson_session_response(input: &str) -> Result<(String, Vec<String>), String> {
+    session_response::tinyjson_session_response(input).map(session_parts)
+}
+
+/// Reads the JSON answer of a webrtc-unreliable server with serde_json, the parser of the
+/// `serde-json` feature, into its sdp and candidates
+#[cfg(feature = "serde-json")]
+pub fn serde_json_session_response(input: &str) -> Result<(String, Vec<String>), String> {
+    session_response::serde_json_session_response(input).map(session_parts)
+}
+
+fn session_parts(response: session_response::JsSessionResponse) -> (String, Vec<String>) {
+    let candidates = response
+        .candidates
+        .into_iter()
+        .map(|candidate| candidate.candidate)
+        .collect();
+    (response.answer.sdp, candidates)
+}
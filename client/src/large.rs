@@ -0,0 +1,298 @@
+use bytes::{Bytes, BytesMut};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::error::LargeTransferError;
+
+// every chunk starts with the id of its transfer (u32), its index within the transfer (u32)
+// and the length of the whole payload (u64), all big-endian
+const CHUNK_HEADER_SIZE: usize = 16;
+// the payload bytes of a chunk, so that a chunk fits a single SCTP packet at the default MTU
+pub(crate) const CHUNK_PAYLOAD_SIZE: usize = 1024;
+
+// chunks splits `payload` into the chunks of transfer `transfer`. An empty payload still
+// takes one chunk, so that it is received
+pub(crate) fn chunks(transfer: u32, payload: &[u8]) -> impl Iterator<Item = Box<[u8]>> + '_ {
+    let total = payload.len() as u64;
+    let count = chunk_count(payload.len());
+    (0..count).map(move |index| {
+        let start = index * CHUNK_PAYLOAD_SIZE;
+        let end = (start + CHUNK_PAYLOAD_SIZE).min(payload.len());
+        let mut chunk = Vec::with_capacity(CHUNK_HEADER_SIZE + end - start);
+        chunk.extend_from_slice(&transfer.to_be_bytes());
+        chunk.extend_from_slice(&(index as u32).to_be_bytes());
+        chunk.extend_from_slice(&total.to_be_bytes());
+        chunk.extend_from_slice(&payload[start..end]);
+        chunk.into_boxed_slice()
+    })
+}
+
+fn chunk_count(total: usize) -> usize {
+    total.div_ceil(CHUNK_PAYLOAD_SIZE).max(1)
+}
+
+struct ChunkHeader {
+    transfer: u32,
+    index: usize,
+    total: usize,
+}
+
+fn parse_chunk(chunk: &[u8]) -> Option<(ChunkHeader, &[u8])> {
+    if chunk.len() < CHUNK_HEADER_SIZE {
+        return None;
+    }
+    let (header, data) = chunk.split_at(CHUNK_HEADER_SIZE);
+    let header = ChunkHeader {
+        transfer: u32::from_be_bytes(header[0..4].try_into().ok()?),
+        index: u32::from_be_bytes(header[4..8].try_into().ok()?) as usize,
+        total: usize::try_from(u64::from_be_bytes(header[8..16].try_into().ok()?)).ok()?,
+    };
+    Some((header, data))
+}
+
+// Transfer is a payload being reassembled from its chunks
+struct Transfer {
+    id: u32,
+    total: usize,
+    next_index: usize,
+    payload: BytesMut,
+}
+
+// Reassembly puts the chunks read from the large transfer channel back together. The channel
+// is reliable and ordered, so a chunk out of sequence means the connection was
+// re-established mid-transfer and the chunks in between are lost
+#[derive(Default)]
+pub(crate) struct Reassembly {
+    transfer: Option<Transfer>,
+    // a transfer whose first chunks were lost, whose remaining chunks are skipped
+    skipped: Option<u32>,
+    // a payload completed by the chunk which cut the previous transfer short
+    ready: Option<Bytes>,
+}
+
+impl Reassembly {
+    // push adds a chunk. Returns the payload once its last chunk is in, or an error if the
+    // chunk cut the transfer in progress short
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Result<Option<Bytes>, LargeTransferError> {
+        let (header, data) = match parse_chunk(chunk) {
+            Some(parsed) => parsed,
+            None => {
+                warn!("Dropping a large transfer chunk of {} bytes", chunk.len());
+                return Ok(None);
+            }
+        };
+        let cut_short = match &self.transfer {
+            Some(transfer)
+                if header.transfer == transfer.id && header.index == transfer.next_index =>
+            {
+                None
+            }
+            Some(_) => Some(self.cut_short()),
+            None => None,
+        };
+        if self.transfer.is_none() {
+            if header.index != 0 {
+                if self.skipped != Some(header.transfer) {
+                    debug!(
+                        "Skipping large transfer {}, whose first chunks were lost",
+                        header.transfer
+                    );
+                    self.skipped = Some(header.transfer);
+                }
+                return cut_short.map_or(Ok(None), Err);
+            }
+            self.skipped = None;
+            self.start(&header);
+        }
+        let payload = self.append(data);
+        match cut_short {
+            Some(err) => {
+                self.ready = payload;
+                Err(err)
+            }
+            None => Ok(payload),
+        }
+    }
+
+    // take_ready returns the payload completed while push returned an error
+    pub(crate) fn take_ready(&mut self) -> Option<Bytes> {
+        self.ready.take()
+    }
+
+    // end is called once no more chunks will arrive. Returns an error if a transfer was in
+    // progress
+    pub(crate) fn end(&mut self) -> Result<(), LargeTransferError> {
+        match self.transfer {
+            Some(_) => Err(self.cut_short()),
+            None => Ok(()),
+        }
+    }
+
+    fn start(&mut self, header: &ChunkHeader) {
+        self.transfer = Some(Transfer {
+            id: header.transfer,
+            total: header.total,
+            next_index: 0,
+            payload: BytesMut::with_capacity(header.total),
+        });
+    }
+
+    fn append(&mut self, data: &[u8]) -> Option<Bytes> {
+        let transfer = self.transfer.as_mut()?;
+        transfer.payload.extend_from_slice(data);
+        transfer.next_index += 1;
+        if transfer.next_index < chunk_count(transfer.total) {
+            return None;
+        }
+        let transfer = self.transfer.take()?;
+        Some(transfer.payload.freeze())
+    }
+
+    fn cut_short(&mut self) -> LargeTransferError {
+        let transfer = self
+            .transfer
+            .take()
+            .expect("cut_short without a transfer in progress");
+        LargeTransferError::Partial {
+            transferred: transfer.payload.len(),
+            total: transfer.total,
+        }
+    }
+}
+
+// LargeReceiver receives the chunks the read loop of the large transfer channel delivers,
+// and puts them back together
+pub(crate) struct LargeReceiver {
+    receiver: mpsc::Receiver<Box<[u8]>>,
+    reassembly: Reassembly,
+}
+
+impl LargeReceiver {
+    pub(crate) fn new(receiver: mpsc::Receiver<Box<[u8]>>) -> Self {
+        LargeReceiver {
+            receiver,
+            reassembly: Reassembly::default(),
+        }
+    }
+
+    // recv returns the next payload, or None once the large transfer channel has closed
+    pub(crate) async fn recv(&mut self) -> Result<Option<Bytes>, LargeTransferError> {
+        if let Some(payload) = self.reassembly.take_ready() {
+            return Ok(Some(payload));
+        }
+        while let Some(chunk) = self.receiver.recv().await {
+            if let Some(payload) = self.reassembly.push(&chunk)? {
+                return Ok(Some(payload));
+            }
+        }
+        self.reassembly.end()?;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    fn reassemble(chunks: impl Iterator<Item = Box<[u8]>>) -> Vec<Bytes> {
+        let mut reassembly = Reassembly::default();
+        chunks
+            .filter_map(|chunk| reassembly.push(&chunk).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn payload_round_trips() {
+        for len in [
+            0,
+            1,
+            CHUNK_PAYLOAD_SIZE,
+            CHUNK_PAYLOAD_SIZE + 1,
+            10 * CHUNK_PAYLOAD_SIZE,
+        ] {
+            let payload = payload(len);
+            assert_eq!(
+                reassemble(chunks(7, &payload)),
+                vec![Bytes::from(payload)],
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn chunks_fit_the_chunk_size() {
+        let payload = payload(3 * CHUNK_PAYLOAD_SIZE + 5);
+        let sizes: Vec<_> = chunks(0, &payload).map(|chunk| chunk.len()).collect();
+        let full = CHUNK_HEADER_SIZE + CHUNK_PAYLOAD_SIZE;
+        assert_eq!(sizes, [full, full, full, CHUNK_HEADER_SIZE + 5]);
+    }
+
+    #[test]
+    fn consecutive_transfers_are_kept_apart() {
+        let first = payload(2 * CHUNK_PAYLOAD_SIZE);
+        let second = payload(CHUNK_PAYLOAD_SIZE / 2);
+        let received = reassemble(chunks(1, &first).chain(chunks(2, &second)));
+        assert_eq!(received, vec![Bytes::from(first), Bytes::from(second)]);
+    }
+
+    #[test]
+    fn gap_cuts_the_transfer_short() {
+        let payload = payload(4 * CHUNK_PAYLOAD_SIZE);
+        let mut chunks = chunks(1, &payload);
+        let mut reassembly = Reassembly::default();
+        assert_eq!(reassembly.push(&chunks.next().unwrap()).unwrap(), None);
+        chunks.next();
+        assert!(matches!(
+            reassembly.push(&chunks.next().unwrap()),
+            Err(LargeTransferError::Partial { transferred, total })
+                if transferred == CHUNK_PAYLOAD_SIZE && total == payload.len()
+        ));
+        // the rest of the broken transfer is skipped
+        assert_eq!(reassembly.push(&chunks.next().unwrap()).unwrap(), None);
+        assert!(reassembly.end().is_ok());
+    }
+
+    #[test]
+    fn new_transfer_cuts_the_previous_one_short() {
+        let first = payload(2 * CHUNK_PAYLOAD_SIZE);
+        let second = payload(10);
+        let mut reassembly = Reassembly::default();
+        let first_chunk = chunks(1, &first).next().unwrap();
+        assert_eq!(reassembly.push(&first_chunk).unwrap(), None);
+        let second_chunk = chunks(2, &second).next().unwrap();
+        assert!(matches!(
+            reassembly.push(&second_chunk),
+            Err(LargeTransferError::Partial { transferred, total })
+                if transferred == CHUNK_PAYLOAD_SIZE && total == first.len()
+        ));
+        // the chunk which cut the first transfer short completed the second one
+        assert_eq!(reassembly.take_ready(), Some(Bytes::from(second)));
+        assert_eq!(reassembly.take_ready(), None);
+    }
+
+    #[test]
+    fn ending_mid_transfer_is_partial() {
+        let payload = payload(2 * CHUNK_PAYLOAD_SIZE);
+        let mut reassembly = Reassembly::default();
+        reassembly
+            .push(&chunks(1, &payload).next().unwrap())
+            .unwrap();
+        assert!(matches!(
+            reassembly.end(),
+            Err(LargeTransferError::Partial { transferred, total })
+                if transferred == CHUNK_PAYLOAD_SIZE && total == payload.len()
+        ));
+    }
+
+    #[test]
+    fn truncated_chunk_is_dropped() {
+        let mut reassembly = Reassembly::default();
+        assert_eq!(reassembly.push(&[0; CHUNK_HEADER_SIZE - 1]).unwrap(), None);
+        assert!(reassembly.end().is_ok());
+    }
+}
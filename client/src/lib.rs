@@ -6,10 +6,91 @@ extern crate lazy_static;
 #[macro_use]
 extern crate serde_derive;
 
+mod abort;
 mod addr_cell;
+mod blocking;
+mod candidate;
+mod coalesce;
+mod compression;
+#[cfg(feature = "network-conditioner")]
+mod conditioner;
+mod config;
+mod congestion;
+mod connection_id;
+mod credentials;
+mod description;
+mod dtls_certificate;
+mod dtls_info;
+#[cfg(feature = "echo-answerer")]
+mod echo_answerer;
+mod error;
+mod event;
+mod exported_session;
+mod ice_server;
+mod idle;
+mod inbound;
+mod interfaces;
+#[cfg(feature = "internals")]
+#[doc(hidden)]
+pub mod internals;
+mod large;
+#[cfg(feature = "lz4")]
+mod lz4;
+mod ping;
+mod probe;
+mod queued;
+mod rate_limit;
+mod resolver;
+mod session_response;
+mod signaling_retry;
 mod socket;
+mod stats;
+mod stream;
+mod timings;
+mod whip;
 
+pub use abort::ConnectAbortHandle;
 pub use addr_cell::{AddrCell, ServerAddr};
-pub use socket::Socket;
+pub use blocking::BlockingSocket;
+pub use candidate::{CandidateInfo, CandidatePair, CandidatePreference, RttSample};
+pub use compression::Compression;
+#[cfg(feature = "network-conditioner")]
+pub use conditioner::NetworkConditioner;
+pub use config::{
+    IceRole, OverflowPolicy, PayloadType, Signaling, SignalingProxy, SocketConfig,
+    DEFAULT_SEND_BUFFER_THRESHOLD,
+};
+pub use congestion::CongestionInfo;
+pub use connection_id::ConnectionId;
+pub use credentials::SessionCredentials;
+pub use description::{LocalDescription, MediaSection};
+pub use dtls_certificate::DtlsCertificate;
+pub use dtls_info::DtlsInfo;
+#[cfg(feature = "echo-answerer")]
+pub use echo_answerer::EchoAnswerer;
+pub use error::{
+    CandidateError, CertificateError, DataChannelError, IceServerError, LargeTransferError,
+    PingError, ProbeError, RecvTimeout, SessionImportError, SocketConnectionError,
+};
+pub use event::SocketEvent;
+pub use exported_session::ExportedSession;
+pub use ice_server::{check_ice_server, IceServerReachability};
+pub use interfaces::{list_interfaces, InterfaceInfo};
+#[cfg(feature = "lz4")]
+pub use lz4::Lz4;
+pub use probe::{probe, ProbeResult};
+pub use queued::SendHandle;
+pub use resolver::Resolver;
+pub use signaling_retry::DEFAULT_RETRY_STATUSES;
+pub use socket::{Socket, SocketIo, SocketRx, SocketTx};
+pub use stats::SocketStats;
+pub use stream::{CandidateStream, SocketSink, SocketStream};
+pub use timings::{HandshakePhase, HandshakeTimings};
+pub use webrtc::util::{
+    fixed_big_int::FixedBigInt,
+    replay_detector::{
+        NoOpReplayDetector, ReplayDetector, SlidingWindowDetector, WrappedSlidingWindowDetector,
+    },
+};
 
 mod webrtc;
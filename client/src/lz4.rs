@@ -0,0 +1,166 @@
+// The LZ4 block format, as described in
+// https://github.com/lz4/lz4/blob/dev/doc/lz4_Block_format.md, with a greedy single-probe
+// match finder. Compression ratio is traded for simplicity, the output is valid LZ4 for
+// any decoder
+
+use crate::compression::Compression;
+
+const MIN_MATCH: usize = 4;
+// the last 5 bytes of a block are always literals
+const LAST_LITERALS: usize = 5;
+// the last match starts at least 12 bytes before the end of a block
+const MF_LIMIT: usize = 12;
+const MAX_OFFSET: usize = u16::MAX as usize;
+const HASH_LOG: u32 = 12;
+const SIZE_PREFIX: usize = 4;
+const MAX_DECOMPRESSED_SIZE: usize = 16 << 20;
+
+/// LZ4 compression for [`SocketConfig::set_compression`](crate::SocketConfig::set_compression).
+/// Only available with the `lz4` feature
+///
+/// Each message is an LZ4 block prefixed with its decompressed size as a little-endian
+/// `u32`, the format of `compress_prepend_size` and `decompress_size_prepended` in the
+/// lz4_flex crate, so a Rust server can decompress with those. Messages claiming to
+/// decompress to more than 16 MiB are rejected as malformed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Lz4;
+
+impl Compression for Lz4 {
+    fn compress(&self, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(SIZE_PREFIX + input.len() + input.len() / 255 + 16);
+        output.extend_from_slice(&(input.len() as u32).to_le_bytes());
+        compress_block(input, &mut output);
+        output
+    }
+
+    fn decompress(&self, input: &[u8]) -> Option<Vec<u8>> {
+        let (size, block) = input.split_at_checked(SIZE_PREFIX)?;
+        let size = u32::from_le_bytes(size.try_into().ok()?) as usize;
+        if size > MAX_DECOMPRESSED_SIZE {
+            return None;
+        }
+        // some encoders write no sequence at all for an empty message
+        if size == 0 && block.is_empty() {
+            return Some(Vec::new());
+        }
+        decompress_block(block, size)
+    }
+}
+
+fn compress_block(input: &[u8], output: &mut Vec<u8>) {
+    let mut table = vec![usize::MAX; 1 << HASH_LOG];
+    let mut anchor = 0;
+    let mut pos = 0;
+
+    if input.len() > MF_LIMIT {
+        let match_limit = input.len() - MF_LIMIT;
+        let extend_limit = input.len() - LAST_LITERALS;
+        while pos < match_limit {
+            let sequence = read_u32(input, pos);
+            let slot = &mut table[hash(sequence)];
+            let candidate = std::mem::replace(slot, pos);
+            if candidate == usize::MAX
+                || pos - candidate > MAX_OFFSET
+                || read_u32(input, candidate) != sequence
+            {
+                pos += 1;
+                continue;
+            }
+
+            let mut length = MIN_MATCH;
+            while pos + length < extend_limit && input[candidate + length] == input[pos + length] {
+                length += 1;
+            }
+            push_sequence(output, &input[anchor..pos], Some((pos - candidate, length)));
+            pos += length;
+            anchor = pos;
+        }
+    }
+    push_sequence(output, &input[anchor..], None);
+}
+
+// push_sequence writes a token, `literals` and, unless this is the last sequence, the
+// offset and length of the match following them
+fn push_sequence(output: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_length = matched.map_or(0, |(_, length)| length - MIN_MATCH);
+    let token = (literals.len().min(15) << 4) as u8 | match_length.min(15) as u8;
+    output.push(token);
+    if literals.len() >= 15 {
+        push_length(output, literals.len() - 15);
+    }
+    output.extend_from_slice(literals);
+    if let Some((offset, _)) = matched {
+        output.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_length >= 15 {
+            push_length(output, match_length - 15);
+        }
+    }
+}
+
+fn push_length(output: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        output.push(255);
+        length -= 255;
+    }
+    output.push(length as u8);
+}
+
+fn decompress_block(input: &[u8], size: usize) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(size);
+    let mut pos = 0;
+    loop {
+        let token = *input.get(pos)?;
+        pos += 1;
+
+        let mut literals = (token >> 4) as usize;
+        if literals == 15 {
+            literals = read_length(input, &mut pos, literals)?;
+        }
+        let end = pos.checked_add(literals)?;
+        if output.len() + literals > size {
+            return None;
+        }
+        output.extend_from_slice(input.get(pos..end)?);
+        pos = end;
+        // the last sequence has no match
+        if pos == input.len() {
+            break;
+        }
+
+        let offset = u16::from_le_bytes([*input.get(pos)?, *input.get(pos + 1)?]) as usize;
+        pos += 2;
+        let mut length = (token & 15) as usize;
+        if length == 15 {
+            length = read_length(input, &mut pos, length)?;
+        }
+        let length = length + MIN_MATCH;
+        if offset == 0 || offset > output.len() || output.len() + length > size {
+            return None;
+        }
+        // the match may overlap the bytes it produces
+        let start = output.len() - offset;
+        for i in start..start + length {
+            output.push(output[i]);
+        }
+    }
+    (output.len() == size).then_some(output)
+}
+
+fn read_length(input: &[u8], pos: &mut usize, mut length: usize) -> Option<usize> {
+    loop {
+        let byte = *input.get(*pos)?;
+        *pos += 1;
+        length = length.checked_add(byte as usize)?;
+        if byte != 255 {
+            return Some(length);
+        }
+    }
+}
+
+fn read_u32(input: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([input[pos], input[pos + 1], input[pos + 2], input[pos + 3]])
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
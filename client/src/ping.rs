@@ -0,0 +1,85 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use tokio::{
+    sync::oneshot,
+    time::{timeout_at, Instant},
+};
+
+use crate::{error::PingError, webrtc::sctp::association::Association};
+
+// Pings tracks the pings in flight. A ping is an SCTP HEARTBEAT carrying its sequence
+// number, which every SCTP peer echoes back unchanged in its HEARTBEAT ACK
+#[derive(Default)]
+pub(crate) struct Pings {
+    next_sequence: AtomicU64,
+    pending: Mutex<HashMap<u64, oneshot::Sender<()>>>,
+}
+
+impl Pings {
+    pub(crate) async fn ping(
+        &self,
+        association: &Association,
+        duration: Duration,
+    ) -> Result<Duration, PingError> {
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        let (echoed, echo) = oneshot::channel();
+        self.pending
+            .lock()
+            .expect("pending pings lock poisoned")
+            .insert(sequence, echoed);
+        let _pending = PendingPing {
+            pings: self,
+            sequence,
+        };
+
+        let sent = Instant::now();
+        association
+            .send_heartbeat(Bytes::copy_from_slice(&sequence.to_be_bytes()))
+            .await
+            .map_err(|_| PingError::NotConnected)?;
+        match timeout_at(sent + duration, echo).await {
+            Ok(_) => Ok(sent.elapsed()),
+            Err(_) => Err(PingError::Timeout),
+        }
+    }
+
+    // echoed completes the ping whose HEARTBEAT ACK has been received
+    pub(crate) fn echoed(&self, payload: &[u8]) {
+        let sequence = match <[u8; 8]>::try_from(payload) {
+            Ok(sequence) => u64::from_be_bytes(sequence),
+            Err(_) => return,
+        };
+        if let Some(echoed) = self
+            .pending
+            .lock()
+            .expect("pending pings lock poisoned")
+            .remove(&sequence)
+        {
+            let _ = echoed.send(());
+        }
+    }
+}
+
+// PendingPing forgets a ping once it has completed, timed out or been cancelled
+struct PendingPing<'a> {
+    pings: &'a Pings,
+    sequence: u64,
+}
+
+impl Drop for PendingPing<'_> {
+    fn drop(&mut self) {
+        self.pings
+            .pending
+            .lock()
+            .expect("pending pings lock poisoned")
+            .remove(&self.sequence);
+    }
+}
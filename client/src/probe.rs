@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use tokio::{
+    sync::broadcast,
+    time::{timeout_at, Instant},
+};
+
+use crate::{
+    candidate::CandidatePair,
+    config::SocketConfig,
+    error::ProbeError,
+    event::SocketEvent,
+    socket::{Socket, SocketIo},
+    timings::{HandshakePhase, HandshakeTimings},
+};
+
+/// A connection made by [`probe`] opened its data channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProbeResult {
+    /// How long each phase of establishing the connection took
+    pub timings: HandshakeTimings,
+    /// The candidate pair the connection went through
+    pub candidate_pair: Option<CandidatePair>,
+    /// Type of the local candidate of that pair, e.g. `host`
+    pub local_candidate_type: Option<String>,
+    /// Type of the server's candidate of that pair
+    pub remote_candidate_type: Option<String>,
+    /// Round trip time of the ping, if one was sent
+    pub ping: Option<Duration>,
+}
+
+/// Checks that the server at `server_url` can be connected to, e.g. for a synthetic monitor
+/// of the signaling server and the WebRTC server behind it. A connection is made with
+/// `config` and its data channel awaited; with `ping`, a [ping](SocketIo::ping) is then
+/// round-tripped. No messages are sent.
+///
+/// Everything has to complete within `timeout`, or the probe fails with
+/// [`ProbeError::Timeout`] naming the phase it got stuck in. The connection is closed
+/// before this returns, successful or not: an attempt still in progress is
+/// [aborted](crate::ConnectAbortHandle::abort), so no sockets or tasks are left behind.
+pub async fn probe(
+    server_url: &str,
+    config: SocketConfig,
+    timeout: Duration,
+    ping: bool,
+) -> Result<ProbeResult, ProbeError> {
+    let deadline = Instant::now() + timeout;
+    let (abort_handle, connect) = Socket::connect_abortable(server_url, config);
+    let mut progress = Progress {
+        events: abort_handle.events(),
+        phase: None,
+    };
+    tokio::pin!(connect);
+
+    let socket = match timeout_at(deadline, &mut connect).await {
+        Ok(connected) => connected?.1,
+        Err(_) => {
+            progress.catch_up();
+            let (_, connected) = tokio::join!(abort_handle.abort(), connect);
+            // the attempt may have completed just as it was aborted
+            if let Ok((_, socket)) = connected {
+                socket.close().await;
+            }
+            return Err(ProbeError::Timeout {
+                phase: progress.phase,
+            });
+        }
+    };
+
+    let result = check(&socket, &mut progress, deadline, ping).await;
+    socket.close().await;
+    result
+}
+
+// check waits for the data channel of `socket` to open, then pings the server if asked to
+async fn check(
+    socket: &SocketIo,
+    progress: &mut Progress,
+    deadline: Instant,
+    ping: bool,
+) -> Result<ProbeResult, ProbeError> {
+    if timeout_at(deadline, progress.wait_open(socket))
+        .await
+        .is_err()
+    {
+        progress.catch_up();
+        return Err(ProbeError::Timeout {
+            phase: progress.phase,
+        });
+    }
+
+    let ping = if ping {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        Some(socket.ping(remaining).await?)
+    } else {
+        None
+    };
+    let (local_candidate_type, remote_candidate_type) = socket
+        .selected_candidate_types()
+        .map_or((None, None), |(local, remote)| (Some(local), Some(remote)));
+    Ok(ProbeResult {
+        timings: socket.handshake_timings(),
+        candidate_pair: socket.selected_candidate_pair(),
+        local_candidate_type,
+        remote_candidate_type,
+        ping,
+    })
+}
+
+// Progress follows the phases of establishing the connection through its events
+struct Progress {
+    events: broadcast::Receiver<SocketEvent>,
+    // the phase in progress
+    phase: Option<HandshakePhase>,
+}
+
+impl Progress {
+    fn observe(&mut self, event: SocketEvent) {
+        match event {
+            SocketEvent::HandshakePhaseStarted { phase, .. } => self.phase = Some(phase),
+            SocketEvent::HandshakePhaseFinished { phase, .. } if self.phase == Some(phase) => {
+                self.phase = None
+            }
+            _ => {}
+        }
+    }
+
+    // catch_up observes the events emitted since the last call
+    fn catch_up(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => self.observe(event),
+                Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+                Err(_) => return,
+            }
+        }
+    }
+
+    // wait_open returns once the data channel of `socket` has opened
+    async fn wait_open(&mut self, socket: &SocketIo) {
+        // the timings are checked as well, in case the event was missed by lagging behind
+        while socket.handshake_timings().sctp_association.is_none() {
+            match self.events.recv().await {
+                Ok(SocketEvent::HandshakePhaseFinished {
+                    phase: HandshakePhase::SctpAssociation,
+                    ..
+                }) => return,
+                Ok(event) => self.observe(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                // the session holds the sender, so this doesn't happen while it is waited on
+                Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+            }
+        }
+    }
+}
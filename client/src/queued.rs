@@ -0,0 +1,138 @@
+use std::{
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+
+const QUEUED: u8 = 0;
+const TAKEN: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// A handle on a message sent with
+/// [`SocketIo::send_reliable_cancellable`](crate::SocketIo::send_reliable_cancellable), which
+/// cancels it while it is still queued
+///
+/// A message is taken from the queue right before it is written to the data channel, after
+/// waiting for the [cap on messages in flight](crate::SocketConfig::set_reliable_max_in_flight)
+/// and, with [send coalescing](crate::SocketConfig::set_send_coalescing), when it is gathered
+/// into a datagram. From then on it is sent in full, and retransmitted until received.
+/// Cancelling races with the write loop taking the message: [`cancel`](Self::cancel) tells
+/// which one won, so a last-write-wins update is sent again unless the stale one was
+/// cancelled.
+#[derive(Debug, Clone)]
+pub struct SendHandle {
+    state: Arc<AtomicU8>,
+}
+
+impl SendHandle {
+    /// Cancels the message unless it has been taken from the queue. Returns whether it is
+    /// cancelled, so that it will never be sent. A cancelled message keeps its place in the
+    /// queue until the write loop gets to it and drops it
+    pub fn cancel(&self) -> bool {
+        match self
+            .state
+            .compare_exchange(QUEUED, CANCELLED, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => true,
+            Err(state) => state == CANCELLED,
+        }
+    }
+
+    /// Returns whether the message has been taken from the queue to be sent, so that it can't
+    /// be cancelled anymore
+    pub fn is_taken(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == TAKEN
+    }
+}
+
+// Queued is a message waiting in a send queue, which the write loop takes to send it
+pub(crate) trait Queued: Send + 'static {
+    // size returns the size of the message in bytes
+    fn size(&self) -> usize;
+
+    // take returns the message to send, or None if it has been cancelled or its deadline
+    // has passed
+    fn take(self) -> Option<Box<[u8]>>;
+
+    // deadline returns when the message is given up unless the server has received it
+    fn deadline(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+impl Queued for Box<[u8]> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn take(self) -> Option<Box<[u8]>> {
+        Some(self)
+    }
+}
+
+// ReliableMessage is a message in the queue of the reliable data channel, which may be
+// cancelled through its SendHandle, or given up at its deadline
+pub(crate) struct ReliableMessage {
+    message: Box<[u8]>,
+    state: Option<Arc<AtomicU8>>,
+    deadline: Option<SystemTime>,
+}
+
+impl ReliableMessage {
+    pub(crate) fn new(message: Box<[u8]>) -> Self {
+        ReliableMessage {
+            message,
+            state: None,
+            deadline: None,
+        }
+    }
+
+    pub(crate) fn with_deadline(message: Box<[u8]>, deadline: SystemTime) -> Self {
+        ReliableMessage {
+            message,
+            state: None,
+            deadline: Some(deadline),
+        }
+    }
+
+    pub(crate) fn cancellable(message: Box<[u8]>) -> (Self, SendHandle) {
+        let state = Arc::new(AtomicU8::new(QUEUED));
+        let handle = SendHandle {
+            state: Arc::clone(&state),
+        };
+        let message = ReliableMessage {
+            message,
+            state: Some(state),
+            deadline: None,
+        };
+        (message, handle)
+    }
+}
+
+impl Queued for ReliableMessage {
+    fn size(&self) -> usize {
+        self.message.len()
+    }
+
+    fn take(self) -> Option<Box<[u8]>> {
+        if self
+            .deadline
+            .is_some_and(|deadline| SystemTime::now() >= deadline)
+        {
+            return None;
+        }
+        match self.state {
+            Some(state) => state
+                .compare_exchange(QUEUED, TAKEN, Ordering::SeqCst, Ordering::SeqCst)
+                .ok()
+                .map(|_| self.message),
+            None => Some(self.message),
+        }
+    }
+
+    fn deadline(&self) -> Option<SystemTime> {
+        self.deadline
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use tokio::time::{sleep, Instant};
+
+/// Rate and burst size of one token bucket
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RateLimit {
+    pub(crate) per_second: u64,
+    pub(crate) burst: u64,
+}
+
+// TokenBucket refills continuously at `per_second` tokens up to `burst`. A take that
+// cannot be covered drives the balance negative, and the caller waits until it is
+// paid back, so messages larger than the bucket still get through
+struct TokenBucket {
+    per_second: f64,
+    burst: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        let burst = limit.burst.max(1) as f64;
+        TokenBucket {
+            per_second: limit.per_second.max(1) as f64,
+            burst,
+            tokens: burst,
+            refilled_at: now,
+        }
+    }
+
+    // take removes `cost` tokens and returns how long to wait before they are covered
+    fn take(&mut self, cost: f64, now: Instant) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.burst);
+        self.refilled_at = now;
+
+        self.tokens -= cost;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+}
+
+/// SendRateLimiter paces outgoing messages by payload bytes and by message count
+pub(crate) struct SendRateLimiter {
+    bytes: Option<TokenBucket>,
+    packets: Option<TokenBucket>,
+}
+
+impl SendRateLimiter {
+    /// Returns `None` when neither limit is set, so the send path stays unpaced
+    pub(crate) fn new(bytes: Option<RateLimit>, packets: Option<RateLimit>) -> Option<Self> {
+        if bytes.is_none() && packets.is_none() {
+            return None;
+        }
+        let now = Instant::now();
+        Some(SendRateLimiter {
+            bytes: bytes.map(|limit| TokenBucket::new(limit, now)),
+            packets: packets.map(|limit| TokenBucket::new(limit, now)),
+        })
+    }
+
+    /// Waits until a message of `len` bytes may be sent. Returns immediately while the
+    /// buckets hold enough tokens
+    pub(crate) async fn acquire(&mut self, len: usize) {
+        let now = Instant::now();
+        let mut delay = Duration::ZERO;
+        if let Some(bucket) = &mut self.bytes {
+            delay = delay.max(bucket.take(len as f64, now));
+        }
+        if let Some(bucket) = &mut self.packets {
+            delay = delay.max(bucket.take(1.0, now));
+        }
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(per_second: u64, burst: u64) -> (TokenBucket, Instant) {
+        let start = Instant::now();
+        (
+            TokenBucket::new(RateLimit { per_second, burst }, start),
+            start,
+        )
+    }
+
+    #[test]
+    fn burst_is_available_at_once() {
+        let (mut bucket, start) = bucket(100, 10);
+        for _ in 0..10 {
+            assert_eq!(bucket.take(1.0, start), Duration::ZERO);
+        }
+        assert_eq!(bucket.take(1.0, start), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn refills_at_the_rate() {
+        let (mut bucket, start) = bucket(100, 10);
+        assert_eq!(bucket.take(10.0, start), Duration::ZERO);
+        // 50 ms refill 5 tokens
+        let later = start + Duration::from_millis(50);
+        assert_eq!(bucket.take(5.0, later), Duration::ZERO);
+        assert_eq!(bucket.take(1.0, later), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn refill_stops_at_the_burst() {
+        let (mut bucket, start) = bucket(100, 10);
+        assert_eq!(bucket.take(10.0, start), Duration::ZERO);
+        let later = start + Duration::from_secs(60);
+        assert_eq!(bucket.take(10.0, later), Duration::ZERO);
+        assert_eq!(bucket.take(1.0, later), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn take_larger_than_the_burst_waits_it_off() {
+        let (mut bucket, start) = bucket(100, 10);
+        // 40 tokens short
+        assert_eq!(bucket.take(50.0, start), Duration::from_millis(400));
+        // once paid back, the bucket refills from empty
+        let paid_back = start + Duration::from_millis(400);
+        assert_eq!(bucket.take(0.0, paid_back), Duration::ZERO);
+        assert_eq!(bucket.take(1.0, paid_back), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn clock_going_back_refills_nothing() {
+        let (mut bucket, start) = bucket(100, 10);
+        let later = start + Duration::from_millis(50);
+        assert_eq!(bucket.take(10.0, later), Duration::ZERO);
+        assert_eq!(bucket.take(1.0, start), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn zero_rate_and_burst_are_clamped_to_one() {
+        let (mut bucket, start) = bucket(0, 0);
+        assert_eq!(bucket.take(1.0, start), Duration::ZERO);
+        assert_eq!(bucket.take(1.0, start), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,36 @@
+use std::{io, net::IpAddr, sync::Arc};
+
+use async_trait::async_trait;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+
+/// Resolves host names to addresses, in place of the system resolver
+///
+/// Set one with [`SocketConfig::set_resolver`](crate::SocketConfig::set_resolver).
+#[async_trait]
+pub trait Resolver: Send + Sync {
+    /// Returns the addresses of `host`. An empty list fails the lookup
+    async fn resolve(&self, host: &str) -> Vec<IpAddr>;
+}
+
+// ReqwestResolver lets the signaling client look up hosts through a Resolver
+pub(crate) struct ReqwestResolver(pub(crate) Arc<dyn Resolver>);
+
+impl Resolve for ReqwestResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = Arc::clone(&self.0);
+        Box::pin(async move {
+            let ips = resolver.resolve(name.as_str()).await;
+            if ips.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no addresses resolved for {}", name.as_str()),
+                )
+                .into());
+            }
+            // reqwest replaces the port with the one of the url
+            let addrs: Addrs = Box::new(ips.into_iter().map(|ip| (ip, 0).into()));
+            Ok(addrs)
+        })
+    }
+}
@@ -0,0 +1,137 @@
+// session_response reads the JSON answer of a webrtc-unreliable server. It is parsed with
+// tinyjson, or with serde_json when the `serde-json` feature is enabled. Both reject a
+// response without an answer sdp, and read a missing candidate as no candidates
+
+#[derive(Clone)]
+pub(crate) struct SessionAnswer {
+    pub(crate) sdp: String,
+}
+
+pub(crate) struct SessionCandidate {
+    pub(crate) candidate: String,
+}
+
+pub(crate) struct JsSessionResponse {
+    pub(crate) answer: SessionAnswer,
+    pub(crate) candidates: Vec<SessionCandidate>,
+}
+
+pub(crate) fn get_session_response(input: &str) -> Result<JsSessionResponse, String> {
+    #[cfg(not(feature = "serde-json"))]
+    return tinyjson_session_response(input);
+    #[cfg(feature = "serde-json")]
+    return serde_json_session_response(input);
+}
+
+#[cfg(any(not(feature = "serde-json"), feature = "internals"))]
+pub(crate) fn tinyjson_session_response(input: &str) -> Result<JsSessionResponse, String> {
+    use std::collections::HashMap;
+
+    use tinyjson::JsonValue;
+
+    // session_candidate reads a candidate of the session response, given as
+    // `{"candidate": ...}` or as the bare candidate string
+    fn session_candidate(json: &JsonValue) -> Option<SessionCandidate> {
+        let candidate: &String = match json {
+            JsonValue::String(candidate) => candidate,
+            JsonValue::Object(object) => object.get("candidate")?.get()?,
+            _ => return None,
+        };
+        Some(SessionCandidate {
+            candidate: candidate.clone(),
+        })
+    }
+
+    let json_obj: JsonValue = input.parse().map_err(|err| format!("{}", err))?;
+    let response: &HashMap<String, JsonValue> =
+        json_obj.get().ok_or("the response is not an object")?;
+
+    let answer: &HashMap<String, JsonValue> = response
+        .get("answer")
+        .and_then(JsonValue::get)
+        .ok_or("the response has no answer")?;
+    let sdp: &String = answer
+        .get("sdp")
+        .and_then(JsonValue::get)
+        .ok_or("the answer has no sdp")?;
+
+    // the server sends either a single candidate or an array of them, entries without a
+    // candidate string are left out
+    let candidates = match response.get("candidate") {
+        Some(JsonValue::Array(candidates)) => {
+            candidates.iter().filter_map(session_candidate).collect()
+        }
+        Some(candidate) => session_candidate(candidate).into_iter().collect(),
+        None => vec![],
+    };
+
+    Ok(JsSessionResponse {
+        answer: SessionAnswer { sdp: sdp.clone() },
+        candidates,
+    })
+}
+
+#[cfg(feature = "serde-json")]
+pub(crate) fn serde_json_session_response(input: &str) -> Result<JsSessionResponse, String> {
+    use serde::de::IgnoredAny;
+
+    #[derive(Deserialize)]
+    struct Response {
+        answer: Answer,
+        #[serde(default)]
+        candidate: Option<Candidates>,
+    }
+
+    #[derive(Deserialize)]
+    struct Answer {
+        sdp: String,
+    }
+
+    // the server sends either a single candidate or an array of them
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Candidates {
+        Many(Vec<Candidate>),
+        One(Candidate),
+    }
+
+    // a candidate given as `{"candidate": ...}` or as the bare candidate string. Entries
+    // without a candidate string are left out
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Candidate {
+        Object { candidate: String },
+        Bare(String),
+        Other(IgnoredAny),
+    }
+
+    impl Candidate {
+        fn into_session_candidate(self) -> Option<SessionCandidate> {
+            match self {
+                Candidate::Object { candidate } | Candidate::Bare(candidate) => {
+                    Some(SessionCandidate { candidate })
+                }
+                Candidate::Other(_) => None,
+            }
+        }
+    }
+
+    let response: Response = serde_json::from_str(input).map_err(|err| err.to_string())?;
+    let candidates = match response.candidate {
+        Some(Candidates::Many(candidates)) => candidates
+            .into_iter()
+            .filter_map(Candidate::into_session_candidate)
+            .collect(),
+        Some(Candidates::One(candidate)) => {
+            candidate.into_session_candidate().into_iter().collect()
+        }
+        None => vec![],
+    };
+
+    Ok(JsSessionResponse {
+        answer: SessionAnswer {
+            sdp: response.answer.sdp,
+        },
+        candidates,
+    })
+}
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::error::SocketConnectionError;
+
+/// The statuses retried by default with [`SocketConfig::set_signaling_retry`], which load
+/// balancers answer with while the server scales up
+///
+/// [`SocketConfig::set_signaling_retry`]: crate::SocketConfig::set_signaling_retry
+pub const DEFAULT_RETRY_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+// UNBOUNDED_RETRY_DELAY is the delay between attempts without a SignalingRetry, which
+// retries sending until the server can be reached
+const UNBOUNDED_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// SignalingRetry bounds the attempts at sending the offer
+#[derive(Debug, Clone)]
+pub(crate) struct SignalingRetry {
+    pub(crate) attempts: u32,
+    pub(crate) base_delay: Duration,
+    pub(crate) max_delay: Duration,
+    pub(crate) statuses: Vec<StatusCode>,
+}
+
+impl SignalingRetry {
+    // delay doubles from base_delay with every failed attempt, up to max_delay, and is
+    // jittered into its upper half so that clients failed together don't retry together
+    fn delay(&self, failed_attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failed_attempts.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        let half = delay / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=delay - half)
+    }
+}
+
+// send sends the request made by `request` until it gets a response. Without `retry`,
+// sending is retried until the server can be reached, and any response is returned. With
+// it, responses with a retryable status are retried too, and the last error is returned
+// once the attempts are used up.
+pub(crate) async fn send<F>(
+    retry: Option<&SignalingRetry>,
+    mut request: F,
+) -> Result<Response, SocketConnectionError>
+where
+    F: FnMut() -> RequestBuilder,
+{
+    let retry = match retry {
+        Some(retry) => retry,
+        None => loop {
+            match request().send().await {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    warn!("Could not send request, original error: {:?}", err);
+                    sleep(UNBOUNDED_RETRY_DELAY).await;
+                }
+            }
+        },
+    };
+
+    let mut attempt = 1;
+    loop {
+        let reason = match request().send().await {
+            Ok(response) if !retry.statuses.contains(&response.status()) => return Ok(response),
+            Ok(response) => format!("the signaling server responded with {}", response.status()),
+            Err(err) => format!("could not send the signaling request: {}", err),
+        };
+        if attempt >= retry.attempts {
+            return Err(SocketConnectionError::Signaling {
+                reason: format!("{}, after {} attempts", reason, attempt),
+            });
+        }
+
+        let delay = retry.delay(attempt);
+        warn!(
+            "Signaling attempt {} of {} failed, retrying in {:?}: {}",
+            attempt, retry.attempts, delay, reason
+        );
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry(base_delay: Duration, max_delay: Duration) -> SignalingRetry {
+        SignalingRetry {
+            attempts: 10,
+            base_delay,
+            max_delay,
+            statuses: Vec::new(),
+        }
+    }
+
+    // assert_jittered samples the delay after `failed_attempts`, which must stay within the
+    // upper half of `expected`
+    fn assert_jittered(retry: &SignalingRetry, failed_attempts: u32, expected: Duration) {
+        for _ in 0..1000 {
+            let delay = retry.delay(failed_attempts);
+            assert!(
+                expected / 2 <= delay && delay <= expected,
+                "{:?} after {} failed attempts, expected {:?} jittered",
+                delay,
+                failed_attempts,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn delay_doubles_with_every_failed_attempt() {
+        let retry = retry(Duration::from_millis(100), Duration::from_secs(10));
+        assert_jittered(&retry, 1, Duration::from_millis(100));
+        assert_jittered(&retry, 2, Duration::from_millis(200));
+        assert_jittered(&retry, 3, Duration::from_millis(400));
+        assert_jittered(&retry, 4, Duration::from_millis(800));
+    }
+
+    #[test]
+    fn delay_is_capped_at_max_delay() {
+        let retry = retry(Duration::from_millis(100), Duration::from_millis(300));
+        assert_jittered(&retry, 3, Duration::from_millis(300));
+        assert_jittered(&retry, 10, Duration::from_millis(300));
+        // the factor saturates instead of overflowing
+        assert_jittered(&retry, u32::MAX, Duration::from_millis(300));
+    }
+
+    #[test]
+    fn delay_is_spread_over_the_upper_half() {
+        let retry = retry(Duration::from_secs(1), Duration::from_secs(1));
+        let delays: Vec<_> = (0..1000).map(|_| retry.delay(1)).collect();
+        // both quarters of the upper half are hit
+        assert!(delays
+            .iter()
+            .any(|&delay| delay < Duration::from_millis(750)));
+        assert!(delays
+            .iter()
+            .any(|&delay| delay > Duration::from_millis(750)));
+    }
+
+    #[test]
+    fn zero_delay_stays_zero() {
+        let retry = retry(Duration::ZERO, Duration::ZERO);
+        assert_eq!(retry.delay(1), Duration::ZERO);
+        assert_eq!(retry.delay(5), Duration::ZERO);
+    }
+}
@@ -1,219 +1,2964 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr, UdpSocket as StdUdpSocket},
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Arc, Mutex as StdMutex, Weak,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Result;
+use bytes::Bytes;
+use reqwest::{header::HeaderValue, Client as HttpClient, Method, Proxy, Response, StatusCode};
+use tokio::{
+    sync::{broadcast, mpsc, watch, Mutex},
+    task::JoinHandle,
+    time::timeout,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, trace, warn, Instrument, Span};
+use url::Url;
+
+use crate::webrtc::{
+    api::Api,
+    data_channel::{data_channel_init::RTCDataChannelInit, internal::data_channel::DataChannel},
+    dtls_transport::dtls_transport_state::RTCDtlsTransportState,
+    ice::{candidate::CandidateType, external_ip_mapper::ExternalIpMapper, RttHistory},
+    ice_transport::{
+        ice_connection_state::RTCIceConnectionState, ice_gatherer_state::RTCIceGathererState,
+    },
+    peer_connection::{sdp::session_description::RTCSessionDescription, RTCPeerConnection},
+    sctp::association::{Association, DEFAULT_MAX_MESSAGE_SIZE},
+    RECEIVE_MTU,
+};
+
+use super::{
+    abort::ConnectAbortHandle,
+    addr_cell::AddrCell,
+    candidate::{add_remote_candidate, gathered_candidates, CandidatePair, RttSample},
+    coalesce::{coalesce, deframe, frame, SendCoalescing},
+    compression::{PayloadCompression, COMPRESSION_HEADER_SIZE},
+    config::{
+        ChannelClosedFn, IceRole, InboundBudget, PayloadType, Signaling, SignalingProxy,
+        SignalingRequest, SocketConfig, MAX_ICE_CHECK_INTERVAL, MIN_DTLS_MTU,
+        MIN_ICE_CHECK_INTERVAL,
+    },
+    congestion::CongestionInfo,
+    connection_id::ConnectionId,
+    credentials::SessionCredentials,
+    description::{check_answer, LocalDescription},
+    dtls_certificate::DtlsCertificate,
+    dtls_info::{DtlsInfo, DtlsInfoCell},
+    error::{
+        CandidateError, DataChannelError, LargeTransferError, PingError, RecvTimeout,
+        SocketConnectionError,
+    },
+    event::{EventSender, SocketEvent},
+    exported_session::ExportedSession,
+    idle::Activity,
+    inbound::{InboundSender, InboundStaging, PushError, SourceAddr, WeakInboundSender},
+    large::{self, LargeReceiver},
+    ping::Pings,
+    queued::{Queued, ReliableMessage, SendHandle},
+    rate_limit::SendRateLimiter,
+    session_response::{get_session_response, JsSessionResponse},
+    signaling_retry,
+    stats::{SocketStats, Traffic},
+    stream::{SocketSink, SocketStream},
+    timings::{HandshakePhase, HandshakeTimings, TimingsCell},
+    whip,
+};
+
+// the read buffer fits the largest message a server may send, which is the default
+// max-message-size since the offer doesn't advertise one. A larger message doesn't fit and
+// ends the read loop
+const MESSAGE_SIZE: usize = DEFAULT_MAX_MESSAGE_SIZE as usize;
+// the SCTP stream of the reliable data channel; the data channel itself uses stream 0
+const RELIABLE_STREAM_ID: u16 = 2;
+// the SCTP stream of the large transfer data channel
+const LARGE_STREAM_ID: u16 = 4;
+const CLIENT_CHANNEL_SIZE: usize = 8;
+
+pub struct Socket;
+
+/// The send and receive halves of a connection returned by [`Socket::connect`]
+pub struct SocketIo {
+    /// Messages sent here are written to the data channel
+    pub to_server_sender: mpsc::Sender<Box<[u8]>>,
+    /// Messages read from the data channel are delivered here. Yields `None` once the
+    /// data channel has closed
+    pub to_client_receiver: mpsc::Receiver<Box<[u8]>>,
+    /// With [`SocketConfig::set_source_addrs`], messages read from the data channel are
+    /// delivered here instead, along with the address of the server they came from
+    pub addressed_receiver: Option<mpsc::Receiver<(Box<[u8]>, SocketAddr)>>,
+    /// With [`SocketConfig::set_payload_types`], messages read from the data channel are
+    /// delivered here instead, along with the payload type the server sent them as
+    pub typed_receiver: Option<mpsc::Receiver<(Box<[u8]>, PayloadType)>>,
+    // messages for the reliable data channel
+    reliable_sender: mpsc::Sender<ReliableMessage>,
+    // chunks for the large transfer data channel
+    large_sender: mpsc::Sender<Box<[u8]>>,
+    // the payloads read from the large transfer data channel, if it is enabled
+    large_receiver: Option<LargeReceiver>,
+    session: Arc<Session>,
+}
+
+impl SocketIo {
+    /// Returns the id of this connection, which prefixes its log lines. It is kept across
+    /// [`restart_ice`](Self::restart_ice)
+    pub fn id(&self) -> ConnectionId {
+        self.session.id
+    }
+
+    /// Subscribes to the events of this connection. Only events emitted after subscribing
+    /// are received
+    pub fn events(&self) -> broadcast::Receiver<SocketEvent> {
+        self.session.events.subscribe()
+    }
+
+    /// Returns how long each phase of establishing the connection took so far. Phases
+    /// complete in the background after `connect` returns, so poll this once the first
+    /// message has been received for a complete picture
+    pub fn handshake_timings(&self) -> HandshakeTimings {
+        self.session.timings.get()
+    }
+
+    /// Returns the DTLS version and cipher suite negotiated for this connection, or `None`
+    /// until the DTLS handshake has completed
+    pub fn dtls_info(&self) -> Option<DtlsInfo> {
+        self.session.dtls_info.get()
+    }
+
+    /// Returns the offer signaled for the current connection, parsed. It is replaced by
+    /// [`restart_ice`](Self::restart_ice)
+    pub fn local_description(&self) -> Option<LocalDescription> {
+        self.session
+            .local_description
+            .lock()
+            .expect("local description lock poisoned")
+            .clone()
+    }
+
+    /// Returns the candidate pair the current connection goes through, or `None` until ICE
+    /// has selected one. Reconnecting to the same server with it set as
+    /// [`SocketConfig::set_cached_candidate_pair`] skips gathering on every interface
+    pub fn selected_candidate_pair(&self) -> Option<CandidatePair> {
+        *self
+            .session
+            .selected_pair
+            .lock()
+            .expect("selected pair lock poisoned")
+    }
+
+    /// Returns the role the ICE agent of the current connection plays in the connectivity
+    /// checks, or `None` until they have started. It is controlling unless forced with
+    /// [`SocketConfig::set_ice_role`]; a server claiming the same role is a common cause of
+    /// ICE never connecting
+    pub fn ice_role(&self) -> Option<IceRole> {
+        *self
+            .session
+            .ice_role
+            .lock()
+            .expect("ICE role lock poisoned")
+    }
+
+    // selected_candidate_types returns the types of the local and remote candidates of the
+    // selected candidate pair, e.g. `host`
+    pub(crate) fn selected_candidate_types(&self) -> Option<(String, String)> {
+        self.session
+            .selected_pair_types
+            .lock()
+            .expect("selected pair types lock poisoned")
+            .clone()
+    }
+
+    /// Returns the DTLS certificate and ICE credentials the current connection was offered
+    /// with, or `None` until it has been signaled. Offering them again with
+    /// [`SocketConfig::set_session_credentials`] keeps the offer of a reconnection stable
+    pub fn session_credentials(&self) -> Option<SessionCredentials> {
+        self.session
+            .credentials
+            .lock()
+            .expect("credentials lock poisoned")
+            .clone()
+    }
+
+    /// Returns the state needed to reconnect to the same server quickly, possibly from
+    /// another process, or `None` until ICE has selected a candidate pair. It holds the
+    /// private key of the certificate, see [`ExportedSession`] and
+    /// [`SocketConfig::set_exported_session`]
+    pub fn export_session(&self) -> Option<ExportedSession> {
+        Some(ExportedSession {
+            candidate_pair: self.selected_candidate_pair()?,
+            credentials: self.session_credentials()?,
+        })
+    }
+
+    /// Returns the fingerprint of the DTLS certificate generated for this connection, as
+    /// advertised in the offer's `a=fingerprint` attribute, e.g. `sha-256 3A:9F:...`. A new
+    /// certificate is generated by [`restart_ice`](Self::restart_ice), unless one is set
+    /// with [`SocketConfig::set_certificate`]
+    pub fn local_dtls_fingerprint(&self) -> String {
+        self.session.dtls_info.local_fingerprint()
+    }
+
+    /// Returns the fingerprint of the DTLS certificate presented by the server, or `None`
+    /// until the DTLS handshake has completed
+    pub fn remote_dtls_fingerprint(&self) -> Option<String> {
+        self.dtls_info()?.remote_fingerprint
+    }
+
+    /// Re-establishes the connection over a new network path, e.g. after a switch from
+    /// Wi-Fi to cellular, while keeping the channel halves of this `SocketIo`.
+    ///
+    /// New ICE credentials are generated, local candidates are gathered again and a new
+    /// offer is signaled. Servers built on webrtc-unreliable treat every offer as a new
+    /// session, so the DTLS and SCTP sessions are set up anew as well. Messages in flight
+    /// on the old path are lost, and messages sent meanwhile are queued until the new data
+    /// channel opens.
+    pub async fn restart_ice(&self) -> Result<(), SocketConnectionError> {
+        self.session.restart().await
+    }
+
+    /// Closes the connection, ending the read and write loops. The messages queued before
+    /// the call are sent first, and the connection is shut down once the server has
+    /// acknowledged them, or once [`SocketConfig::set_drain_timeout`] has passed. With
+    /// [`Signaling::Whip`], the session's resource is deleted as well. A later
+    /// [`restart_ice`](Self::restart_ice) connects again
+    pub async fn close(&self) {
+        self.session.close().await
+    }
+
+    /// Waits until the data channel has opened, and messages are sent rather than queued.
+    /// This is for [`Socket::connect_background`]: if establishing the connection fails,
+    /// the error is returned, once, and [`SocketConnectionError::Closed`] afterwards.
+    /// Without a response from the server, it waits for as long as the connection attempt
+    /// lasts, so a deadline is best put around it with [`tokio::time::timeout`]
+    pub async fn ready(&self) -> Result<(), SocketConnectionError> {
+        self.session.ready().await
+    }
+
+    /// Closes the send direction of the data channels, while messages from the server keep
+    /// arriving, like the half-close of a TCP socket, e.g. to tell the server that the
+    /// client is leaving while receiving its last state updates. Messages queued before the
+    /// call are still sent, then the outgoing SCTP streams are reset. Later sends fail with
+    /// [`SocketConnectionError::SendClosed`].
+    ///
+    /// Servers following the data channel spec close their direction in response, which
+    /// ends receiving with a [`SocketEvent::RecvClosed`]. The send direction stays closed
+    /// across [`restart_ice`](Self::restart_ice)
+    pub fn close_send(&self) {
+        self.session.close_send()
+    }
+
+    /// Returns the largest message which can be sent. Until the data channel has opened,
+    /// the server is assumed to accept the default of 64 KiB
+    pub fn max_message_size(&self) -> usize {
+        self.session.max_message_size()
+    }
+
+    /// Stops reading from the data channel, e.g. to ride out a transient overload without
+    /// tearing down the connection. Messages already read keep being delivered to
+    /// `to_client_receiver`, anything arriving afterwards waits in the SCTP receive buffer.
+    /// Once that buffer is full, the receive window advertised to the server drops to
+    /// zero, so the server stops sending; being unreliable, the data channel then lets the
+    /// server abandon messages rather than queue them up indefinitely.
+    ///
+    /// The pause is kept across [`restart_ice`](Self::restart_ice).
+    pub fn pause_recv(&self) {
+        self.session.recv_paused.send_replace(true);
+    }
+
+    /// Resumes reading from the data channel after [`pause_recv`](Self::pause_recv),
+    /// starting with the messages buffered meanwhile
+    pub fn resume_recv(&self) {
+        self.session.recv_paused.send_replace(false);
+    }
+
+    /// Returns whether reading is paused by [`pause_recv`](Self::pause_recv)
+    pub fn is_recv_paused(&self) -> bool {
+        *self.session.recv_paused.borrow()
+    }
+
+    /// Sends a message, waiting while the outgoing queue is full. Unlike sending through
+    /// `to_server_sender`, a message larger than [`max_message_size`](Self::max_message_size)
+    /// is rejected with [`SocketConnectionError::MessageTooLarge`]
+    ///
+    /// An empty message is delivered as a message of its own. SCTP can't carry empty
+    /// messages, so it goes out as a single zero byte marked with the empty-message PPID of
+    /// its [`PayloadType`], as data channels do. Empty messages from the server are
+    /// received as distinct empty messages the same way
+    pub async fn send(&self, message: Box<[u8]>) -> Result<(), SocketConnectionError> {
+        self.session.send(&self.to_server_sender, message).await
+    }
+
+    /// Sends a message over the reliable data channel, which retransmits it until the
+    /// server has received it and delivers it in order with the other reliable messages,
+    /// e.g. for a chat message. Fails with [`SocketConnectionError::NoReliableChannel`]
+    /// unless enabled with [`SocketConfig::set_reliable_channel`]
+    pub async fn send_reliable(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        self.session
+            .send_reliable(&self.reliable_sender, message)
+            .await
+    }
+
+    /// Sends a message over the reliable data channel like
+    /// [`send_reliable`](Self::send_reliable), returning a [`SendHandle`] which cancels it as
+    /// long as it waits in the outgoing queue, e.g. to replace a state update which has gone
+    /// stale before it was sent. Once taken from the queue, it is sent regardless
+    pub async fn send_reliable_cancellable(
+        &self,
+        message: &[u8],
+    ) -> Result<SendHandle, SocketConnectionError> {
+        self.session
+            .send_reliable_cancellable(&self.reliable_sender, message)
+            .await
+    }
+
+    /// Sends a message over the reliable data channel like
+    /// [`send_reliable`](Self::send_reliable), giving it up unless the server has received
+    /// it within `timeout`, e.g. for a time-sensitive command which is worthless once late.
+    /// This bounds how long it holds up the reliable messages sent after it.
+    ///
+    /// A message still waiting in the outgoing queue at the deadline is dropped. One
+    /// already written is given up by SCTP at its first retransmission after the deadline,
+    /// and the server told to skip it, so the messages behind it are still delivered, in
+    /// order. That takes a server supporting partial reliability (RFC 3758), as data
+    /// channel implementations do; otherwise a written message is retransmitted until
+    /// received, as with `send_reliable`. Whether a message was given up isn't reported.
+    /// With [send coalescing](SocketConfig::set_send_coalescing), the message goes in a
+    /// datagram of its own, so that no other message is given up with it
+    pub async fn send_reliable_timeout(
+        &self,
+        message: &[u8],
+        timeout: Duration,
+    ) -> Result<(), SocketConnectionError> {
+        self.session
+            .send_reliable_timeout(&self.reliable_sender, message, timeout)
+            .await
+    }
+
+    /// Sends `payload` over the large transfer data channel, split into chunks which fit a
+    /// datagram each, waiting while the outgoing queue is full. The chunks are
+    /// retransmitted until the server has received them, and the payloads sent this way
+    /// arrive whole and in order. Fails with [`SocketConnectionError::NoLargeTransfers`]
+    /// unless enabled with [`SocketConfig::set_large_transfers`]
+    ///
+    /// Dropping the returned future before it completes leaves the server with part of
+    /// the payload, which it discards once the next payload starts
+    pub async fn send_large(&self, payload: Bytes) -> Result<(), SocketConnectionError> {
+        self.session.send_large(&self.large_sender, payload).await
+    }
+
+    /// Sends a message over the unreliable data channel, like [`send`](Self::send), which
+    /// sends it once and may lose or reorder it, e.g. for a position update
+    pub async fn send_unreliable(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        self.send(message.into()).await
+    }
+
+    /// Sends a message without waiting. While more bytes than the
+    /// [send buffer threshold](SocketConfig::set_send_buffer_threshold) are buffered on the
+    /// data channel, or the outgoing queue is full, the message is refused with
+    /// [`SocketConnectionError::WouldBlock`], so that the caller can drop a stale update
+    /// instead of queueing it
+    pub fn try_send(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        self.session.try_send(&self.to_server_sender, message)
+    }
+
+    /// Returns the number of bytes written to the data channel but not yet acknowledged by
+    /// the server. Messages still waiting in the outgoing queue aren't counted
+    pub fn buffered_amount(&self) -> usize {
+        self.session.buffered_amount()
+    }
+
+    /// Returns the identifier of the SCTP stream carrying the data channel, e.g. to match
+    /// the server's per-stream logs, or `None` until the data channel has opened
+    pub fn channel_stream_id(&self) -> Option<u16> {
+        self.session
+            .data_channel
+            .lock()
+            .expect("data channel lock poisoned")
+            .as_ref()
+            .map(|data_channel| data_channel.stream_identifier())
+    }
+
+    /// Returns the congestion control state of the connection, or `None` until the data
+    /// channel has opened. [`CongestionInfo::is_congested`] tells whether sending is held
+    /// back by the network, e.g. to lower the rate of state updates until it clears
+    pub async fn congestion(&self) -> Option<CongestionInfo> {
+        self.session.congestion().await
+    }
+
+    /// Returns the payload and wire bytes sent and received since the socket connected. The
+    /// two tell the application's throughput apart from what the network carried, e.g. to
+    /// report goodput, or the overhead of small messages
+    pub fn stats(&self) -> SocketStats {
+        self.session.traffic.stats()
+    }
+
+    /// Returns the number of messages sent through `to_server_sender` which the write loop
+    /// hasn't picked up yet. Permits reserved on the sender count as well. A count that keeps
+    /// growing means messages are produced faster than they can be sent
+    pub fn pending_outbound(&self) -> usize {
+        self.to_server_sender.max_capacity() - self.to_server_sender.capacity()
+    }
+
+    /// Returns the round trip times of the latest ICE connectivity checks answered on the
+    /// selected candidate pair, oldest first, e.g. to compute jitter from. As many are kept as
+    /// set with [`SocketConfig::set_rtt_history_size`], across
+    /// [`restart_ice`](Self::restart_ice).
+    ///
+    /// The checks are the ICE agent's keepalives, which only go out once nothing has been
+    /// sent or received on the pair for two seconds, so steady traffic leaves gaps in the
+    /// history. [`ping`](Self::ping) measures the round trip on demand instead
+    pub fn rtt_history(&self) -> Vec<RttSample> {
+        self.session.rtt_history()
+    }
+
+    /// Sends a ping which the server echoes back and returns the round trip time, without
+    /// going through the message queues. The ping is an SCTP HEARTBEAT, which every SCTP
+    /// stack answers with a HEARTBEAT ACK, so it never reaches the application on either
+    /// side. If no answer arrives in time, the ping fails with [`PingError::Timeout`]
+    pub async fn ping(&self, timeout: Duration) -> Result<Duration, PingError> {
+        self.session.ping(timeout).await
+    }
+
+    /// Receives the next message, giving up after `duration`. Returns `Ok(None)` once the
+    /// data channel has closed, and `Err(RecvTimeout)` if no message arrived in time
+    pub async fn recv_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<Option<Box<[u8]>>, RecvTimeout> {
+        timeout(duration, self.to_client_receiver.recv())
+            .await
+            .map_err(|_| RecvTimeout)
+    }
+
+    /// Receives the next payload the server sent over the large transfer data channel,
+    /// once all of its chunks have arrived. Returns `Ok(None)` once that data channel has
+    /// closed. Fails with [`LargeTransferError::Partial`] if the connection was lost, or
+    /// re-established, while a payload was being received, and with
+    /// [`SocketConnectionError::NoLargeTransfers`] unless enabled with
+    /// [`SocketConfig::set_large_transfers`]
+    pub async fn recv_large(&mut self) -> Result<Option<Bytes>, LargeTransferError> {
+        recv_large(&mut self.large_receiver).await
+    }
+
+    /// Moves every message already delivered to `to_client_receiver` into `out` without
+    /// waiting, and returns how many were moved, e.g. to process the messages of a game
+    /// tick as a batch. Messages arriving meanwhile are left for the next call.
+    ///
+    /// Returns 0 when nothing is queued, including once the data channel has closed, which
+    /// [`is_inbound_closed`](Self::is_inbound_closed) tells apart
+    pub fn drain_inbound(&mut self, out: &mut Vec<Box<[u8]>>) -> usize {
+        drain_receiver(&mut self.to_client_receiver, out)
+    }
+
+    /// Returns whether no more messages will be delivered to `to_client_receiver`, because
+    /// the data channel has closed for good. Messages still queued can be received
+    pub fn is_inbound_closed(&self) -> bool {
+        self.to_client_receiver.is_closed()
+    }
+
+    /// Converts the channel halves into a [`futures_sink::Sink`] and a
+    /// [`futures_core::Stream`], for use with the combinators of the wider async ecosystem
+    pub fn into_stream(self) -> (SocketSink, SocketStream) {
+        (
+            SocketSink::new(self.to_server_sender),
+            SocketStream::new(self.to_client_receiver),
+        )
+    }
+
+    /// Splits the connection into a sending and a receiving half, which can be moved into
+    /// different tasks without sharing the `SocketIo`. The halves stay connected across
+    /// [`SocketTx::restart_ice`], like the channels of the `SocketIo`
+    pub fn split(self) -> (SocketTx, SocketRx) {
+        (
+            SocketTx {
+                to_server_sender: self.to_server_sender,
+                reliable_sender: self.reliable_sender,
+                large_sender: self.large_sender,
+                session: Arc::clone(&self.session),
+            },
+            SocketRx {
+                to_client_receiver: self.to_client_receiver,
+                addressed_receiver: self.addressed_receiver,
+                typed_receiver: self.typed_receiver,
+                large_receiver: self.large_receiver,
+                session: self.session,
+            },
+        )
+    }
+
+    pub(crate) fn session(&self) -> Arc<Session> {
+        Arc::clone(&self.session)
+    }
+}
+
+/// The sending half of a [`SocketIo`], made by [`SocketIo::split`]
+///
+/// It can be cloned to send from several tasks.
+#[derive(Clone)]
+pub struct SocketTx {
+    to_server_sender: mpsc::Sender<Box<[u8]>>,
+    reliable_sender: mpsc::Sender<ReliableMessage>,
+    large_sender: mpsc::Sender<Box<[u8]>>,
+    session: Arc<Session>,
+}
+
+impl SocketTx {
+    /// See [`SocketIo::id`]
+    pub fn id(&self) -> ConnectionId {
+        self.session.id
+    }
+
+    /// See [`SocketIo::send`]
+    pub async fn send(&self, message: Box<[u8]>) -> Result<(), SocketConnectionError> {
+        self.session.send(&self.to_server_sender, message).await
+    }
+
+    /// See [`SocketIo::send_reliable`]
+    pub async fn send_reliable(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        self.session
+            .send_reliable(&self.reliable_sender, message)
+            .await
+    }
+
+    /// See [`SocketIo::send_reliable_cancellable`]
+    pub async fn send_reliable_cancellable(
+        &self,
+        message: &[u8],
+    ) -> Result<SendHandle, SocketConnectionError> {
+        self.session
+            .send_reliable_cancellable(&self.reliable_sender, message)
+            .await
+    }
+
+    /// See [`SocketIo::send_reliable_timeout`]
+    pub async fn send_reliable_timeout(
+        &self,
+        message: &[u8],
+        timeout: Duration,
+    ) -> Result<(), SocketConnectionError> {
+        self.session
+            .send_reliable_timeout(&self.reliable_sender, message, timeout)
+            .await
+    }
+
+    /// See [`SocketIo::send_large`]
+    pub async fn send_large(&self, payload: Bytes) -> Result<(), SocketConnectionError> {
+        self.session.send_large(&self.large_sender, payload).await
+    }
+
+    /// See [`SocketIo::send_unreliable`]
+    pub async fn send_unreliable(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        self.send(message.into()).await
+    }
+
+    /// See [`SocketIo::try_send`]
+    pub fn try_send(&self, message: &[u8]) -> Result<(), SocketConnectionError> {
+        self.session.try_send(&self.to_server_sender, message)
+    }
+
+    /// See [`SocketIo::max_message_size`]
+    pub fn max_message_size(&self) -> usize {
+        self.session.max_message_size()
+    }
+
+    /// See [`SocketIo::buffered_amount`]
+    pub fn buffered_amount(&self) -> usize {
+        self.session.buffered_amount()
+    }
+
+    /// See [`SocketIo::congestion`]
+    pub async fn congestion(&self) -> Option<CongestionInfo> {
+        self.session.congestion().await
+    }
+
+    /// See [`SocketIo::stats`]
+    pub fn stats(&self) -> SocketStats {
+        self.session.traffic.stats()
+    }
+
+    /// See [`SocketIo::rtt_history`]
+    pub fn rtt_history(&self) -> Vec<RttSample> {
+        self.session.rtt_history()
+    }
+
+    /// See [`SocketIo::ping`]
+    pub async fn ping(&self, timeout: Duration) -> Result<Duration, PingError> {
+        self.session.ping(timeout).await
+    }
+
+    /// See [`SocketIo::restart_ice`]
+    pub async fn restart_ice(&self) -> Result<(), SocketConnectionError> {
+        self.session.restart().await
+    }
+
+    /// See [`SocketIo::close_send`]
+    pub fn close_send(&self) {
+        self.session.close_send()
+    }
+
+    /// See [`SocketIo::close`]
+    pub async fn close(&self) {
+        self.session.close().await
+    }
+
+    /// See [`SocketIo::ready`]
+    pub async fn ready(&self) -> Result<(), SocketConnectionError> {
+        self.session.ready().await
+    }
+}
+
+/// The receiving half of a [`SocketIo`], made by [`SocketIo::split`]
+pub struct SocketRx {
+    to_client_receiver: mpsc::Receiver<Box<[u8]>>,
+    addressed_receiver: Option<mpsc::Receiver<(Box<[u8]>, SocketAddr)>>,
+    typed_receiver: Option<mpsc::Receiver<(Box<[u8]>, PayloadType)>>,
+    large_receiver: Option<LargeReceiver>,
+    session: Arc<Session>,
+}
+
+impl SocketRx {
+    /// See [`SocketIo::id`]
+    pub fn id(&self) -> ConnectionId {
+        self.session.id
+    }
+
+    /// Receives the next message. Returns `None` once the data channel has closed, or
+    /// right away with [`SocketConfig::set_source_addrs`] or
+    /// [`SocketConfig::set_payload_types`]
+    pub async fn recv(&mut self) -> Option<Box<[u8]>> {
+        self.to_client_receiver.recv().await
+    }
+
+    /// Receives the next message along with the address of the server it came from. Needs
+    /// [`SocketConfig::set_source_addrs`], without which it returns `None` right away
+    pub async fn recv_from(&mut self) -> Option<(Box<[u8]>, SocketAddr)> {
+        self.addressed_receiver.as_mut()?.recv().await
+    }
+
+    /// Receives the next message along with the payload type the server sent it as. Needs
+    /// [`SocketConfig::set_payload_types`], without which it returns `None` right away
+    pub async fn recv_typed(&mut self) -> Option<(Box<[u8]>, PayloadType)> {
+        self.typed_receiver.as_mut()?.recv().await
+    }
+
+    /// See [`SocketIo::recv_timeout`]
+    pub async fn recv_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<Option<Box<[u8]>>, RecvTimeout> {
+        timeout(duration, self.to_client_receiver.recv())
+            .await
+            .map_err(|_| RecvTimeout)
+    }
+
+    /// See [`SocketIo::recv_large`]
+    pub async fn recv_large(&mut self) -> Result<Option<Bytes>, LargeTransferError> {
+        recv_large(&mut self.large_receiver).await
+    }
+
+    /// See [`SocketIo::drain_inbound`]
+    pub fn drain_inbound(&mut self, out: &mut Vec<Box<[u8]>>) -> usize {
+        drain_receiver(&mut self.to_client_receiver, out)
+    }
+
+    /// See [`SocketIo::is_inbound_closed`]
+    pub fn is_inbound_closed(&self) -> bool {
+        self.to_client_receiver.is_closed()
+    }
+
+    /// See [`SocketIo::pause_recv`]
+    pub fn pause_recv(&self) {
+        self.session.recv_paused.send_replace(true);
+    }
+
+    /// See [`SocketIo::resume_recv`]
+    pub fn resume_recv(&self) {
+        self.session.recv_paused.send_replace(false);
+    }
+
+    /// See [`SocketIo::is_recv_paused`]
+    pub fn is_recv_paused(&self) -> bool {
+        *self.session.recv_paused.borrow()
+    }
+}
+
+impl Socket {
+    pub async fn connect(server_url: &str) -> Result<(AddrCell, SocketIo), SocketConnectionError> {
+        Self::connect_with_config(server_url, SocketConfig::default()).await
+    }
+
+    pub async fn connect_with_config(
+        server_url: &str,
+        config: SocketConfig,
+    ) -> Result<(AddrCell, SocketIo), SocketConnectionError> {
+        Self::connect_until_aborted(
+            server_url,
+            config,
+            &CancellationToken::new(),
+            EventSender::new(),
+        )
+        .await
+    }
+
+    /// Connects like [`connect_with_config`](Self::connect_with_config), returning a
+    /// [`ConnectAbortHandle`] along with the connect future. Aborting through the handle
+    /// shuts down everything the attempt has set up so far, unlike dropping the future.
+    /// The handle also reports the progress of the attempt through
+    /// [`ConnectAbortHandle::events`]
+    pub fn connect_abortable(
+        server_url: &str,
+        config: SocketConfig,
+    ) -> (
+        ConnectAbortHandle,
+        impl Future<Output = Result<(AddrCell, SocketIo), SocketConnectionError>>,
+    ) {
+        let (abort_handle, attempt) = ConnectAbortHandle::new();
+        let server_url = server_url.to_owned();
+        let connect = async move {
+            Self::connect_until_aborted(&server_url, config, &attempt.abort, attempt.events).await
+        };
+        (abort_handle, connect)
+    }
+
+    /// Starts connecting like [`connect`](Self::connect), but returns right away with a
+    /// `SocketIo` while the connection is established in a spawned task, e.g. to start
+    /// rendering without waiting for the server. Needs to be called within a Tokio runtime.
+    /// Only invalid arguments, which are checked up front, fail here.
+    ///
+    /// Messages sent before the data channel opens are queued, as far as the queues allow,
+    /// and sent once it opens. [`SocketIo::ready`] waits until then. If the connection
+    /// can't be established, queued messages are dropped, later sends fail with
+    /// [`SocketConnectionError::Closed`], and [`SocketEvent::ConnectFailed`] is emitted.
+    /// The [`SocketEvent::HandshakePhaseStarted`] and [`SocketEvent::HandshakePhaseFinished`]
+    /// events of [`SocketIo::events`] report the progress until then.
+    pub fn connect_background(
+        server_url: &str,
+    ) -> Result<(AddrCell, SocketIo), SocketConnectionError> {
+        Self::connect_background_with_config(server_url, SocketConfig::default())
+    }
+
+    /// Starts connecting with `config` like [`connect_background`](Self::connect_background)
+    pub fn connect_background_with_config(
+        server_url: &str,
+        config: SocketConfig,
+    ) -> Result<(AddrCell, SocketIo), SocketConnectionError> {
+        let (addr_cell, socket_io, client_senders) =
+            Self::prepare(server_url, config, EventSender::new())?;
+        let session = socket_io.session();
+        tokio::spawn(async move {
+            let abort = session.background_abort.clone();
+            if let Err(err) = session.start(client_senders, &abort).await {
+                session.connect_failed(err);
+            }
+        });
+        Ok((addr_cell, socket_io))
+    }
+
+    async fn connect_until_aborted(
+        server_url: &str,
+        config: SocketConfig,
+        abort: &CancellationToken,
+        events: EventSender,
+    ) -> Result<(AddrCell, SocketIo), SocketConnectionError> {
+        let (addr_cell, socket_io, client_senders) = Self::prepare(server_url, config, events)?;
+        socket_io.session.start(client_senders, abort).await?;
+        Ok((addr_cell, socket_io))
+    }
+
+    // prepare checks the arguments of a connection and sets up its session, returning the
+    // senders the read loops deliver inbound messages with
+    fn prepare(
+        server_url: &str,
+        config: SocketConfig,
+        events: EventSender,
+    ) -> Result<(AddrCell, SocketIo, ClientSenders), SocketConnectionError> {
+        let server_url = parse_server_url(server_url)?;
+        let fallback_server_urls = config
+            .fallback_server_urls
+            .iter()
+            .map(|url| parse_server_url(url))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(bind_address) = config.bind_address {
+            check_bind_address(bind_address)?;
+        }
+        if let Some(ips) = &config.nat_1to1_ips {
+            check_nat_1to1_ips(ips)?;
+        }
+        check_signaling_proxy(&config.signaling_proxy)?;
+        if let Some(user_agent) = &config.user_agent {
+            check_user_agent(user_agent)?;
+        }
+        if let Some(request) = &config.signaling_request {
+            check_signaling_method(&request.method)?;
+        }
+        if let Some((interval, retries)) = config.ice_checks {
+            check_ice_checks(interval, retries)?;
+        }
+        if let Some((initial, min, max)) = config.sctp_rto {
+            check_sctp_rto(initial, min, max)?;
+        }
+        if let Some(mtu) = config.dtls_mtu {
+            check_dtls_mtu(mtu)?;
+        }
+        if let Some(dscp) = config.dscp.filter(|&dscp| dscp >= 64) {
+            return Err(SocketConnectionError::InvalidDscp { dscp });
+        }
+        if let Some(port) = config.sctp_port.filter(|&port| port == 0) {
+            return Err(SocketConnectionError::InvalidSctpPort { port });
+        }
+        if config.reliable_max_in_flight == Some(0) {
+            return Err(SocketConnectionError::InvalidReliableMaxInFlight);
+        }
+        if let Some(bytes) = config.udp_recv_buffer_size {
+            check_udp_buffer_size("receive", bytes)?;
+        }
+        if let Some(bytes) = config.udp_send_buffer_size {
+            check_udp_buffer_size("send", bytes)?;
+        }
+        if let Some((attempts, base_delay, max_delay)) = config.signaling_retry {
+            check_signaling_retry(attempts, base_delay, max_delay)?;
+        }
+        if let Some(statuses) = &config.signaling_retry_statuses {
+            check_retry_statuses(statuses)?;
+        }
+
+        let (to_server_sender, to_server_receiver) =
+            mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
+        let (to_client_sender, to_client_receiver) =
+            mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
+        // with a cap on the messages in flight, a single message waits for the cap, so that
+        // send_reliable waits as soon as the cap is reached
+        let reliable_queue_size = match config.reliable_max_in_flight {
+            Some(_) => 1,
+            None => CLIENT_CHANNEL_SIZE,
+        };
+        let (reliable_sender, reliable_receiver) =
+            mpsc::channel::<ReliableMessage>(reliable_queue_size);
+        let (large_sender, large_receiver) = mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
+        let (to_client_large_sender, to_client_large_receiver) = if config.large_transfers {
+            let (sender, receiver) = mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
+            (Some(sender), Some(LargeReceiver::new(receiver)))
+        } else {
+            (None, None)
+        };
+        // dropping the plain sender leaves to_client_receiver closed
+        let (to_client_sender, addressed_receiver, typed_receiver) = if config.payload_types {
+            let (typed_sender, typed_receiver) =
+                mpsc::channel::<(Box<[u8]>, PayloadType)>(CLIENT_CHANNEL_SIZE);
+            (
+                InboundSender::Typed(typed_sender),
+                None,
+                Some(typed_receiver),
+            )
+        } else if config.source_addrs {
+            let (addressed_sender, addressed_receiver) =
+                mpsc::channel::<(Box<[u8]>, SocketAddr)>(CLIENT_CHANNEL_SIZE);
+            (
+                InboundSender::Addressed(addressed_sender),
+                Some(addressed_receiver),
+                None,
+            )
+        } else {
+            (InboundSender::Plain(to_client_sender), None, None)
+        };
+
+        // put the conditioner stages between the channel halves handed out and the loops
+        #[cfg(feature = "network-conditioner")]
+        let (to_server_receiver, to_client_sender) = match config.network_conditioner {
+            Some(conditioner) => {
+                let (conditioned_sender, conditioned_receiver) =
+                    mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
+                conditioner.spawn_stage(0, to_server_receiver, conditioned_sender);
+                (
+                    conditioned_receiver,
+                    to_client_sender.conditioned(conditioner, 1),
+                )
+            }
+            None => (to_server_receiver, to_client_sender),
+        };
+
+        let addr_cell = AddrCell::default();
+        let id = ConnectionId::next();
+        let span = tracing::info_span!("connection", %id, server_url = %server_url);
+        let session = Arc::new(Session {
+            id,
+            max_message_size: Arc::new(AtomicUsize::new(config.assumed_max_message_size())),
+            server_url,
+            fallback_server_urls,
+            addr_cell: addr_cell.clone(),
+            timings: TimingsCell::new(events.clone(), span.clone()),
+            dtls_info: DtlsInfoCell::default(),
+            local_description: StdMutex::new(None),
+            credentials: StdMutex::new(None),
+            selected_pair: Arc::new(StdMutex::new(None)),
+            selected_pair_types: Arc::new(StdMutex::new(None)),
+            ice_role: Arc::new(StdMutex::new(None)),
+            rtt_history: match config.rtt_history_size() {
+                0 => None,
+                size => Some(Arc::new(RttHistory::new(size))),
+            },
+            data_channel: Arc::new(StdMutex::new(None)),
+            association: Arc::new(StdMutex::new(None)),
+            pings: Arc::default(),
+            activity: config
+                .idle_timeout
+                .map(|(_, pings)| Arc::new(Activity::new(pings))),
+            traffic: Arc::default(),
+            config,
+            events,
+            to_server_receiver: Arc::new(Mutex::new(to_server_receiver)),
+            reliable_receiver: Arc::new(Mutex::new(reliable_receiver)),
+            large_receiver: Arc::new(Mutex::new(large_receiver)),
+            large_transfer_id: AtomicU32::new(0),
+            large_send: Mutex::new(()),
+            recv_paused: watch::channel(false).0,
+            send_closed: CancellationToken::new(),
+            to_client_sender: to_client_sender.downgrade(),
+            to_client_large_sender: to_client_large_sender.as_ref().map(mpsc::Sender::downgrade),
+            readiness: Arc::new(watch::channel(Readiness::Connecting).0),
+            connect_error: StdMutex::new(None),
+            background_abort: CancellationToken::new(),
+            connection: Mutex::new(None),
+            span,
+        });
+
+        Ok((
+            addr_cell,
+            SocketIo {
+                to_server_sender,
+                to_client_receiver,
+                addressed_receiver,
+                typed_receiver,
+                reliable_sender,
+                large_sender,
+                large_receiver: to_client_large_receiver,
+                session,
+            },
+            ClientSenders {
+                messages: to_client_sender,
+                large: to_client_large_sender,
+            },
+        ))
+    }
+}
+
+// parse_server_url checks that the signaling request can be sent to server_url, so mistakes
+// like a missing scheme are reported up front instead of as an opaque request error
+fn parse_server_url(server_url: &str) -> Result<Url, SocketConnectionError> {
+    let invalid = |reason: String| SocketConnectionError::InvalidServerUrl {
+        url: server_url.to_owned(),
+        reason,
+    };
+
+    let url = Url::parse(server_url.trim()).map_err(|err| match err {
+        url::ParseError::RelativeUrlWithoutBase => {
+            invalid("missing scheme, e.g. http://localhost:3000".to_owned())
+        }
+        err => invalid(err.to_string()),
+    })?;
+
+    match url.scheme() {
+        "http" | "https" => {}
+        // `localhost:3000` parses with `localhost` as its scheme
+        scheme if url.cannot_be_a_base() => {
+            return Err(invalid(format!(
+                "scheme {:?} is not http or https, did you mean http://{}?",
+                scheme,
+                server_url.trim()
+            )));
+        }
+        scheme => {
+            return Err(invalid(format!("scheme {:?} is not http or https", scheme)));
+        }
+    }
+
+    match url.host_str() {
+        Some(host) if !host.is_empty() => {}
+        _ => return Err(invalid("missing host".to_owned())),
+    }
+
+    Ok(url)
+}
+
+// check_bind_address makes sure the ICE UDP sockets can be bound to addr, since gathering
+// would otherwise just end up without candidates
+fn check_bind_address(addr: IpAddr) -> Result<(), SocketConnectionError> {
+    let invalid = |reason: String| SocketConnectionError::InvalidBindAddress { addr, reason };
+
+    if addr.is_unspecified() {
+        return Err(invalid(
+            "the unspecified address binds every interface, which is the default".to_owned(),
+        ));
+    }
+    if addr.is_multicast() {
+        return Err(invalid("multicast addresses can't be bound".to_owned()));
+    }
+    StdUdpSocket::bind(SocketAddr::new(addr, 0))
+        .map(|_| ())
+        .map_err(|err| invalid(format!("not usable on this host: {}", err)))
+}
+
+// check_signaling_proxy makes sure the signaling client can be built with the proxy, whose
+// url is only parsed then
+fn check_signaling_proxy(proxy: &SignalingProxy) -> Result<(), SocketConnectionError> {
+    if let SignalingProxy::Url(url) = proxy {
+        Proxy::all(url.as_str()).map_err(|err| SocketConnectionError::InvalidSignalingProxy {
+            url: url.clone(),
+            reason: err.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+// check_user_agent makes sure the user agent can be sent as a header, since building the
+// signaling client would fail otherwise
+fn check_user_agent(user_agent: &str) -> Result<(), SocketConnectionError> {
+    HeaderValue::from_str(user_agent).map_err(|err| SocketConnectionError::InvalidUserAgent {
+        user_agent: user_agent.to_owned(),
+        reason: err.to_string(),
+    })?;
+    Ok(())
+}
+
+// check_signaling_method makes sure the offer can be sent with the method, since building
+// the signaling request would fail otherwise
+fn check_signaling_method(method: &str) -> Result<(), SocketConnectionError> {
+    Method::from_bytes(method.as_bytes()).map_err(|err| {
+        SocketConnectionError::InvalidSignalingMethod {
+            method: method.to_owned(),
+            reason: err.to_string(),
+        }
+    })?;
+    Ok(())
+}
+
+// check_ice_checks makes sure the checks are paced within range, so that they can't flood
+// the network, and that a candidate pair is checked more than once
+fn check_ice_checks(interval: Duration, retries: u16) -> Result<(), SocketConnectionError> {
+    if !(MIN_ICE_CHECK_INTERVAL..=MAX_ICE_CHECK_INTERVAL).contains(&interval) {
+        return Err(SocketConnectionError::InvalidIceChecks {
+            reason: format!(
+                "the interval must be between {:?} and {:?}, got {:?}",
+                MIN_ICE_CHECK_INTERVAL, MAX_ICE_CHECK_INTERVAL, interval
+            ),
+        });
+    }
+    if retries == 0 {
+        return Err(SocketConnectionError::InvalidIceChecks {
+            reason: "the checks must be retried at least once".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+// check_sctp_rto makes sure the retransmission timeout can start at `initial` and stay
+// within `min` and `max`, which the association keeps in milliseconds
+fn check_sctp_rto(
+    initial: Duration,
+    min: Duration,
+    max: Duration,
+) -> Result<(), SocketConnectionError> {
+    if min.as_millis() == 0 {
+        return Err(SocketConnectionError::InvalidSctpRto {
+            reason: "RTO.Min must be at least a millisecond".to_owned(),
+        });
+    }
+    if !(min <= initial && initial <= max) {
+        return Err(SocketConnectionError::InvalidSctpRto {
+            reason: format!(
+                "the values must satisfy min <= initial <= max, got min {:?}, initial {:?}, max {:?}",
+                min, initial, max
+            ),
+        });
+    }
+    Ok(())
+}
+
+// check_dtls_mtu makes sure the handshake datagrams have room for more than their headers,
+// and fit into the datagrams the server reads
+fn check_dtls_mtu(mtu: usize) -> Result<(), SocketConnectionError> {
+    if !(MIN_DTLS_MTU..=RECEIVE_MTU).contains(&mtu) {
+        return Err(SocketConnectionError::InvalidDtlsMtu {
+            mtu,
+            reason: format!("must be between {} and {} bytes", MIN_DTLS_MTU, RECEIVE_MTU),
+        });
+    }
+    Ok(())
+}
+
+// check_udp_buffer_size makes sure the kernel is asked for a buffer which can hold a
+// datagram at all
+fn check_udp_buffer_size(buffer: &str, bytes: usize) -> Result<(), SocketConnectionError> {
+    if bytes == 0 {
+        return Err(SocketConnectionError::InvalidUdpBufferSize {
+            reason: format!("the {} buffer size must be positive", buffer),
+        });
+    }
+    Ok(())
+}
+
+// check_signaling_retry makes sure the offer is sent at least once, and that the delay
+// between attempts can grow from its base to its maximum
+fn check_signaling_retry(
+    attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+) -> Result<(), SocketConnectionError> {
+    if attempts == 0 {
+        return Err(SocketConnectionError::InvalidSignalingRetry {
+            reason: "at least one attempt is needed".to_owned(),
+        });
+    }
+    if base_delay > max_delay {
+        return Err(SocketConnectionError::InvalidSignalingRetry {
+            reason: format!(
+                "the base delay of {:?} exceeds the maximum delay of {:?}",
+                base_delay, max_delay
+            ),
+        });
+    }
+    Ok(())
+}
+
+// check_retry_statuses makes sure the statuses to retry are HTTP status codes, which a
+// response could be answered with at all
+fn check_retry_statuses(statuses: &[u16]) -> Result<(), SocketConnectionError> {
+    match statuses
+        .iter()
+        .find(|&&status| StatusCode::from_u16(status).is_err())
+    {
+        Some(status) => Err(SocketConnectionError::InvalidSignalingRetry {
+            reason: format!("{} isn't a valid HTTP status code", status),
+        }),
+        None => Ok(()),
+    }
+}
+
+// check_nat_1to1_ips makes sure the ICE agent accepts the external IPs, since it would
+// otherwise fail to gather
+fn check_nat_1to1_ips(ips: &[String]) -> Result<(), SocketConnectionError> {
+    let invalid = |reason: String| SocketConnectionError::InvalidNat1to1Ips { reason };
+
+    if ips.is_empty() {
+        return Err(invalid("no external IPs were given".to_owned()));
+    }
+    for entry in ips {
+        let mut ips = entry.split('/').map(|ip| ip.parse::<IpAddr>());
+        match (ips.next(), ips.next(), ips.next()) {
+            (Some(Ok(_)), None, _) => {}
+            (Some(Ok(external)), Some(Ok(local)), None) => {
+                if external.is_ipv4() != local.is_ipv4() {
+                    return Err(invalid(format!("{:?} maps between IPv4 and IPv6", entry)));
+                }
+            }
+            _ => {
+                return Err(invalid(format!(
+                    "{:?} is neither an IP nor an external/local IP pair",
+                    entry
+                )))
+            }
+        }
+    }
+    // catches conflicting entries, e.g. two for the same local IP
+    ExternalIpMapper::new(CandidateType::Host, ips)
+        .map(|_| ())
+        .map_err(|err| invalid(err.to_string()))
+}
+
+// Session holds everything needed to establish the connection behind a SocketIo again
+pub(crate) struct Session {
+    id: ConnectionId,
+    server_url: Url,
+    // tried in order when the server url doesn't answer the offer
+    fallback_server_urls: Vec<Url>,
+    // negotiated once the data channel opens
+    max_message_size: Arc<AtomicUsize>,
+    config: SocketConfig,
+    addr_cell: AddrCell,
+    timings: TimingsCell,
+    dtls_info: DtlsInfoCell,
+    // the offer of the current connection
+    local_description: StdMutex<Option<LocalDescription>>,
+    // the certificate and ICE credentials of the current connection
+    credentials: StdMutex<Option<SessionCredentials>>,
+    // the candidate pair selected for the current connection
+    selected_pair: Arc<StdMutex<Option<CandidatePair>>>,
+    // the types of the local and remote candidates of that pair, e.g. `host`
+    selected_pair_types: Arc<StdMutex<Option<(String, String)>>>,
+    // the ICE role of the current connection, once its connectivity checks have started
+    ice_role: Arc<StdMutex<Option<IceRole>>>,
+    // the round trip times of the checks on the selected pairs, unless none are kept
+    rtt_history: Option<Arc<RttHistory>>,
+    // the data channel of the current connection, once it has opened
+    data_channel: Arc<StdMutex<Option<Arc<DataChannel>>>>,
+    // the SCTP association carrying the data channel, once it has opened
+    association: Arc<StdMutex<Option<Arc<Association>>>>,
+    // the pings awaiting their echo, which the SCTP association delivers
+    pings: Arc<Pings>,
+    // when data was last sent or received, with an idle timeout
+    activity: Option<Arc<Activity>>,
+    // the bytes sent and received, over every connection
+    traffic: Arc<Traffic>,
+    events: EventSender,
+    // taken by the write_loop of the current connection
+    to_server_receiver: Arc<Mutex<mpsc::Receiver<Box<[u8]>>>>,
+    // taken by the write_loop of the current reliable data channel
+    reliable_receiver: Arc<Mutex<mpsc::Receiver<ReliableMessage>>>,
+    // taken by the write_loop of the current large transfer data channel
+    large_receiver: Arc<Mutex<mpsc::Receiver<Box<[u8]>>>>,
+    // the id of the next payload sent with send_large
+    large_transfer_id: AtomicU32,
+    // held while the chunks of a payload are queued, so that they aren't interleaved with
+    // the chunks of another one
+    large_send: Mutex<()>,
+    // watched by the read loops, which stop reading from the data channel while it's true
+    recv_paused: watch::Sender<bool>,
+    // cancelled by close_send, upon which the write loops flush their queue and reset their
+    // outgoing stream
+    send_closed: CancellationToken,
+    // weak, so that the receiver still yields `None` once the read loops have ended
+    to_client_sender: WeakInboundSender,
+    to_client_large_sender: Option<mpsc::WeakSender<Box<[u8]>>>,
+    // whether the data channel has opened yet, or establishing the connection failed
+    readiness: Arc<watch::Sender<Readiness>>,
+    // why establishing the connection failed, until SocketIo::ready has returned it
+    connect_error: StdMutex<Option<SocketConnectionError>>,
+    // cancelled by close, which aborts a background connect still in progress
+    background_abort: CancellationToken,
+    connection: Mutex<Option<Connection>>,
+    // the tracing span of the connection, covering its establishment and its loops
+    span: Span,
+}
+
+// Readiness is how far a Session has got with its first connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Readiness {
+    Connecting,
+    Open,
+    Failed,
+}
+
+// Connection is one peer connection established by a Session
+struct Connection {
+    // the span of the session, which records its id
+    span: Span,
+    peer_connection: Arc<RTCPeerConnection>,
+    closed: CancellationToken,
+    // lets the write loops send what's queued before close
+    drain: Arc<Drain>,
+    // the WHIP resource of the connection, deleted on close
+    whip_resource: Option<(HttpClient, Url)>,
+}
+
+impl Connection {
+    // drain has the write loops send the messages queued so far and waits, for up to
+    // `drain_timeout`, until the server has acknowledged them
+    async fn drain(&self, drain_timeout: Duration) {
+        if drain_timeout.is_zero() {
+            return;
+        }
+        if !self
+            .drain
+            .run(drain_timeout)
+            .instrument(self.span.clone())
+            .await
+        {
+            debug!(
+                parent: &self.span,
+                "Messages not sent within {:?} are abandoned", drain_timeout
+            );
+        }
+    }
+
+    async fn close(self) {
+        let span = self.span.clone();
+        async move {
+            // stop the loops first, so the write_loop hands back the receiver
+            self.closed.cancel();
+            if let Err(error) = self.peer_connection.close().await {
+                warn!("Error while closing the peer connection: {}", error);
+            }
+            if let Some((http_client, resource)) = self.whip_resource {
+                if let Err(error) = whip::delete_resource(&http_client, resource).await {
+                    warn!("Error while deleting the WHIP resource: {}", error);
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+// Drain tells the write loops of a connection to stop once they have sent what's queued
+#[derive(Default)]
+struct Drain {
+    started: CancellationToken,
+    write_loops: StdMutex<Vec<JoinHandle<()>>>,
+}
+
+impl Drain {
+    fn track(&self, write_loop: JoinHandle<()>) {
+        self.write_loops
+            .lock()
+            .expect("write loops lock poisoned")
+            .push(write_loop);
+    }
+
+    // run starts draining, and returns whether the write loops finished within
+    // `drain_timeout`
+    async fn run(&self, drain_timeout: Duration) -> bool {
+        self.started.cancel();
+        let write_loops =
+            std::mem::take(&mut *self.write_loops.lock().expect("write loops lock poisoned"));
+        timeout(drain_timeout, async {
+            for write_loop in write_loops {
+                let _ = write_loop.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+impl Session {
+    pub(crate) fn max_message_size(&self) -> usize {
+        self.max_message_size.load(Ordering::SeqCst)
+    }
+
+    async fn send<M: Queued>(
+        &self,
+        sender: &mpsc::Sender<M>,
+        message: M,
+    ) -> Result<(), SocketConnectionError> {
+        self.check_send_open()?;
+        check_message_size(message.size(), self.max_message_size())?;
+        sender.send(message).await.map_err(|_| self.send_error())
+    }
+
+    // check_send_open refuses messages once close_send has been called, as the write loops
+    // may not have closed their queues yet
+    fn check_send_open(&self) -> Result<(), SocketConnectionError> {
+        if self.send_closed.is_cancelled() {
+            Err(SocketConnectionError::SendClosed)
+        } else {
+            Ok(())
+        }
+    }
+
+    // send_error is why a message couldn't be queued, once the queue has been closed
+    fn send_error(&self) -> SocketConnectionError {
+        if self.send_closed.is_cancelled() {
+            SocketConnectionError::SendClosed
+        } else {
+            SocketConnectionError::Closed
+        }
+    }
+
+    fn close_send(&self) {
+        self.send_closed.cancel();
+        // without a write loop to close the queues, they are closed here, so that sending
+        // fails right away
+        close_queue(&self.to_server_receiver, false);
+        close_queue(&self.reliable_receiver, false);
+        close_queue(&self.large_receiver, false);
+    }
+
+    async fn send_reliable(
+        &self,
+        reliable_sender: &mpsc::Sender<ReliableMessage>,
+        message: &[u8],
+    ) -> Result<(), SocketConnectionError> {
+        if !self.config.reliable_channel {
+            return Err(SocketConnectionError::NoReliableChannel);
+        }
+        self.send(reliable_sender, ReliableMessage::new(message.into()))
+            .await
+    }
+
+    async fn send_reliable_cancellable(
+        &self,
+        reliable_sender: &mpsc::Sender<ReliableMessage>,
+        message: &[u8],
+    ) -> Result<SendHandle, SocketConnectionError> {
+        if !self.config.reliable_channel {
+            return Err(SocketConnectionError::NoReliableChannel);
+        }
+        let (message, handle) = ReliableMessage::cancellable(message.into());
+        self.send(reliable_sender, message).await?;
+        Ok(handle)
+    }
+
+    async fn send_reliable_timeout(
+        &self,
+        reliable_sender: &mpsc::Sender<ReliableMessage>,
+        message: &[u8],
+        timeout: Duration,
+    ) -> Result<(), SocketConnectionError> {
+        if !self.config.reliable_channel {
+            return Err(SocketConnectionError::NoReliableChannel);
+        }
+        let deadline = SystemTime::now() + timeout;
+        self.send(
+            reliable_sender,
+            ReliableMessage::with_deadline(message.into(), deadline),
+        )
+        .await
+    }
+
+    async fn send_large(
+        &self,
+        large_sender: &mpsc::Sender<Box<[u8]>>,
+        payload: Bytes,
+    ) -> Result<(), SocketConnectionError> {
+        if !self.config.large_transfers {
+            return Err(SocketConnectionError::NoLargeTransfers);
+        }
+        let _sending = self.large_send.lock().await;
+        let transfer = self.large_transfer_id.fetch_add(1, Ordering::SeqCst);
+        for chunk in large::chunks(transfer, &payload) {
+            self.send(large_sender, chunk).await?;
+        }
+        Ok(())
+    }
+
+    fn try_send(
+        &self,
+        sender: &mpsc::Sender<Box<[u8]>>,
+        message: &[u8],
+    ) -> Result<(), SocketConnectionError> {
+        self.check_send_open()?;
+        check_message_size(message.len(), self.max_message_size())?;
+        if self.buffered_amount() > self.config.send_buffer_threshold() {
+            return Err(SocketConnectionError::WouldBlock);
+        }
+        sender.try_send(message.into()).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => SocketConnectionError::WouldBlock,
+            mpsc::error::TrySendError::Closed(_) => self.send_error(),
+        })
+    }
+
+    fn rtt_history(&self) -> Vec<RttSample> {
+        self.rtt_history
+            .as_ref()
+            .map_or_else(Vec::new, |rtt_history| {
+                rtt_history
+                    .samples()
+                    .into_iter()
+                    .map(|(at, rtt)| RttSample { at, rtt })
+                    .collect()
+            })
+    }
+
+    fn buffered_amount(&self) -> usize {
+        self.data_channel
+            .lock()
+            .expect("data channel lock poisoned")
+            .as_ref()
+            .map_or(0, |data_channel| data_channel.buffered_amount())
+    }
+
+    async fn congestion(&self) -> Option<CongestionInfo> {
+        let association = self
+            .association
+            .lock()
+            .expect("association lock poisoned")
+            .clone()?;
+        Some(CongestionInfo::from_association(&association).await)
+    }
+
+    async fn ping(&self, timeout: Duration) -> Result<Duration, PingError> {
+        let association = self
+            .association
+            .lock()
+            .expect("association lock poisoned")
+            .clone()
+            .ok_or(PingError::NotConnected)?;
+        if let Some(activity) = &self.activity {
+            activity.touch_ping();
+        }
+        self.pings.ping(&association, timeout).await
+    }
+
+    pub(crate) fn max_message_size_ref(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.max_message_size)
+    }
+
+    pub(crate) async fn close(&self) {
+        self.background_abort.cancel();
+        if let Some(connection) = self.connection.lock().await.take() {
+            connection.drain(self.config.drain_timeout()).await;
+            connection.close().await;
+        }
+    }
 
-use anyhow::{Error, Result};
-use bytes::Bytes;
-use log::warn;
-use reqwest::{Client as HttpClient, Response};
-use tinyjson::JsonValue;
-use tokio::{sync::mpsc, time::sleep};
+    // start establishes the first connection of the session, and starts the timers which
+    // close it again
+    async fn start(
+        self: &Arc<Self>,
+        client_senders: ClientSenders,
+        abort: &CancellationToken,
+    ) -> Result<(), SocketConnectionError> {
+        // subscribed before connecting, so that the data channel opening isn't missed
+        let lifetime_events = self.events.subscribe();
+        let idle_events = self.events.subscribe();
+        let connection = self
+            .establish_first(client_senders, abort)
+            .instrument(self.span.clone())
+            .await?;
+        {
+            let mut current = self.connection.lock().await;
+            // closed while the connection was being established
+            if abort.is_cancelled() {
+                drop(current);
+                connection.close().await;
+                return Err(SocketConnectionError::Aborted);
+            }
+            *current = Some(connection);
+        }
+        if let Some(lifetime) = self.config.max_connection_lifetime {
+            tokio::spawn(
+                lifetime_timer(Arc::downgrade(self), lifetime_events, lifetime)
+                    .instrument(self.span.clone()),
+            );
+        }
+        if let (Some((timeout, _)), Some(activity)) =
+            (self.config.idle_timeout, self.activity.clone())
+        {
+            tokio::spawn(
+                idle_timer(Arc::downgrade(self), activity, idle_events, timeout)
+                    .instrument(self.span.clone()),
+            );
+        }
+        Ok(())
+    }
 
-use crate::webrtc::{
-    data_channel::internal::data_channel::DataChannel,
-    peer_connection::{sdp::session_description::RTCSessionDescription, RTCPeerConnection},
-};
+    // connect_failed reports that a background connect failed with `error`, dropping the
+    // messages queued meanwhile
+    fn connect_failed(&self, error: SocketConnectionError) {
+        warn!(
+            parent: &self.span,
+            "Connecting in the background failed: {}", error
+        );
+        self.events.emit(SocketEvent::ConnectFailed {
+            phase: error.phase(),
+            reason: error.to_string(),
+        });
+        close_queue(&self.to_server_receiver, true);
+        close_queue(&self.reliable_receiver, true);
+        close_queue(&self.large_receiver, true);
+        *self
+            .connect_error
+            .lock()
+            .expect("connect error lock poisoned") = Some(error);
+        self.readiness.send_replace(Readiness::Failed);
+    }
+
+    async fn ready(&self) -> Result<(), SocketConnectionError> {
+        let mut readiness = self.readiness.subscribe();
+        loop {
+            match *readiness.borrow_and_update() {
+                Readiness::Open => return Ok(()),
+                Readiness::Failed => {
+                    return Err(self
+                        .connect_error
+                        .lock()
+                        .expect("connect error lock poisoned")
+                        .take()
+                        .unwrap_or(SocketConnectionError::Closed))
+                }
+                Readiness::Connecting => {}
+            }
+            // the session holds the sender, so it doesn't go away while waited on
+            let _ = readiness.changed().await;
+        }
+    }
 
-use super::addr_cell::AddrCell;
+    async fn restart(&self) -> Result<(), SocketConnectionError> {
+        if *self.readiness.borrow() == Readiness::Failed {
+            return Err(SocketConnectionError::Closed);
+        }
+        let client_senders = ClientSenders {
+            messages: self
+                .to_client_sender
+                .upgrade()
+                .ok_or(SocketConnectionError::Closed)?,
+            large: self
+                .to_client_large_sender
+                .as_ref()
+                .and_then(mpsc::WeakSender::upgrade),
+        };
 
-const MESSAGE_SIZE: usize = 1500;
-const CLIENT_CHANNEL_SIZE: usize = 8;
+        let mut connection = self.connection.lock().await;
+        if let Some(connection) = connection.take() {
+            connection.close().await;
+        }
 
-pub struct Socket;
+        self.timings.reset();
+        *connection = Some(
+            self.establish(client_senders, &CancellationToken::new(), None, None)
+                .instrument(self.span.clone())
+                .await?,
+        );
+        Ok(())
+    }
 
-impl Socket {
-    pub async fn connect(
-        server_url: &str,
-    ) -> (AddrCell, mpsc::Sender<Box<[u8]>>, mpsc::Receiver<Box<[u8]>>) {
-        let (to_server_sender, to_server_receiver) =
-            mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
-        let (to_client_sender, to_client_receiver) =
-            mpsc::channel::<Box<[u8]>>(CLIENT_CHANNEL_SIZE);
+    // set_loss_events makes the SCTP association and the DTLS handshake of a connection
+    // report their retransmissions and receive gaps as events
+    #[cfg(feature = "transport-events")]
+    fn set_loss_events(
+        &self,
+        setting_engine: &mut crate::webrtc::api::setting_engine::SettingEngine,
+    ) {
+        use crate::webrtc::sctp::association::LossEvent;
 
-        let addr_cell = AddrCell::default();
+        let events = self.events.clone();
+        let sctp = Arc::new(move |event| {
+            events.emit(match event {
+                LossEvent::Retransmit { tsn, size, fast } => {
+                    SocketEvent::SctpRetransmit { tsn, size, fast }
+                }
+                LossEvent::Gap { tsn, size, missing } => {
+                    SocketEvent::SctpGap { tsn, size, missing }
+                }
+            })
+        });
+        let events = self.events.clone();
+        let dtls = Arc::new(move |message_sequence, size| {
+            events.emit(SocketEvent::DtlsRetransmit {
+                message_sequence,
+                size,
+            })
+        });
+        setting_engine.set_loss_events(sctp, dtls);
+    }
+
+    // offered_credentials returns the certificate and ICE credentials `peer_connection` was
+    // offered with, which keep the time they were first offered at if they are `seed`'s
+    fn offered_credentials(
+        &self,
+        peer_connection: &RTCPeerConnection,
+        seed: Option<&SessionCredentials>,
+    ) -> Option<SessionCredentials> {
+        let certificate = peer_connection
+            .sctp()
+            .transport()
+            .certificates
+            .first()?
+            .clone();
+        let description = self
+            .local_description
+            .lock()
+            .expect("local description lock poisoned")
+            .clone()?;
+        let issued = match seed {
+            Some(seed) if seed.ice_ufrag == description.ice_ufrag => seed.issued,
+            _ => Instant::now(),
+        };
+        Some(SessionCredentials {
+            certificate: DtlsCertificate::from_rtc(certificate),
+            ice_ufrag: description.ice_ufrag,
+            ice_pwd: description.ice_pwd,
+            issued,
+        })
+    }
+
+    // establish_first makes the first connection of the session, over the cached candidate
+    // pair if there is one, and otherwise or if that fails to connect, as usual. Both
+    // attempts offer the session credentials set in the config while they are young enough
+    async fn establish_first(
+        &self,
+        client_senders: ClientSenders,
+        abort: &CancellationToken,
+    ) -> Result<Connection, SocketConnectionError> {
+        let seed = match &self.config.session_credentials {
+            Some((credentials, max_age)) if credentials.age() <= *max_age => Some(credentials),
+            Some((credentials, max_age)) => {
+                debug!(
+                    "Not reusing session credentials {:?} old, older than {:?}",
+                    credentials.age(),
+                    max_age
+                );
+                None
+            }
+            None => None,
+        };
+        let cached = match self.config.cached_candidate_pair {
+            Some(_) if self.config.bind_address.is_some() => None,
+            Some((pair, timeout)) => match check_bind_address(pair.local.ip()) {
+                Ok(()) => Some((pair, timeout)),
+                Err(err) => {
+                    debug!("Not trying the cached candidate pair: {}", err);
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(cached) = cached {
+            match self
+                .establish(client_senders.clone(), abort, Some(cached), seed)
+                .await
+            {
+                Err(SocketConnectionError::Aborted) => return Err(SocketConnectionError::Aborted),
+                Err(err) => warn!(
+                    "The cached candidate pair did not connect, gathering again: {}",
+                    err
+                ),
+                connection => return connection,
+            }
+            self.timings.reset();
+        }
+        self.establish(client_senders, abort, None, seed).await
+    }
+
+    // establish makes a new connection. With a cached candidate pair, its local address is
+    // the only one gathered on, and the connection fails unless ICE connects in time. With
+    // `seed`, its certificate and ICE credentials are offered instead of new ones
+    async fn establish(
+        &self,
+        client_senders: ClientSenders,
+        abort: &CancellationToken,
+        cached: Option<(CandidatePair, Duration)>,
+        seed: Option<&SessionCredentials>,
+    ) -> Result<Connection, SocketConnectionError> {
+        let closed = CancellationToken::new();
+        let drain = Arc::new(Drain::default());
+        *self
+            .selected_pair
+            .lock()
+            .expect("selected pair lock poisoned") = None;
+        *self
+            .selected_pair_types
+            .lock()
+            .expect("selected pair types lock poisoned") = None;
+        *self.ice_role.lock().expect("ICE role lock poisoned") = None;
+        let mut events = self.events.subscribe();
+        #[cfg(feature = "deterministic-rng")]
+        if let Some(seed) = self.config.rng_seed {
+            crate::webrtc::util::rng::seed(seed);
+        }
 
         // create a new RTCPeerConnection
-        let peer_connection = RTCPeerConnection::new().await;
+        let mut setting_engine = self.config.setting_engine();
+        setting_engine.set_name(self.id.to_string());
+        if let Some((pair, _)) = cached {
+            setting_engine.set_bind_address(pair.local.ip());
+        }
+        if let Some(seed) = seed {
+            setting_engine.set_certificate(seed.certificate.certificate.clone());
+            setting_engine.set_ice_credentials(seed.ice_ufrag.clone(), seed.ice_pwd.clone());
+        }
+        setting_engine.set_udp_byte_counts(self.traffic.udp_byte_counts());
+        let sctp_events = self.events.clone();
+        setting_engine.set_sctp_up_events(Arc::new(move |up| {
+            let at = Instant::now();
+            sctp_events.emit(if up {
+                SocketEvent::SctpAssociationUp { at }
+            } else {
+                SocketEvent::SctpAssociationDown { at }
+            })
+        }));
+        let pings = Arc::clone(&self.pings);
+        let activity = self.activity.clone();
+        setting_engine.set_sctp_heartbeat_acks(Arc::new(move |info| {
+            if let Some(activity) = &activity {
+                activity.touch_ping();
+            }
+            pings.echoed(info);
+        }));
+        if let Some(rtt_history) = &self.rtt_history {
+            setting_engine.set_rtt_history(Arc::clone(rtt_history));
+        }
+        #[cfg(feature = "transport-events")]
+        self.set_loss_events(&mut setting_engine);
+        let api = Api::new(setting_engine);
+        let peer_connection = api.new_peer_connection().await;
+
+        let negotiated = tokio::select! {
+            negotiated = async {
+                let whip_resource = self
+                    .negotiate(&peer_connection, client_senders, &closed, &drain)
+                    .await?;
+                if let Some((_, ice_timeout)) = cached {
+                    timeout(ice_timeout, ice_connected(&mut events))
+                        .await
+                        .map_err(|_| SocketConnectionError::WebrtcError {
+                            phase: Some(HandshakePhase::IceConnectivity),
+                            source: format!("ICE did not connect within {:?}", ice_timeout).into(),
+                        })?;
+                }
+                Ok(whip_resource)
+            } => negotiated,
+            _ = abort.cancelled() => Err(SocketConnectionError::Aborted),
+        };
+        if negotiated.is_ok() {
+            *self.credentials.lock().expect("credentials lock poisoned") =
+                self.offered_credentials(&peer_connection, seed);
+        }
+        let connection = Connection {
+            span: self.span.clone(),
+            peer_connection,
+            closed,
+            drain,
+            whip_resource: None,
+        };
+        match negotiated {
+            Ok(whip_resource) => Ok(Connection {
+                whip_resource,
+                ..connection
+            }),
+            Err(error) => {
+                // shut down the ICE agent and whatever else the attempt has started
+                connection.close().await;
+                Err(error)
+            }
+        }
+    }
+
+    // negotiate sets up the peer connection and exchanges the offer for the server's answer.
+    // Returns the WHIP resource of the session, if any
+    async fn negotiate(
+        &self,
+        peer_connection: &Arc<RTCPeerConnection>,
+        client_senders: ClientSenders,
+        closed: &CancellationToken,
+        drain: &Arc<Drain>,
+    ) -> Result<Option<(HttpClient, Url)>, SocketConnectionError> {
+        // record when each phase of the handshake starts and ends
+        let timings_ref = self.timings.clone();
+        peer_connection
+            .on_ice_gathering_state_change(Box::new(move |state| {
+                match state {
+                    RTCIceGathererState::Gathering => {
+                        timings_ref.start(HandshakePhase::IceGathering)
+                    }
+                    RTCIceGathererState::Complete => {
+                        timings_ref.finish(HandshakePhase::IceGathering)
+                    }
+                    _ => {}
+                }
+                Box::pin(async {})
+            }))
+            .await;
+        let timings_ref = self.timings.clone();
+        let ice_role_ref = Arc::clone(&self.ice_role);
+        let ice_transport_ref = Arc::downgrade(&peer_connection.sctp().transport().ice_transport);
+        peer_connection
+            .on_ice_connection_state_change(Box::new(move |state| {
+                match state {
+                    RTCIceConnectionState::Checking => {
+                        timings_ref.start(HandshakePhase::IceConnectivity);
+
+                        // the agent has taken its role once it starts checking
+                        let ice_role_ref_2 = Arc::clone(&ice_role_ref);
+                        let ice_transport_ref_2 = ice_transport_ref.clone();
+                        return Box::pin(async move {
+                            if let Some(ice_transport) = ice_transport_ref_2.upgrade() {
+                                *ice_role_ref_2.lock().expect("ICE role lock poisoned") =
+                                    IceRole::from_rtc(ice_transport.role().await);
+                            }
+                        });
+                    }
+                    RTCIceConnectionState::Connected => {
+                        timings_ref.finish(HandshakePhase::IceConnectivity)
+                    }
+                    _ => {}
+                }
+                Box::pin(async {})
+            }))
+            .await;
+        let timings_ref = self.timings.clone();
+        let dtls_info_ref = self.dtls_info.clone();
+        let dtls_transport = peer_connection.sctp().transport();
+        if let Some(certificate) = dtls_transport.certificates.first() {
+            if let Some(fingerprint) = certificate
+                .get_fingerprints()
+                .map_err(SocketConnectionError::in_phase(
+                    HandshakePhase::DtlsHandshake,
+                ))?
+                .first()
+            {
+                self.dtls_info
+                    .set_local_fingerprint(fingerprint.to_string());
+            }
+        }
+        if let Some(candidate_sender) = &self.config.candidate_sender {
+            peer_connection
+                .on_ice_candidate(gathered_candidates(Arc::clone(candidate_sender)))
+                .await;
+        }
+        let source_addr = SourceAddr::default();
+        let source_addr_ref = source_addr.clone();
+        let selected_pair_ref = Arc::clone(&self.selected_pair);
+        let selected_pair_types_ref = Arc::clone(&self.selected_pair_types);
+        let span = self.span.clone();
+        dtls_transport
+            .ice_transport
+            .on_selected_candidate_pair_change(Box::new(move |pair| {
+                let remote = pair.remote();
+                debug!(
+                    parent: &span,
+                    local_type = %pair.local().typ,
+                    remote_type = %remote.typ,
+                    "selected candidate pair"
+                );
+                match remote.address.parse::<IpAddr>() {
+                    Ok(ip) => source_addr_ref.set(SocketAddr::new(ip, remote.port)),
+                    Err(_) => warn!(
+                        parent: &span,
+                        "Selected remote candidate has no IP address: {}", remote.address
+                    ),
+                }
+                let local = pair.local();
+                *selected_pair_types_ref
+                    .lock()
+                    .expect("selected pair types lock poisoned") =
+                    Some((local.typ.to_string(), remote.typ.to_string()));
+                *selected_pair_ref
+                    .lock()
+                    .expect("selected pair lock poisoned") =
+                    match (local.address.parse(), remote.address.parse()) {
+                        (Ok(local_ip), Ok(remote_ip)) => Some(CandidatePair::new(
+                            SocketAddr::new(local_ip, local.port),
+                            SocketAddr::new(remote_ip, remote.port),
+                        )),
+                        _ => None,
+                    };
+                Box::pin(async {})
+            }))
+            .await;
+        let dtls_transport_ref = Arc::downgrade(&dtls_transport);
+        dtls_transport
+            .on_state_change(Box::new(move |state| {
+                match state {
+                    RTCDtlsTransportState::Connecting => {
+                        timings_ref.start(HandshakePhase::DtlsHandshake)
+                    }
+                    RTCDtlsTransportState::Connected => {
+                        timings_ref.finish(HandshakePhase::DtlsHandshake);
+                        timings_ref.start(HandshakePhase::SctpAssociation);
+
+                        // the DTLS connection is stored before Connected is reported
+                        let dtls_info_ref_2 = dtls_info_ref.clone();
+                        let dtls_transport_ref_2 = dtls_transport_ref.clone();
+                        return Box::pin(async move {
+                            let dtls_transport = match dtls_transport_ref_2.upgrade() {
+                                Some(dtls_transport) => dtls_transport,
+                                None => return,
+                            };
+                            if let Some(conn) = dtls_transport.conn().await {
+                                if let Some(info) = DtlsInfo::from_conn(&conn).await {
+                                    dtls_info_ref_2.set(info);
+                                }
+                            }
+                        });
+                    }
+                    _ => {}
+                }
+                Box::pin(async {})
+            }))
+            .await;
 
         let label = "data";
         let protocol = "";
 
         // create a datachannel with label 'data'
         let data_channel = peer_connection
-            .create_data_channel(label, protocol)
+            .create_data_channel(label, protocol, RTCDataChannelInit::default())
             .await
-            .expect("cannot create data channel");
+            .map_err(SocketConnectionError::in_phase(
+                HandshakePhase::SctpAssociation,
+            ))?;
 
         // datachannel on_error callback
+        let span = self.span.clone();
         data_channel
             .on_error(Box::new(move |error| {
-                println!("data channel error: {:?}", error);
+                warn!(parent: &span, "data channel error: {:?}", error);
                 Box::pin(async {})
             }))
             .await;
 
         // datachannel on_open callback
         let data_channel_ref = Arc::clone(&data_channel);
+        let timings_ref = self.timings.clone();
+        let framing = Framing {
+            send_coalescing: self.config.send_coalescing,
+            deframe_received: self.config.deframe_received,
+            compression: self.config.compression.clone(),
+            payload_type: self.config.payload_type,
+        };
+        let message_overhead = self.config.message_overhead();
+        let loops = ChannelLoops {
+            inbound_budget: self.config.inbound_budget,
+            to_client_sender: client_senders.messages.clone(),
+            source_addr: source_addr.clone(),
+            framing,
+            events: self.events.clone(),
+            closed: closed.clone(),
+            drain: Arc::clone(drain),
+            send_closed: self.send_closed.clone(),
+            activity: self.activity.clone(),
+            traffic: Arc::clone(&self.traffic),
+            span: self.span.clone(),
+        };
+        let reliable_loops = loops.clone();
+        let large_loops = loops.clone();
+        let send_rate_limiter = self.config.send_rate_limiter();
+        let to_server_receiver = Arc::clone(&self.to_server_receiver);
+        let sctp_transport = peer_connection.sctp();
+        let max_message_size_ref = Arc::clone(&self.max_message_size);
+        let recv_paused = self.recv_paused.subscribe();
+        let activity = self.activity.clone();
+        let traffic = Arc::clone(&self.traffic);
+        let on_closed = self.config.on_channel_closed.clone();
+        let data_channel_cell = Arc::clone(&self.data_channel);
+        let association_cell = Arc::clone(&self.association);
+        let readiness = Arc::clone(&self.readiness);
         data_channel
             .on_open(Box::new(move || {
+                timings_ref.finish(HandshakePhase::SctpAssociation);
+                let max_message_size = sctp_transport.max_message_size() as usize;
+                max_message_size_ref.store(
+                    max_message_size.saturating_sub(message_overhead),
+                    Ordering::SeqCst,
+                );
                 let data_channel_ref_2 = Arc::clone(&data_channel_ref);
+                let sctp_transport = Arc::clone(&sctp_transport);
                 Box::pin(async move {
+                    *association_cell.lock().expect("association lock poisoned") =
+                        sctp_transport.association().await;
                     let detached_data_channel = data_channel_ref_2
                         .detach()
                         .await
                         .expect("data channel detach got error");
+                    *data_channel_cell
+                        .lock()
+                        .expect("data channel lock poisoned") =
+                        Some(Arc::clone(&detached_data_channel));
+                    readiness.send_replace(Readiness::Open);
 
-                    // Handle reading from the data channel
-                    let detached_data_channel_1 = Arc::clone(&detached_data_channel);
-                    let detached_data_channel_2 = Arc::clone(&detached_data_channel);
-                    tokio::spawn(async move {
-                        let _loop_result =
-                            read_loop(detached_data_channel_1, to_client_sender).await;
-                        // do nothing with result, just close thread
-                    });
-
-                    // Handle writing to the data channel
-                    tokio::spawn(async move {
-                        let _loop_result =
-                            write_loop(detached_data_channel_2, to_server_receiver).await;
-                        // do nothing with result, just close thread
-                    });
+                    let reader = DataChannelReader {
+                        data_channel: Arc::clone(&detached_data_channel),
+                        label,
+                        on_closed,
+                        recv_paused,
+                        activity,
+                        traffic,
+                    };
+                    loops.spawn(
+                        reader,
+                        to_server_receiver,
+                        send_rate_limiter,
+                        max_message_size,
+                        None,
+                    );
                 })
             }))
             .await;
 
+        if self.config.reliable_channel {
+            // a second data channel on its own SCTP stream, for SocketIo::send_reliable
+            self.open_reliable_channel(
+                peer_connection,
+                "reliable",
+                RELIABLE_STREAM_ID,
+                reliable_loops,
+                Arc::clone(&self.reliable_receiver),
+                self.config.reliable_max_in_flight,
+            )
+            .await?;
+        }
+        if let Some(large_sender) = client_senders.large {
+            // chunks go out and come in as they are, and queue up in front of
+            // SocketIo::recv_large rather than in a staging area
+            let large_loops = ChannelLoops {
+                inbound_budget: None,
+                to_client_sender: InboundSender::Plain(large_sender),
+                framing: Framing {
+                    send_coalescing: None,
+                    deframe_received: false,
+                    compression: None,
+                    payload_type: PayloadType::Binary,
+                },
+                ..large_loops
+            };
+            self.open_reliable_channel(
+                peer_connection,
+                "large",
+                LARGE_STREAM_ID,
+                large_loops,
+                Arc::clone(&self.large_receiver),
+                None,
+            )
+            .await?;
+        }
+
         // create an offer to send to the server
-        let offer = peer_connection
-            .create_offer()
-            .await
-            .expect("cannot create offer");
+        let offer =
+            peer_connection
+                .create_offer()
+                .await
+                .map_err(SocketConnectionError::in_phase(
+                    HandshakePhase::IceGathering,
+                ))?;
 
         // sets the LocalDescription, and starts our UDP listeners
-        peer_connection
-            .set_local_description(offer)
-            .await
-            .expect("cannot set local description");
+        peer_connection.set_local_description(offer).await.map_err(
+            SocketConnectionError::in_phase(HandshakePhase::IceGathering),
+        )?;
 
         // send a request to server to initiate connection (signaling, essentially)
-        let http_client = HttpClient::new();
+        let http_client = self.config.http_client();
 
-        let sdp = peer_connection.local_description().await.unwrap().sdp;
-
-        let sdp_len = sdp.len();
+        let local_description = peer_connection.local_description().await.unwrap();
+        *self
+            .local_description
+            .lock()
+            .expect("local description lock poisoned") =
+            Some(LocalDescription::parse(&local_description).map_err(
+                SocketConnectionError::in_phase(HandshakePhase::IceGathering),
+            )?);
 
         // wait to receive a response from server
-        let response: Response = loop {
-            let request = http_client
-                .post(server_url)
-                .header("Content-Length", sdp_len)
-                .body(sdp.clone());
+        self.timings.start(HandshakePhase::Signaling);
+        let (answer, trickled_candidates, whip_resource) =
+            self.signal(&http_client, &local_description.sdp).await?;
+        self.timings.finish(HandshakePhase::Signaling);
 
-            match request.send().await {
-                Ok(resp) => {
-                    break resp;
-                }
-                Err(err) => {
-                    warn!("Could not send request, original error: {:?}", err);
-                    sleep(Duration::from_secs(1)).await;
-                }
-            };
+        let server_candidate = match &trickled_candidates {
+            Some(candidates) => candidates.first().map(String::as_str),
+            None => whip::first_candidate(&answer),
         };
-        let response_string = response.text().await.unwrap();
-
-        // parse session from server response
-        let session_response: JsSessionResponse = get_session_response(response_string.as_str());
+        if let Some(candidate) = server_candidate {
+            self.addr_cell.receive_candidate(candidate).await;
+        }
 
         // apply the server's response as the remote description
-        let session_description =
-            RTCSessionDescription::answer(session_response.answer.sdp).unwrap();
+        let session_description = RTCSessionDescription::answer(answer)
+            .map_err(SocketConnectionError::in_phase(HandshakePhase::Signaling))?;
+        check_answer(&local_description, &session_description)
+            .map_err(|reason| SocketConnectionError::IncompatibleAnswer { reason })?;
 
         peer_connection
             .set_remote_description(session_description)
             .await
-            .expect("cannot set remote description");
+            .map_err(SocketConnectionError::in_phase(HandshakePhase::Signaling))?;
 
-        addr_cell
-            .receive_candidate(session_response.candidate.candidate.as_str())
-            .await;
+        // add the ice candidates to the connection. A candidate which can't be added is
+        // skipped, the connection only fails if none of them can be
+        if let Some(candidates) = trickled_candidates {
+            let mut added_any = false;
+            let mut last_err = None;
+            for candidate in candidates {
+                let added = add_remote_candidate(peer_connection, &candidate).await;
+                if let Some(on_remote_candidate) = &self.config.on_remote_candidate {
+                    (on_remote_candidate
+                        .lock()
+                        .expect("remote candidate callback lock poisoned"))(
+                        &candidate,
+                        added.clone(),
+                    );
+                }
+                match added {
+                    Ok(()) => added_any = true,
+                    Err(CandidateError::Unreachable) => {
+                        added_any = true;
+                        warn!(
+                            "No local candidate can reach the server's candidate {}",
+                            candidate
+                        );
+                    }
+                    Err(err) => {
+                        warn!("Skipping the server's candidate {}: {}", candidate, err);
+                        last_err = Some(err);
+                    }
+                }
+            }
+            if let (false, Some(err)) = (added_any, last_err) {
+                return Err(SocketConnectionError::WebrtcError {
+                    phase: Some(HandshakePhase::Signaling),
+                    source: Box::new(err),
+                });
+            }
+        }
+
+        Ok(whip_resource.map(|resource| (http_client, resource)))
+    }
 
-        // add ice candidate to connection
-        if let Err(error) = peer_connection
-            .add_ice_candidate(session_response.candidate.candidate)
+    // open_reliable_channel creates a reliable, ordered data channel on the SCTP stream
+    // `id`, which writes the messages of `to_server_receiver` once it opens
+    async fn open_reliable_channel<M: Queued>(
+        &self,
+        peer_connection: &Arc<RTCPeerConnection>,
+        label: &'static str,
+        id: u16,
+        loops: ChannelLoops,
+        to_server_receiver: Arc<Mutex<mpsc::Receiver<M>>>,
+        max_in_flight: Option<usize>,
+    ) -> Result<(), SocketConnectionError> {
+        let data_channel = peer_connection
+            .create_data_channel(label, "", RTCDataChannelInit { id, reliable: true })
             .await
-        {
-            panic!("Error during add_ice_candidate: {:?}", error);
+            .map_err(SocketConnectionError::in_phase(
+                HandshakePhase::SctpAssociation,
+            ))?;
+        let data_channel_ref = Arc::clone(&data_channel);
+        let send_rate_limiter = self.config.send_rate_limiter();
+        let sctp_transport = peer_connection.sctp();
+        let recv_paused = self.recv_paused.subscribe();
+        let activity = self.activity.clone();
+        let traffic = Arc::clone(&self.traffic);
+        let on_closed = self.config.on_channel_closed.clone();
+        data_channel
+            .on_open(Box::new(move || {
+                let max_message_size = sctp_transport.max_message_size() as usize;
+                Box::pin(async move {
+                    let detached_data_channel = data_channel_ref
+                        .detach()
+                        .await
+                        .expect("data channel detach got error");
+                    let reader = DataChannelReader {
+                        data_channel: detached_data_channel,
+                        label,
+                        on_closed,
+                        recv_paused,
+                        activity,
+                        traffic,
+                    };
+                    loops.spawn(
+                        reader,
+                        to_server_receiver,
+                        send_rate_limiter,
+                        max_message_size,
+                        max_in_flight,
+                    );
+                })
+            }))
+            .await;
+        Ok(())
+    }
+
+    // signal exchanges the offer for the server's answer, the candidates trickled with it
+    // and the WHIP resource of the session, trying the fallback server urls in order when
+    // the server url fails
+    async fn signal(
+        &self,
+        http_client: &HttpClient,
+        sdp: &str,
+    ) -> Result<(String, Option<Vec<String>>, Option<Url>), SocketConnectionError> {
+        let mut last_err = None;
+        let server_urls = std::iter::once(&self.server_url).chain(&self.fallback_server_urls);
+        for (index, server_url) in server_urls.enumerate() {
+            let answered = match self.config.signaling {
+                Signaling::WebrtcUnreliable => self
+                    .post_offer(http_client, server_url, sdp.to_owned())
+                    .await
+                    .map(|session_response| {
+                        let candidates: Vec<String> = session_response
+                            .candidates
+                            .into_iter()
+                            .map(|candidate| candidate.candidate)
+                            .collect();
+                        (session_response.answer.sdp, Some(candidates), None)
+                    }),
+                Signaling::Whip => whip::post_offer(
+                    http_client,
+                    server_url,
+                    sdp.to_owned(),
+                    self.config.signaling_retry().as_ref(),
+                )
+                .await
+                // the candidates come with the answer
+                .map(|answer| (answer.sdp, None, answer.resource)),
+            };
+            match answered {
+                Ok(answered) => {
+                    if !self.fallback_server_urls.is_empty() {
+                        debug!("Signaled through {}", server_url);
+                    }
+                    return Ok(answered);
+                }
+                Err(err) if index < self.fallback_server_urls.len() => {
+                    warn!(
+                        "Signaling through {} failed, trying the next server url: {}",
+                        server_url, err
+                    );
+                    last_err = Some(err);
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.expect("the server url is always tried"))
+    }
+
+    // post_offer sends the offer to `server_url`, retrying until it can be reached, and
+    // returns its answer
+    async fn post_offer(
+        &self,
+        http_client: &HttpClient,
+        server_url: &Url,
+        sdp: String,
+    ) -> Result<JsSessionResponse, SocketConnectionError> {
+        let response: Response =
+            signaling_retry::send(self.config.signaling_retry().as_ref(), || {
+                SignalingRequest::build(
+                    self.config.signaling_request.as_ref(),
+                    http_client,
+                    server_url.clone(),
+                    &sdp,
+                )
+            })
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SocketConnectionError::Signaling {
+                reason: format!("the signaling server responded with {}", status),
+            });
+        }
+        let response_string =
+            response
+                .text()
+                .await
+                .map_err(|err| SocketConnectionError::Signaling {
+                    reason: format!("could not read the answer: {}", err),
+                })?;
+
+        // parse session from server response
+        get_session_response(response_string.as_str()).map_err(|reason| {
+            SocketConnectionError::Signaling {
+                reason: format!("could not read the answer: {}", reason),
+            }
+        })
+    }
+}
+
+// ClientSenders are what the read loops deliver inbound messages with
+#[derive(Clone)]
+struct ClientSenders {
+    messages: InboundSender,
+    // the chunks read from the large transfer data channel, if it is enabled
+    large: Option<mpsc::Sender<Box<[u8]>>>,
+}
+
+// ChannelLoops holds what the read and write loops of a datachannel share with the
+// other datachannels of the connection
+#[derive(Clone)]
+struct ChannelLoops {
+    // with an inbound budget, each datachannel stages its messages for its own deliver_loop
+    inbound_budget: Option<InboundBudget>,
+    to_client_sender: InboundSender,
+    source_addr: SourceAddr,
+    framing: Framing,
+    events: EventSender,
+    closed: CancellationToken,
+    drain: Arc<Drain>,
+    send_closed: CancellationToken,
+    activity: Option<Arc<Activity>>,
+    traffic: Arc<Traffic>,
+    // the span of the connection, which the loops run in
+    span: Span,
+}
+
+impl ChannelLoops {
+    // spawn starts reading from the datachannel of `reader`, and writing the messages of
+    // `to_server_receiver` to it
+    fn spawn<M: Queued>(
+        self,
+        reader: DataChannelReader,
+        to_server_receiver: Arc<Mutex<mpsc::Receiver<M>>>,
+        send_rate_limiter: Option<SendRateLimiter>,
+        max_message_size: usize,
+        max_in_flight: Option<usize>,
+    ) {
+        let ChannelLoops {
+            inbound_budget,
+            to_client_sender,
+            source_addr,
+            framing,
+            events,
+            closed,
+            drain,
+            send_closed,
+            activity,
+            traffic,
+            span,
+        } = self;
+        if let Some(activity) = &activity {
+            // the data channel has just opened
+            activity.touch();
+        }
+        let writer = DataChannelWriter {
+            data_channel: Arc::clone(&reader.data_channel),
+            max_in_flight,
+            activity,
+            traffic,
+        };
+
+        // Handle reading from the data channel
+        let closed_ref = closed.clone();
+        let framing_ref = framing.clone();
+        let events_ref = events.clone();
+        match inbound_budget {
+            Some(inbound_budget) => {
+                let inbound_staging = InboundStaging::new(inbound_budget, events.clone());
+                let inbound_staging_ref = Arc::clone(&inbound_staging);
+                tokio::spawn(
+                    async move {
+                        let _loop_result =
+                            deliver_loop(inbound_staging_ref, to_client_sender, source_addr).await;
+                    }
+                    .instrument(span.clone()),
+                );
+                tokio::spawn(
+                    async move {
+                        let _loop_result = staged_read_loop(
+                            reader,
+                            inbound_staging,
+                            framing_ref,
+                            events_ref,
+                            closed_ref,
+                        )
+                        .await;
+                    }
+                    .instrument(span.clone()),
+                );
+            }
+            None => {
+                tokio::spawn(
+                    async move {
+                        let _loop_result = read_loop(
+                            reader,
+                            to_client_sender,
+                            source_addr,
+                            framing_ref,
+                            events_ref,
+                            closed_ref,
+                        )
+                        .await;
+                        // do nothing with result, just close thread
+                    }
+                    .instrument(span.clone()),
+                );
+            }
         }
 
-        (addr_cell, to_server_sender, to_client_receiver)
+        // Handle writing to the data channel
+        let draining = drain.started.clone();
+        drain.track(tokio::spawn(
+            async move {
+                let _loop_result = write_loop(WriteLoop {
+                    writer,
+                    to_server_receiver,
+                    send_rate_limiter,
+                    framing,
+                    max_message_size,
+                    events,
+                    closed,
+                    send_closed,
+                    draining,
+                })
+                .await;
+                // do nothing with result, just close thread
+            }
+            .instrument(span),
+        ));
     }
 }
 
 // read_loop shows how to read from the datachannel directly
 async fn read_loop(
-    data_channel: Arc<DataChannel>,
-    to_client_sender: mpsc::Sender<Box<[u8]>>,
+    mut reader: DataChannelReader,
+    to_client_sender: InboundSender,
+    source_addr: SourceAddr,
+    framing: Framing,
+    events: EventSender,
+    closed: CancellationToken,
 ) -> Result<()> {
     let mut buffer = vec![0u8; MESSAGE_SIZE];
     loop {
-        let message_length = match data_channel.read(&mut buffer).await {
-            Ok(length) => length,
+        let read_result = tokio::select! {
+            read_result = reader.read(&mut buffer) => read_result,
+            _ = closed.cancelled() => return Ok(()),
+        };
+        let (message_length, payload_type) = match read_result {
+            Ok(read) => read,
+            Err(err) => {
+                debug!("Datachannel closed; Exit the read_loop: {}", err);
+                read_ended(&events, &reader, err);
+                return Ok(());
+            }
+        };
+
+        let messages = framing.unpack(&buffer[..message_length]);
+        for message in messages {
+            trace!(size = message.len(), "received message");
+            to_client_sender
+                .send(message, payload_type, &source_addr)
+                .await?;
+        }
+    }
+}
+
+// staged_read_loop reads from the datachannel into a byte-budgeted staging area, so a slow
+// receiver doesn't stop the datachannel from being drained
+async fn staged_read_loop(
+    mut reader: DataChannelReader,
+    inbound_staging: Arc<InboundStaging>,
+    framing: Framing,
+    events: EventSender,
+    closed: CancellationToken,
+) -> Result<()> {
+    let mut buffer = vec![0u8; MESSAGE_SIZE];
+    loop {
+        let read_result = tokio::select! {
+            read_result = reader.read(&mut buffer) => read_result,
+            _ = closed.cancelled() => {
+                inbound_staging.close();
+                return Ok(());
+            }
+        };
+        let (message_length, payload_type) = match read_result {
+            Ok(read) => read,
             Err(err) => {
-                println!("Datachannel closed; Exit the read_loop: {}", err);
+                debug!("Datachannel closed; Exit the read_loop: {}", err);
+                read_ended(&events, &reader, err);
+                inbound_staging.close();
                 return Ok(());
             }
         };
 
-        match to_client_sender.send(buffer[..message_length].into()).await {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Error::new(e));
+        let messages = framing.unpack(&buffer[..message_length]);
+        for message in messages {
+            trace!(size = message.len(), "received message");
+            match inbound_staging.push(message, payload_type) {
+                Ok(()) => {}
+                Err(PushError::Closed) => {
+                    debug!("Inbound staging closed; Exit the read_loop");
+                    return Ok(());
+                }
+                Err(PushError::BudgetExceeded) => {
+                    warn!("Inbound buffer budget exceeded, closing the data channel");
+                    reader.data_channel.close().await?;
+                    return Ok(());
+                }
             }
         }
     }
 }
 
-// write_loop shows how to write to the datachannel directly
-async fn write_loop(
+// Framing holds how messages are packed into the messages of the datachannel
+#[derive(Clone)]
+struct Framing {
+    send_coalescing: Option<SendCoalescing>,
+    deframe_received: bool,
+    compression: Option<PayloadCompression>,
+    // what the sent messages are marked as
+    payload_type: PayloadType,
+}
+
+impl Framing {
+    // unpack turns a message read from the datachannel into the messages it carries
+    fn unpack(&self, payload: &[u8]) -> Vec<Box<[u8]>> {
+        let decompressed;
+        let payload = match &self.compression {
+            Some(compression) => match compression.decompress(payload) {
+                Some(payload) => {
+                    decompressed = payload;
+                    &decompressed[..]
+                }
+                None => return Vec::new(),
+            },
+            None => payload,
+        };
+        if self.deframe_received {
+            deframe(payload)
+        } else {
+            vec![payload.into()]
+        }
+    }
+}
+
+// DataChannelReader reads the messages of the datachannel for the read loops
+struct DataChannelReader {
     data_channel: Arc<DataChannel>,
-    mut to_server_receiver: mpsc::Receiver<Box<[u8]>>,
+    label: &'static str,
+    on_closed: Option<ChannelClosedFn>,
+    recv_paused: watch::Receiver<bool>,
+    activity: Option<Arc<Activity>>,
+    traffic: Arc<Traffic>,
+}
+
+impl DataChannelReader {
+    // read reads from the datachannel while reading isn't paused. A read still pending when
+    // reading gets paused is abandoned, so nothing more is delivered once pause_recv returns
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<(usize, PayloadType), DataChannelError> {
+        loop {
+            while *self.recv_paused.borrow_and_update() {
+                if self.recv_paused.changed().await.is_err() {
+                    break;
+                }
+            }
+            let read_result = tokio::select! {
+                read_result = self.data_channel.read_payload(buffer) => read_result,
+                changed = self.recv_paused.changed() => {
+                    if changed.is_ok() {
+                        continue;
+                    }
+                    // the session has gone away, so reading can't be paused anymore
+                    self.data_channel.read_payload(buffer).await
+                }
+            };
+            let (n, ppid) = read_result?;
+            if let Some(activity) = &self.activity {
+                activity.touch();
+            }
+            self.traffic.payload_received(n);
+            return Ok((n, PayloadType::from_ppid(ppid)));
+        }
+    }
+}
+
+// deliver_loop hands staged messages over to the client
+async fn deliver_loop(
+    inbound_staging: Arc<InboundStaging>,
+    to_client_sender: InboundSender,
+    source_addr: SourceAddr,
 ) -> Result<()> {
+    while let Some((message, payload_type)) = inbound_staging.pop().await {
+        to_client_sender
+            .send(message, payload_type, &source_addr)
+            .await?;
+    }
+    Ok(())
+}
+
+// DataChannelWriter is the datachannel the write loop writes to, the cap on its messages
+// in flight, and where its writes are recorded for the idle timeout and the stats
+struct DataChannelWriter {
+    data_channel: Arc<DataChannel>,
+    max_in_flight: Option<usize>,
+    activity: Option<Arc<Activity>>,
+    traffic: Arc<Traffic>,
+}
+
+// WriteLoop holds what the write loop of a datachannel takes: the queue it sends from, how
+// the messages are limited and framed, and the tokens which end it
+struct WriteLoop<M> {
+    writer: DataChannelWriter,
+    to_server_receiver: Arc<Mutex<mpsc::Receiver<M>>>,
+    send_rate_limiter: Option<SendRateLimiter>,
+    framing: Framing,
+    max_message_size: usize,
+    events: EventSender,
+    closed: CancellationToken,
+    send_closed: CancellationToken,
+    draining: CancellationToken,
+}
+
+// write_loop shows how to write to the datachannel directly
+async fn write_loop<M: Queued>(write_loop: WriteLoop<M>) -> Result<()> {
+    let WriteLoop {
+        writer,
+        to_server_receiver,
+        mut send_rate_limiter,
+        framing,
+        max_message_size,
+        events,
+        closed,
+        send_closed,
+        draining,
+    } = write_loop;
+    let mut to_server_receiver = tokio::select! {
+        to_server_receiver = to_server_receiver.lock() => to_server_receiver,
+        _ = closed.cancelled() => return Ok(()),
+    };
+    // a coalesced datagram is sent before it grows too large to be written, as it would be
+    // dropped along with every message in it
+    let compression_overhead = match framing.compression {
+        Some(_) => COMPRESSION_HEADER_SIZE,
+        None => 0,
+    };
+    let send_coalescing = framing.send_coalescing.map(|send_coalescing| {
+        send_coalescing.within(max_message_size.saturating_sub(compression_overhead))
+    });
+    // a message which didn't fit into the previous coalesced datagram
+    let mut next_message = None;
+    let mut send_closed_seen = false;
     loop {
-        if let Some(write_message) = to_server_receiver.recv().await {
-            match data_channel.write(&Bytes::from(write_message)).await {
-                Ok(_) => {}
-                Err(e) => {
-                    return Err(Error::new(e));
+        let write_message = match next_message.take() {
+            Some(write_message) => Some(write_message),
+            None => tokio::select! {
+                write_message = to_server_receiver.recv() => write_message,
+                // closing the queue lets recv return what's queued, and then None
+                _ = send_closed.cancelled(), if !send_closed_seen => {
+                    send_closed_seen = true;
+                    to_server_receiver.close();
+                    continue;
+                }
+                // what's queued is still sent, after which the loop waits for the server to
+                // acknowledge it
+                _ = draining.cancelled() => match to_server_receiver.try_recv() {
+                    Ok(write_message) => Some(write_message),
+                    Err(_) => {
+                        debug!("Queue drained; Exit the write_loop");
+                        tokio::select! {
+                            _ = writer.data_channel.wait_buffered_messages_below(1) => {}
+                            _ = closed.cancelled() => {}
+                        }
+                        return Ok(());
+                    }
+                },
+                _ = closed.cancelled() => return Ok(()),
+            },
+        };
+        if let Some(write_message) = write_message {
+            // the cap is waited for before the message is taken, so that it can still be
+            // cancelled meanwhile
+            if let Some(max_in_flight) = writer.max_in_flight {
+                tokio::select! {
+                    _ = writer.data_channel.wait_buffered_messages_below(max_in_flight) => {}
+                    _ = closed.cancelled() => return Ok(()),
+                }
+            }
+            let deadline = write_message.deadline();
+            let write_message = match write_message.take() {
+                Some(write_message) => write_message,
+                None => continue,
+            };
+            let write_message = match send_coalescing {
+                Some(send_coalescing) => {
+                    let datagram = if deadline.is_some() {
+                        frame(&write_message)
+                    } else {
+                        let (datagram, leftover) =
+                            coalesce(write_message, &mut to_server_receiver, send_coalescing).await;
+                        next_message = leftover;
+                        datagram
+                    };
+                    match datagram {
+                        Some(datagram) => datagram,
+                        None => continue,
+                    }
+                }
+                None => Bytes::from(write_message),
+            };
+            let write_message = match &framing.compression {
+                Some(compression) => Bytes::from(compression.compress(&write_message)),
+                None => write_message,
+            };
+            if let Err(err) = check_message_size(write_message.len(), max_message_size) {
+                warn!("Dropping a message: {}", err);
+                continue;
+            }
+            if let Some(send_rate_limiter) = &mut send_rate_limiter {
+                send_rate_limiter.acquire(write_message.len()).await;
+            }
+            let is_string = framing.payload_type == PayloadType::String;
+            match writer
+                .data_channel
+                .write_data_channel(&write_message, is_string, deadline)
+                .await
+            {
+                Ok(written) => {
+                    // writes are all or nothing, so a message is never sent truncated
+                    debug_assert_eq!(written, write_message.len());
+                    trace!(size = written, "sent message");
+                    if let Some(activity) = &writer.activity {
+                        activity.touch();
+                    }
+                    writer.traffic.payload_sent(written);
+                }
+                Err(err) => {
+                    let err = DataChannelError::from(err);
+                    channel_failed(&events, &writer.data_channel, err.clone());
+                    return Err(err.into());
                 }
             }
         } else {
+            if send_closed.is_cancelled() {
+                debug!("Send direction closed; Exit the write_loop");
+                if let Err(err) = writer.data_channel.close_write().await {
+                    channel_failed(&events, &writer.data_channel, err.into());
+                }
+            }
             return Ok(());
         }
     }
 }
 
-#[derive(Clone)]
-pub(crate) struct SessionAnswer {
-    pub(crate) sdp: String,
+// lifetime_timer closes the connection of `session` once `lifetime` has passed since its
+// data channel first opened
+async fn lifetime_timer(
+    session: Weak<Session>,
+    mut events: broadcast::Receiver<SocketEvent>,
+    lifetime: Duration,
+) {
+    loop {
+        match events.recv().await {
+            Ok(SocketEvent::HandshakePhaseFinished {
+                phase: HandshakePhase::SctpAssociation,
+                ..
+            }) => break,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            // the session is gone
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+    drop(events);
+
+    tokio::time::sleep(lifetime).await;
+    let session = match session.upgrade() {
+        Some(session) => session,
+        None => return,
+    };
+    let connection = session.connection.lock().await.take();
+    // nothing to close if the connection was closed meanwhile
+    if let Some(connection) = connection {
+        debug!(
+            "The connection reached its maximum lifetime of {:?}, closing",
+            lifetime
+        );
+        session.events.emit(SocketEvent::LifetimeExpired);
+        connection.close().await;
+    }
+}
+
+// idle_timer closes the connection of `session` once `activity` has recorded no data sent or
+// received for `timeout`, counting from when its data channel first opened
+async fn idle_timer(
+    session: Weak<Session>,
+    activity: Arc<Activity>,
+    mut events: broadcast::Receiver<SocketEvent>,
+    timeout: Duration,
+) {
+    loop {
+        match events.recv().await {
+            Ok(SocketEvent::HandshakePhaseFinished {
+                phase: HandshakePhase::SctpAssociation,
+                ..
+            }) => break,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            // the session is gone
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+    drop(events);
+    // the timeout counts from here, not from when the connection was first attempted
+    activity.touch();
+
+    loop {
+        tokio::time::sleep_until(activity.deadline(timeout)).await;
+        let session = match session.upgrade() {
+            Some(session) => session,
+            None => return,
+        };
+        let mut connection = session.connection.lock().await;
+        // the connection may have been active, or restarted, while the timer slept
+        if activity.deadline(timeout) > tokio::time::Instant::now() {
+            continue;
+        }
+        // nothing to close if the connection was closed meanwhile
+        let connection = match connection.take() {
+            Some(connection) => connection,
+            None => return,
+        };
+        debug!("No data was sent or received for {:?}, closing", timeout);
+        session.events.emit(SocketEvent::IdleTimedOut);
+        connection.close().await;
+        return;
+    }
 }
 
-pub(crate) struct SessionCandidate {
-    pub(crate) candidate: String,
+// ice_connected waits until `events` reports that ICE has connected
+async fn ice_connected(events: &mut broadcast::Receiver<SocketEvent>) {
+    loop {
+        match events.recv().await {
+            Ok(SocketEvent::HandshakePhaseFinished {
+                phase: HandshakePhase::IceConnectivity,
+                ..
+            }) => return,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            // the session holds the sender, so this doesn't happen while it is waited on
+            Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+// read_ended reports why the read loop of `reader` stopped, which is no failure if the
+// server closed its send direction
+fn read_ended(events: &EventSender, reader: &DataChannelReader, error: DataChannelError) {
+    let data_channel = &reader.data_channel;
+    if data_channel.reset_by_peer() {
+        let stream_id = data_channel.stream_identifier();
+        if let Some(on_closed) = &reader.on_closed {
+            (on_closed
+                .lock()
+                .expect("channel closed callback lock poisoned"))(
+                reader.label, stream_id
+            );
+        }
+        events.emit(SocketEvent::RecvClosed { stream_id });
+    } else {
+        channel_failed(events, data_channel, error);
+    }
 }
 
-pub(crate) struct JsSessionResponse {
-    pub(crate) answer: SessionAnswer,
-    pub(crate) candidate: SessionCandidate,
+// channel_failed reports why the loops of `data_channel` stopped
+fn channel_failed(events: &EventSender, data_channel: &DataChannel, error: DataChannelError) {
+    events.emit(SocketEvent::DataChannelFailed {
+        stream_id: data_channel.stream_identifier(),
+        error,
+    });
 }
 
-fn get_session_response(input: &str) -> JsSessionResponse {
-    let json_obj: JsonValue = input.parse().unwrap();
+// close_queue closes the send queue of `receiver` unless a write loop holds it, also dropping
+// the queued messages with `drop_queued`
+fn close_queue<M>(receiver: &Mutex<mpsc::Receiver<M>>, drop_queued: bool) {
+    if let Ok(mut receiver) = receiver.try_lock() {
+        receiver.close();
+        if drop_queued {
+            while receiver.try_recv().is_ok() {}
+        }
+    }
+}
 
-    let sdp_opt: Option<&String> = json_obj["answer"]["sdp"].get();
-    let sdp: String = sdp_opt.unwrap().clone();
+// recv_large receives the next payload of the large transfer data channel, unless it isn't
+// enabled
+async fn recv_large(
+    large_receiver: &mut Option<LargeReceiver>,
+) -> Result<Option<Bytes>, LargeTransferError> {
+    match large_receiver {
+        Some(large_receiver) => large_receiver.recv().await,
+        None => Err(SocketConnectionError::NoLargeTransfers.into()),
+    }
+}
 
-    let candidate_opt: Option<&String> = json_obj["candidate"]["candidate"].get();
-    let candidate: String = candidate_opt.unwrap().clone();
+// drain_receiver moves the messages queued on `receiver` into `out`, returning their count
+fn drain_receiver(receiver: &mut mpsc::Receiver<Box<[u8]>>, out: &mut Vec<Box<[u8]>>) -> usize {
+    let queued = receiver.len();
+    out.reserve(queued);
+    let mut count = 0;
+    while count < queued {
+        match receiver.try_recv() {
+            Ok(message) => {
+                out.push(message);
+                count += 1;
+            }
+            Err(_) => break,
+        }
+    }
+    count
+}
 
-    JsSessionResponse {
-        answer: SessionAnswer { sdp },
-        candidate: SessionCandidate { candidate },
+pub(crate) fn check_message_size(size: usize, max: usize) -> Result<(), SocketConnectionError> {
+    if size > max {
+        Err(SocketConnectionError::MessageTooLarge { size, max })
+    } else {
+        Ok(())
     }
 }
@@ -0,0 +1,77 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use crate::webrtc::ice::UdpByteCounts;
+
+/// The bytes a socket has sent and received, at the time they were taken
+///
+/// Payload bytes are the messages written to and read from the data channels, after
+/// coalescing and compression. Wire bytes are the UDP datagrams carrying them, with the
+/// SCTP and DTLS headers, retransmissions, acknowledgements, pings, and the ICE and DTLS
+/// handshakes. The IP and UDP headers aren't counted. Both add up over the connections of the
+/// socket, restarts included. See [`SocketIo::stats`](crate::SocketIo::stats).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SocketStats {
+    /// Bytes of the messages written to the data channels
+    pub payload_bytes_sent: u64,
+    /// Bytes of the messages read from the data channels
+    pub payload_bytes_received: u64,
+    /// Bytes of the UDP datagrams sent
+    pub wire_bytes_sent: u64,
+    /// Bytes of the UDP datagrams received
+    pub wire_bytes_received: u64,
+    /// UDP datagrams dropped for being larger than the 8192 bytes the receive buffers hold,
+    /// rather than handed on cut short. Their bytes count in `wire_bytes_received` up to the
+    /// size of the buffer
+    pub oversized_datagrams_dropped: u64,
+}
+
+impl SocketStats {
+    /// Returns the bytes sent on top of the payload, the transport's overhead
+    pub fn overhead_bytes_sent(&self) -> u64 {
+        self.wire_bytes_sent.saturating_sub(self.payload_bytes_sent)
+    }
+
+    /// Returns the bytes received on top of the payload, the transport's overhead
+    pub fn overhead_bytes_received(&self) -> u64 {
+        self.wire_bytes_received
+            .saturating_sub(self.payload_bytes_received)
+    }
+}
+
+// Traffic counts the payload bytes of the data channels of a socket, which the read and
+// write loops add to, and the bytes of its UDP sockets, which ICE adds to
+#[derive(Debug, Default)]
+pub(crate) struct Traffic {
+    payload_sent: AtomicU64,
+    payload_received: AtomicU64,
+    udp: Arc<UdpByteCounts>,
+}
+
+impl Traffic {
+    pub(crate) fn payload_sent(&self, n: usize) {
+        self.payload_sent.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn payload_received(&self, n: usize) {
+        self.payload_received.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    // udp_byte_counts returns the counts the ICE UDP sockets of a connection add to
+    pub(crate) fn udp_byte_counts(&self) -> Arc<UdpByteCounts> {
+        Arc::clone(&self.udp)
+    }
+
+    pub(crate) fn stats(&self) -> SocketStats {
+        SocketStats {
+            payload_bytes_sent: self.payload_sent.load(Ordering::Relaxed),
+            payload_bytes_received: self.payload_received.load(Ordering::Relaxed),
+            wire_bytes_sent: self.udp.get_sent(),
+            wire_bytes_received: self.udp.get_received(),
+            oversized_datagrams_dropped: self.udp.get_oversized(),
+        }
+    }
+}
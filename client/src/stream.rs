@@ -0,0 +1,92 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+use tokio::sync::mpsc;
+use tokio_util::sync::{PollSendError, PollSender};
+
+use crate::candidate::CandidateInfo;
+
+/// A [`Stream`] of messages received from the server
+///
+/// The stream ends (yields `None`) once the data channel closes.
+pub struct SocketStream {
+    receiver: mpsc::Receiver<Box<[u8]>>,
+}
+
+impl SocketStream {
+    pub(crate) fn new(receiver: mpsc::Receiver<Box<[u8]>>) -> Self {
+        SocketStream { receiver }
+    }
+
+    /// Returns the underlying receiver
+    pub fn into_inner(self) -> mpsc::Receiver<Box<[u8]>> {
+        self.receiver
+    }
+}
+
+impl Stream for SocketStream {
+    type Item = Box<[u8]>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// A [`Stream`] of the local ICE candidates of a connection, as they are gathered
+///
+/// See [`SocketConfig::candidates_stream`](crate::SocketConfig::candidates_stream). The stream
+/// ends (yields `None`) once gathering has completed.
+pub struct CandidateStream {
+    receiver: mpsc::UnboundedReceiver<CandidateInfo>,
+}
+
+impl CandidateStream {
+    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<CandidateInfo>) -> Self {
+        CandidateStream { receiver }
+    }
+}
+
+impl Stream for CandidateStream {
+    type Item = CandidateInfo;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// A [`Sink`] of messages to be sent to the server
+pub struct SocketSink {
+    sender: PollSender<Box<[u8]>>,
+}
+
+impl SocketSink {
+    pub(crate) fn new(sender: mpsc::Sender<Box<[u8]>>) -> Self {
+        SocketSink {
+            sender: PollSender::new(sender),
+        }
+    }
+}
+
+impl Sink<Box<[u8]>> for SocketSink {
+    type Error = PollSendError<Box<[u8]>>;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.sender).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Box<[u8]>) -> Result<(), Self::Error> {
+        Pin::new(&mut self.sender).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.sender).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.sender).poll_close(cx)
+    }
+}
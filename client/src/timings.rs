@@ -0,0 +1,148 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tracing::Span;
+
+use crate::event::{EventSender, SocketEvent};
+
+/// Time spent in each phase of establishing a connection, measured with a monotonic clock
+///
+/// Phases which have not completed (yet) are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakeTimings {
+    /// Signaling round trip, including DNS resolution: from sending the offer until the
+    /// server's answer has been read
+    pub signaling: Option<Duration>,
+    /// Gathering of the local ICE candidates
+    pub ice_gathering: Option<Duration>,
+    /// ICE connectivity checks, from checking until connected
+    pub ice_connectivity: Option<Duration>,
+    /// The DTLS handshake
+    pub dtls_handshake: Option<Duration>,
+    /// SCTP association and data channel setup, from the end of the DTLS handshake until
+    /// the data channel opened
+    pub sctp_association: Option<Duration>,
+    /// Total time from the start of `Socket::connect`, or of the last
+    /// [`SocketIo::restart_ice`](crate::SocketIo::restart_ice), until the data channel opened
+    pub total: Option<Duration>,
+}
+
+/// A phase of connection establishment, as tracked by [`HandshakeTimings`] and reported by
+/// [`SocketConnectionError::phase`](crate::SocketConnectionError::phase)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandshakePhase {
+    /// Exchanging the offer and answer with the server
+    Signaling,
+    /// Gathering the local ICE candidates
+    IceGathering,
+    /// ICE connectivity checks
+    IceConnectivity,
+    /// The DTLS handshake
+    DtlsHandshake,
+    /// SCTP association and data channel setup
+    SctpAssociation,
+}
+
+#[derive(Default)]
+struct PhaseMarks {
+    started: Option<Instant>,
+    finished: Option<Instant>,
+}
+
+impl PhaseMarks {
+    fn duration(&self) -> Option<Duration> {
+        Some(self.finished?.saturating_duration_since(self.started?))
+    }
+}
+
+struct TimingsState {
+    connect_started: Instant,
+    phases: [PhaseMarks; 5],
+    // the span of each phase in progress, closed when the phase finishes
+    spans: [Option<Span>; 5],
+}
+
+// TimingsCell
+#[derive(Clone)]
+pub(crate) struct TimingsCell {
+    cell: Arc<Mutex<TimingsState>>,
+    // reports each recorded start and finish
+    events: EventSender,
+    // the span of the connection, which the spans of the phases are children of
+    span: Span,
+}
+
+impl TimingsCell {
+    pub(crate) fn new(events: EventSender, span: Span) -> Self {
+        TimingsCell {
+            cell: Arc::new(Mutex::new(TimingsState {
+                connect_started: Instant::now(),
+                phases: Default::default(),
+                spans: Default::default(),
+            })),
+            events,
+            span,
+        }
+    }
+
+    /// Clears all phases and restarts the total clock, for when the connection is
+    /// established again
+    pub(crate) fn reset(&self) {
+        let mut state = self.cell.lock().expect("timings lock poisoned");
+        state.connect_started = Instant::now();
+        state.phases = Default::default();
+        state.spans = Default::default();
+    }
+
+    /// Marks the start of a phase. Only the first call for each phase is recorded
+    pub(crate) fn start(&self, phase: HandshakePhase) {
+        let mut state = self.cell.lock().expect("timings lock poisoned");
+        if let Some(at) = record(&mut state.phases[phase as usize].started) {
+            state.spans[phase as usize] = Some(tracing::debug_span!(
+                parent: &self.span,
+                "handshake_phase",
+                ?phase
+            ));
+            drop(state);
+            self.events
+                .emit(SocketEvent::HandshakePhaseStarted { phase, at });
+        }
+    }
+
+    /// Marks the end of a phase. Only the first call for each phase is recorded
+    pub(crate) fn finish(&self, phase: HandshakePhase) {
+        let mut state = self.cell.lock().expect("timings lock poisoned");
+        if let Some(at) = record(&mut state.phases[phase as usize].finished) {
+            let span = state.spans[phase as usize].take();
+            drop(state);
+            drop(span);
+            self.events
+                .emit(SocketEvent::HandshakePhaseFinished { phase, at });
+        }
+    }
+
+    pub(crate) fn get(&self) -> HandshakeTimings {
+        let state = self.cell.lock().expect("timings lock poisoned");
+        let phase = |phase: HandshakePhase| state.phases[phase as usize].duration();
+        let opened = state.phases[HandshakePhase::SctpAssociation as usize].finished;
+
+        HandshakeTimings {
+            signaling: phase(HandshakePhase::Signaling),
+            ice_gathering: phase(HandshakePhase::IceGathering),
+            ice_connectivity: phase(HandshakePhase::IceConnectivity),
+            dtls_handshake: phase(HandshakePhase::DtlsHandshake),
+            sctp_association: phase(HandshakePhase::SctpAssociation),
+            total: opened.map(|opened| opened.saturating_duration_since(state.connect_started)),
+        }
+    }
+}
+
+// record sets an unset mark to now, returning it
+fn record(mark: &mut Option<Instant>) -> Option<Instant> {
+    match mark {
+        Some(_) => None,
+        None => Some(*mark.insert(Instant::now())),
+    }
+}
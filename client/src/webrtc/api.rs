@@ -1,32 +1,49 @@
+pub(crate) mod setting_engine;
+
 use crate::webrtc::dtls_transport::RTCDtlsTransport;
 use crate::webrtc::ice_transport::ice_gatherer::RTCIceGatherer;
 use crate::webrtc::ice_transport::RTCIceTransport;
 use crate::webrtc::peer_connection::certificate::RTCCertificate;
+use crate::webrtc::peer_connection::RTCPeerConnection;
 
 use crate::webrtc::error::Result;
 use crate::webrtc::sctp_transport::RTCSctpTransport;
 
 use rcgen::KeyPair;
+use setting_engine::SettingEngine;
 use std::sync::Arc;
 
-/// API bundles the global functions of the WebRTC and ORTC API.
+/// Api bundles the global functions of the WebRTC and ORTC API.
 /// Some of these functions are also exported globally using the
 /// defaultAPI object. Note that the global version of the API
 /// may be phased out in the future.
-pub(crate) struct API;
+pub(crate) struct Api {
+    pub(crate) setting_engine: Arc<SettingEngine>,
+}
+
+impl Api {
+    pub(crate) fn new(setting_engine: SettingEngine) -> Self {
+        Api {
+            setting_engine: Arc::new(setting_engine),
+        }
+    }
+
+    /// new_peer_connection creates a new PeerConnection against the received API object
+    pub(crate) async fn new_peer_connection(&self) -> Arc<RTCPeerConnection> {
+        RTCPeerConnection::new(self).await
+    }
 
-impl API {
     /// new_ice_gatherer creates a new ice gatherer.
     /// This constructor is part of the ORTC API. It is not
     /// meant to be used together with the basic WebRTC API.
-    pub(crate) fn new_ice_gatherer() -> Result<RTCIceGatherer> {
-        Ok(RTCIceGatherer::new())
+    pub(crate) fn new_ice_gatherer(&self) -> Result<RTCIceGatherer> {
+        Ok(RTCIceGatherer::new(Arc::clone(&self.setting_engine)))
     }
 
     /// new_ice_transport creates a new ice transport.
     /// This constructor is part of the ORTC API. It is not
     /// meant to be used together with the basic WebRTC API.
-    pub(crate) fn new_ice_transport(gatherer: Arc<RTCIceGatherer>) -> RTCIceTransport {
+    pub(crate) fn new_ice_transport(&self, gatherer: Arc<RTCIceGatherer>) -> RTCIceTransport {
         RTCIceTransport::new(gatherer)
     }
 
@@ -34,21 +51,35 @@ impl API {
     /// This constructor is part of the ORTC API. It is not
     /// meant to be used together with the basic WebRTC API.
     pub(crate) fn new_dtls_transport(
+        &self,
         ice_transport: Arc<RTCIceTransport>,
     ) -> Result<RTCDtlsTransport> {
-        let kp = KeyPair::generate(&rcgen::PKCS_ECDSA_P256_SHA256)?;
-        let cert = RTCCertificate::from_key_pair(kp)?;
+        let cert = match &self.setting_engine.certificate {
+            Some(cert) => cert.clone(),
+            None => {
+                let kp = KeyPair::generate(&rcgen::PKCS_ECDSA_P256_SHA256)?;
+                RTCCertificate::from_key_pair(kp)?
+            }
+        };
         let certificates = vec![cert];
 
-        Ok(RTCDtlsTransport::new(ice_transport, certificates))
+        Ok(RTCDtlsTransport::new(
+            ice_transport,
+            certificates,
+            Arc::clone(&self.setting_engine),
+        ))
     }
 
     /// new_sctp_transport creates a new SCTPTransport.
     /// This constructor is part of the ORTC API. It is not
     /// meant to be used together with the basic WebRTC API.
     pub(crate) fn new_sctp_transport(
+        &self,
         dtls_transport: Arc<RTCDtlsTransport>,
     ) -> Result<RTCSctpTransport> {
-        Ok(RTCSctpTransport::new(dtls_transport))
+        Ok(RTCSctpTransport::new(
+            dtls_transport,
+            Arc::clone(&self.setting_engine),
+        ))
     }
 }
@@ -0,0 +1,236 @@
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use crate::webrtc::dtls::handshaker::FlightRetransmitFn;
+use crate::webrtc::ice::agent::agent_config::CandidatePreferenceFn;
+use crate::webrtc::ice::candidate::CandidateType;
+use crate::webrtc::ice::{RttHistory, UdpByteCounts};
+use crate::webrtc::ice_transport::ice_candidate::RTCIceCandidate;
+use crate::webrtc::ice_transport::ice_role::RTCIceRole;
+use crate::webrtc::peer_connection::certificate::RTCCertificate;
+use crate::webrtc::sctp::association::{AssociationUpFn, HeartbeatAckFn, LossEventFn};
+use crate::webrtc::sctp::timer::rtx_timer::RtoConfig;
+use crate::webrtc::util::conn::conn_filtered::SendFilterFn;
+
+/// CandidateRewriteFn may rewrite a local candidate before it is signaled, and returns
+/// whether to keep it.
+pub(crate) type CandidateRewriteFn = Arc<dyn Fn(&mut RTCIceCandidate) -> bool + Send + Sync>;
+
+/// InterfaceFilter returns whether candidates are gathered on the interface of the given name.
+pub(crate) type InterfaceFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// SettingEngine allows influencing behavior in ways that are not
+/// supported by the WebRTC API. This allows us to support additional
+/// use-cases without deviating from the WebRTC API elsewhere.
+#[derive(Default, Clone)]
+pub(crate) struct SettingEngine {
+    pub(crate) dscp: Option<u8>,
+    pub(crate) udp_recv_buffer_size: Option<usize>,
+    pub(crate) udp_send_buffer_size: Option<usize>,
+    pub(crate) bind_address: Option<IpAddr>,
+    pub(crate) udp_byte_counts: Option<Arc<UdpByteCounts>>,
+    pub(crate) rtt_history: Option<Arc<RttHistory>>,
+    pub(crate) name: String,
+    pub(crate) sctp_max_message_size: u32,
+    pub(crate) sctp_port: Option<u16>,
+    pub(crate) sctp_rto: RtoConfig,
+    pub(crate) sort_candidates: bool,
+    pub(crate) race_candidate_pairs: bool,
+    pub(crate) one_candidate_per_type: bool,
+    pub(crate) candidate_rewrite: Option<CandidateRewriteFn>,
+    pub(crate) interface_filter: Option<InterfaceFilter>,
+    pub(crate) candidate_preference: Option<CandidatePreferenceFn>,
+    pub(crate) nat_1to1_ips: Vec<String>,
+    pub(crate) nat_1to1_ip_candidate_type: CandidateType,
+    pub(crate) handshake_retries: u32,
+    pub(crate) handshake_retry_delay: Duration,
+    pub(crate) certificate: Option<RTCCertificate>,
+    pub(crate) ice_credentials: Option<(String, String)>,
+    pub(crate) dtls_mtu: Option<usize>,
+    pub(crate) dtls_psk: Option<(Vec<u8>, Vec<u8>)>,
+    pub(crate) dtls_flight_retransmit: Option<FlightRetransmitFn>,
+    pub(crate) sctp_loss_events: Option<LossEventFn>,
+    pub(crate) sctp_up_events: Option<AssociationUpFn>,
+    pub(crate) sctp_heartbeat_acks: Option<HeartbeatAckFn>,
+    pub(crate) ice_check_interval: Option<Duration>,
+    pub(crate) ice_max_binding_requests: Option<u16>,
+    pub(crate) ice_role: Option<RTCIceRole>,
+    pub(crate) send_filter: Option<SendFilterFn>,
+}
+
+impl SettingEngine {
+    /// set_dscp sets the DSCP value marked on packets sent from the ICE UDP sockets,
+    /// through the IPv4 ToS or the IPv6 Traffic Class field.
+    pub(crate) fn set_dscp(&mut self, dscp: u8) {
+        self.dscp = Some(dscp);
+    }
+
+    /// set_udp_recv_buffer_size sets SO_RCVBUF on the ICE UDP sockets.
+    pub(crate) fn set_udp_recv_buffer_size(&mut self, bytes: usize) {
+        self.udp_recv_buffer_size = Some(bytes);
+    }
+
+    /// set_udp_send_buffer_size sets SO_SNDBUF on the ICE UDP sockets.
+    pub(crate) fn set_udp_send_buffer_size(&mut self, bytes: usize) {
+        self.udp_send_buffer_size = Some(bytes);
+    }
+
+    /// set_bind_address binds the ICE UDP sockets to `bind_address` only, rather than to
+    /// the addresses of every interface.
+    pub(crate) fn set_bind_address(&mut self, bind_address: IpAddr) {
+        self.bind_address = Some(bind_address);
+    }
+
+    /// set_udp_byte_counts adds the bytes sent and received on the ICE UDP sockets to
+    /// `counts`.
+    pub(crate) fn set_udp_byte_counts(&mut self, counts: Arc<UdpByteCounts>) {
+        self.udp_byte_counts = Some(counts);
+    }
+
+    /// set_rtt_history records the round trip times of the connectivity checks answered on
+    /// the selected candidate pair in `history`.
+    pub(crate) fn set_rtt_history(&mut self, history: Arc<RttHistory>) {
+        self.rtt_history = Some(history);
+    }
+
+    pub(crate) fn set_sctp_max_message_size(&mut self, max_message_size: u32) {
+        self.sctp_max_message_size = max_message_size;
+    }
+
+    /// set_sctp_port sets the SCTP port advertised in the `a=sctp-port` attribute of the
+    /// offer and used by the association, instead of the default of 5000.
+    pub(crate) fn set_sctp_port(&mut self, port: u16) {
+        self.sctp_port = Some(port);
+    }
+
+    /// set_sctp_rto sets the RTO.Initial, RTO.Min and RTO.Max parameters of the SCTP
+    /// association, which bound its retransmission timeout.
+    pub(crate) fn set_sctp_rto(&mut self, initial: Duration, min: Duration, max: Duration) {
+        self.sctp_rto = RtoConfig {
+            initial: initial.as_millis() as u64,
+            min: min.as_millis() as u64,
+            max: max.as_millis() as u64,
+        };
+    }
+
+    /// set_sort_candidates orders the local candidates by priority, then address, instead of
+    /// the order they were gathered in.
+    pub(crate) fn set_sort_candidates(&mut self, sort_candidates: bool) {
+        self.sort_candidates = sort_candidates;
+    }
+
+    /// set_race_candidate_pairs makes the ICE agent nominate the first candidate pair to pass
+    /// its connectivity check, instead of the pair of the highest priority.
+    pub(crate) fn set_race_candidate_pairs(&mut self, race_candidate_pairs: bool) {
+        self.race_candidate_pairs = race_candidate_pairs;
+    }
+
+    /// set_one_candidate_per_type makes the ICE agent keep the first local candidate of each
+    /// type and network type it gathers, closing the others.
+    pub(crate) fn set_one_candidate_per_type(&mut self, one_candidate_per_type: bool) {
+        self.one_candidate_per_type = one_candidate_per_type;
+    }
+
+    /// set_candidate_rewrite passes every local candidate through `rewrite` before it is
+    /// written into a description, dropping the candidates it returns false for.
+    pub(crate) fn set_candidate_rewrite(&mut self, rewrite: CandidateRewriteFn) {
+        self.candidate_rewrite = Some(rewrite);
+    }
+
+    /// set_interface_filter limits candidate gathering to the interfaces `filter` returns
+    /// true for.
+    pub(crate) fn set_interface_filter(&mut self, filter: InterfaceFilter) {
+        self.interface_filter = Some(filter);
+    }
+
+    /// set_candidate_preference makes the ICE agent compute the priority of each host
+    /// candidate from the type and local preferences `preference` returns for its IP.
+    pub(crate) fn set_candidate_preference(&mut self, preference: CandidatePreferenceFn) {
+        self.candidate_preference = Some(preference);
+    }
+
+    /// set_nat_1to1_ips sets the external IP addresses of a 1:1 (D)NAT, and the candidate
+    /// type they are offered as. Each entry is an external IP, or an `external/local` pair
+    /// to map a single local IP.
+    pub(crate) fn set_nat_1to1_ips(&mut self, ips: Vec<String>, candidate_type: CandidateType) {
+        self.nat_1to1_ips = ips;
+        self.nat_1to1_ip_candidate_type = candidate_type;
+    }
+
+    /// set_handshake_retries makes a failed DTLS handshake or SCTP association attempt
+    /// start over up to `retries` times, `delay` apart, over the same ICE connection.
+    pub(crate) fn set_handshake_retries(&mut self, retries: u32, delay: Duration) {
+        self.handshake_retries = retries;
+        self.handshake_retry_delay = delay;
+    }
+
+    /// set_certificate makes the DTLS transports present `certificate`, instead of a
+    /// certificate generated for each of them.
+    pub(crate) fn set_certificate(&mut self, certificate: RTCCertificate) {
+        self.certificate = Some(certificate);
+    }
+
+    /// set_ice_credentials makes the ICE agent use the username fragment `ufrag` and the
+    /// password `pwd`, instead of generating them.
+    pub(crate) fn set_ice_credentials(&mut self, ufrag: String, pwd: String) {
+        self.ice_credentials = Some((ufrag, pwd));
+    }
+
+    /// set_dtls_mtu sets the largest datagram the DTLS transports send during the
+    /// handshake, instead of the default of 1200 bytes.
+    pub(crate) fn set_dtls_mtu(&mut self, mtu: usize) {
+        self.dtls_mtu = Some(mtu);
+    }
+
+    /// set_dtls_psk authenticates the DTLS handshake with the pre-shared `key`, presenting
+    /// `identity`, instead of the certificate, and offers only PSK cipher suites.
+    pub(crate) fn set_dtls_psk(&mut self, identity: Vec<u8>, key: Vec<u8>) {
+        self.dtls_psk = Some((identity, key));
+    }
+
+    /// set_loss_events calls `sctp` with every retransmission and receive gap of the SCTP
+    /// association, and `dtls` with every handshake flight sent again.
+    #[cfg(feature = "transport-events")]
+    pub(crate) fn set_loss_events(&mut self, sctp: LossEventFn, dtls: FlightRetransmitFn) {
+        self.sctp_loss_events = Some(sctp);
+        self.dtls_flight_retransmit = Some(dtls);
+    }
+
+    /// set_send_filter drops the datagrams sent on the selected candidate pair which
+    /// `filter` returns false for, as if they were lost on the way.
+    #[cfg(feature = "network-conditioner")]
+    pub(crate) fn set_send_filter(&mut self, filter: SendFilterFn) {
+        self.send_filter = Some(filter);
+    }
+
+    /// set_sctp_up_events calls `f` with true once the SCTP association is established, and
+    /// with false once it closes after that.
+    pub(crate) fn set_sctp_up_events(&mut self, f: AssociationUpFn) {
+        self.sctp_up_events = Some(f);
+    }
+
+    /// set_sctp_heartbeat_acks calls `f` with the Heartbeat Info of every HEARTBEAT ACK the
+    /// SCTP association receives.
+    pub(crate) fn set_sctp_heartbeat_acks(&mut self, f: HeartbeatAckFn) {
+        self.sctp_heartbeat_acks = Some(f);
+    }
+
+    /// set_ice_checks sets how long the ICE agent waits between rounds of connectivity
+    /// checks while connecting, and how many times it retries the check of a candidate
+    /// pair before marking the pair as failed.
+    pub(crate) fn set_ice_checks(&mut self, interval: Duration, retries: u16) {
+        self.ice_check_interval = Some(interval);
+        self.ice_max_binding_requests = Some(retries);
+    }
+
+    /// set_ice_role makes the ICE agent take `role` instead of the one derived from the
+    /// offer/answer exchange.
+    pub(crate) fn set_ice_role(&mut self, role: RTCIceRole) {
+        self.ice_role = Some(role);
+    }
+
+    /// set_name sets the name prefixed to the log lines of the ICE agent and the SCTP
+    /// association, so that the logs of concurrent connections can be told apart.
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+}
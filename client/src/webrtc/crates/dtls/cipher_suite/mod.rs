@@ -171,6 +171,13 @@ pub(crate) fn cipher_suite_for_id(id: CipherSuiteId) -> Result<Box<dyn CipherSui
     }
 }
 
+// PSK CipherSuites in order of preference, which the defaults leave out
+pub(crate) const PSK_CIPHER_SUITES: [CipherSuiteId; 3] = [
+    CipherSuiteId::Tls_Psk_With_Aes_128_Gcm_Sha256,
+    CipherSuiteId::Tls_Psk_With_Aes_128_Ccm,
+    CipherSuiteId::Tls_Psk_With_Aes_128_Ccm_8,
+];
+
 // CipherSuites we support in order of preference
 pub(crate) fn default_cipher_suites() -> Vec<Box<dyn CipherSuite + Send + Sync>> {
     vec![
@@ -2,7 +2,7 @@ use crate::webrtc::dtls::cipher_suite::*;
 use crate::webrtc::dtls::crypto::*;
 use crate::webrtc::dtls::error::*;
 use crate::webrtc::dtls::extension::extension_use_srtp::SrtpProtectionProfile;
-use crate::webrtc::dtls::handshaker::VerifyPeerCertificateFn;
+use crate::webrtc::dtls::handshaker::{FlightRetransmitFn, VerifyPeerCertificateFn};
 
 use std::sync::Arc;
 use tokio::time::Duration;
@@ -37,6 +37,9 @@ pub(crate) struct Config {
     /// defaults to time.Second
     pub(crate) flight_interval: Duration,
 
+    /// on_flight_retransmit is called whenever a handshake flight is sent again
+    pub(crate) on_flight_retransmit: Option<FlightRetransmitFn>,
+
     /// psk sets the pre-shared key used by this DTLS connection
     /// If psk is non-nil only psk cipher_suites will be used
     pub(crate) psk: Option<PskCallback>,
@@ -79,8 +82,9 @@ pub(crate) struct Config {
     /// certificates unless insecure_skip_verify is given.
     pub(crate) server_name: String,
 
-    /// mtu is the length at which handshake messages will be fragmented to
-    /// fit within the maximum transmission unit (default is 1200 bytes)
+    /// mtu is the largest datagram sent during the handshake: handshake messages are
+    /// fragmented so that every record, headers included, fits within it (default is
+    /// 1200 bytes)
     pub(crate) mtu: usize,
 
     /// replay_protection_window is the size of the replay attack protection window.
@@ -99,6 +103,7 @@ impl Default for Config {
             client_auth: ClientAuthType::default(),
             extended_master_secret: ExtendedMasterSecretType::default(),
             flight_interval: Duration::default(),
+            on_flight_retransmit: None,
             psk: None,
             psk_identity_hint: None,
             insecure_skip_verify: false,
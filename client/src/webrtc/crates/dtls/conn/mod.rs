@@ -12,7 +12,7 @@ use crate::webrtc::dtls::flight::flight6::*;
 use crate::webrtc::dtls::flight::*;
 use crate::webrtc::dtls::fragment_buffer::*;
 use crate::webrtc::dtls::handshake::handshake_cache::*;
-use crate::webrtc::dtls::handshake::handshake_header::HandshakeHeader;
+use crate::webrtc::dtls::handshake::handshake_header::{HandshakeHeader, HANDSHAKE_HEADER_LENGTH};
 use crate::webrtc::dtls::handshake::*;
 use crate::webrtc::dtls::handshaker::*;
 use crate::webrtc::dtls::record_layer::record_layer_header::*;
@@ -232,6 +232,7 @@ impl DTLSConn {
                 None
             },
             retransmit_interval,
+            on_flight_retransmit: config.on_flight_retransmit.take(),
             //log: logger,
             initial_epoch: 0,
             ..Default::default()
@@ -697,7 +698,12 @@ impl DTLSConn {
 
         let mut fragmented_handshakes = vec![];
 
-        let mut content_fragments = split_bytes(&content, maximum_transmission_unit);
+        // Every fragment is sent in its own record, so the headers of both count against
+        // the MTU
+        let fragment_len = maximum_transmission_unit
+            .saturating_sub(RECORD_LAYER_HEADER_SIZE + HANDSHAKE_HEADER_LENGTH)
+            .max(1);
+        let mut content_fragments = split_bytes(&content, fragment_len);
         if content_fragments.is_empty() {
             content_fragments = vec![vec![]];
         }
@@ -742,6 +748,23 @@ impl DTLSConn {
         self.handshake_completed_successfully.load(Ordering::SeqCst)
     }
 
+    /// cipher_suite_id returns the negotiated cipher suite, or None if one hasn't been chosen
+    pub(crate) async fn cipher_suite_id(&self) -> Option<CipherSuiteId> {
+        let cipher_suite = self.state.cipher_suite.lock().await;
+        cipher_suite.as_ref().map(|cipher_suite| cipher_suite.id())
+    }
+
+    /// peer_certificates returns the DER encoded certificate chain presented by the peer
+    pub(crate) fn peer_certificates(&self) -> &[Vec<u8>] {
+        &self.state.peer_certificates
+    }
+
+    /// protocol_version returns the DTLS version used by this connection. Only DTLS 1.2
+    /// is negotiated
+    pub(crate) fn protocol_version(&self) -> ProtocolVersion {
+        PROTOCOL_VERSION1_2
+    }
+
     async fn read_and_buffer(
         ctx: &mut ConnReaderContext,
         next_conn: &Arc<dyn crate::webrtc::util::Conn + Send + Sync>,
@@ -1142,7 +1165,7 @@ fn compact_raw_packets(raw_packets: &[Vec<u8>], maximum_transmission_unit: usize
 
     for raw_packet in raw_packets {
         if !current_combined_raw_packet.is_empty()
-            && current_combined_raw_packet.len() + raw_packet.len() >= maximum_transmission_unit
+            && current_combined_raw_packet.len() + raw_packet.len() > maximum_transmission_unit
         {
             combined_raw_packets.push(current_combined_raw_packet);
             current_combined_raw_packet = vec![];
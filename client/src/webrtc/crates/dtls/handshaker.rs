@@ -5,6 +5,7 @@ use crate::webrtc::dtls::content::*;
 use crate::webrtc::dtls::crypto::*;
 use crate::webrtc::dtls::error::*;
 use crate::webrtc::dtls::extension::extension_use_srtp::*;
+use crate::webrtc::dtls::record_layer::record_layer_header::RECORD_LAYER_HEADER_SIZE;
 use crate::webrtc::dtls::signature_hash_algorithm::*;
 
 use log::*;
@@ -71,6 +72,10 @@ impl fmt::Display for HandshakeState {
 pub(crate) type VerifyPeerCertificateFn =
     Arc<dyn (Fn(&[Vec<u8>], &[rustls::Certificate]) -> Result<()>) + Send + Sync>;
 
+/// FlightRetransmitFn is called whenever a flight is sent again, with the message sequence
+/// of its first handshake message and the size of its records before encryption
+pub(crate) type FlightRetransmitFn = Arc<dyn Fn(u16, usize) + Send + Sync>;
+
 pub(crate) struct HandshakeConfig {
     pub(crate) local_psk_callback: Option<PskCallback>,
     pub(crate) local_psk_identity_hint: Option<Vec<u8>>,
@@ -88,6 +93,7 @@ pub(crate) struct HandshakeConfig {
     pub(crate) server_cert_verifier: Arc<dyn rustls::ServerCertVerifier>,
     pub(crate) client_cert_verifier: Option<Arc<dyn rustls::ClientCertVerifier>>,
     pub(crate) retransmit_interval: tokio::time::Duration,
+    pub(crate) on_flight_retransmit: Option<FlightRetransmitFn>,
     pub(crate) initial_epoch: u16,
     //log           logging.LeveledLogger
     //mu sync.Mutex
@@ -112,6 +118,7 @@ impl Default for HandshakeConfig {
             server_cert_verifier: Arc::new(rustls::WebPKIVerifier::new()),
             client_cert_verifier: None,
             retransmit_interval: tokio::time::Duration::from_secs(0),
+            on_flight_retransmit: None,
             initial_epoch: 0,
         }
     }
@@ -286,6 +293,27 @@ impl DTLSConn {
 
         Ok(HandshakeState::Sending)
     }
+    // report_retransmit tells on_flight_retransmit about the flight about to be sent again
+    fn report_retransmit(&self) {
+        let (on_flight_retransmit, flights) = match (&self.cfg.on_flight_retransmit, &self.flights)
+        {
+            (Some(on_flight_retransmit), Some(flights)) => (on_flight_retransmit, flights),
+            _ => return,
+        };
+        let message_sequence = flights
+            .iter()
+            .find_map(|p| match &p.record.content {
+                Content::Handshake(h) => Some(h.handshake_header.message_sequence),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let size = flights
+            .iter()
+            .map(|p| RECORD_LAYER_HEADER_SIZE + p.record.content.size())
+            .sum();
+        on_flight_retransmit(message_sequence, size);
+    }
+
     async fn send(&mut self) -> Result<HandshakeState> {
         // Send flights
         if let Some(pkts) = self.flights.clone() {
@@ -351,6 +379,7 @@ impl DTLSConn {
                     if !self.retransmit {
                         return Ok(HandshakeState::Waiting);
                     }
+                    self.report_retransmit();
                     return Ok(HandshakeState::Sending);
                 }
 
@@ -388,6 +417,7 @@ impl DTLSConn {
                     Ok(_) => {
                         retransmit_timer.await;
                         // Retransmit last flight
+                        self.report_retransmit();
                         return Ok(HandshakeState::Sending);
                     }
                 };
@@ -47,6 +47,10 @@ pub(crate) fn default_candidate_types() -> Vec<CandidateType> {
 
 pub(crate) type InterfaceFilterFn = Box<dyn (Fn(&str) -> bool) + Send + Sync>;
 
+/// CandidatePreferenceFn returns the type preference and local preference the priority of
+/// the host candidate on the given IP is computed from, or None for the defaults.
+pub(crate) type CandidatePreferenceFn = Arc<dyn (Fn(IpAddr) -> Option<(u8, u16)>) + Send + Sync>;
+
 /// Collects the arguments to `ice::Agent` construction into a single structure, for
 /// future-proofness of the interface.
 #[derive(Default)]
@@ -88,8 +92,8 @@ pub(crate) struct AgentConfig {
     pub(crate) candidate_types: Vec<CandidateType>,
 
     //LoggerFactory logging.LoggerFactory
-    /// Controls how often our internal task loop runs when in the connecting state.
-    /// Only useful for testing.
+    /// Controls how often our internal task loop runs when in the connecting state, which
+    /// paces the connectivity checks.
     pub(crate) check_interval: Duration,
 
     /// The max amount of binding requests the agent will send over a candidate pair for validation
@@ -130,11 +134,50 @@ pub(crate) struct AgentConfig {
     /// A function that you can use in order to whitelist or blacklist the interfaces which are
     /// used to gather ICE candidates.
     pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+
+    /// A function overriding the preferences the priorities of the host candidates are
+    /// computed from, to bias the candidate pairs ICE nominates.
+    pub(crate) candidate_preference: Option<CandidatePreferenceFn>,
+
+    /// The DSCP value marked on packets sent from the host candidates' sockets. Left to the
+    /// operating system's default when None.
+    pub(crate) dscp: Option<u8>,
+
+    /// The sizes of the kernel receive and send buffers of the host candidates' sockets.
+    /// Left to the operating system's defaults when None.
+    pub(crate) udp_recv_buffer_size: Option<usize>,
+    pub(crate) udp_send_buffer_size: Option<usize>,
+
+    /// The only address host candidates are gathered on, instead of the addresses of every
+    /// interface.
+    pub(crate) bind_address: Option<IpAddr>,
+
+    /// Where the bytes sent and received on the host candidates' sockets are counted.
+    pub(crate) udp_byte_counts: Option<Arc<UdpByteCounts>>,
+
+    /// Where the round trip times of the checks answered on the selected pair are recorded.
+    pub(crate) rtt_history: Option<Arc<RttHistory>>,
+
+    /// Nominate the first candidate pair to pass its connectivity check, rather than the
+    /// pair of the highest priority, like happy eyeballs does across address families.
+    pub(crate) race_candidate_pairs: bool,
+
+    /// Keep only the first local candidate of each candidate type and network type, rather
+    /// than one per interface and ICE server.
+    pub(crate) one_candidate_per_type: bool,
+
+    /// Prefixed to the agent's log lines, to tell apart the agents of concurrent connections.
+    pub(crate) name: String,
 }
 
 impl AgentConfig {
     /// Populates an agent and falls back to defaults if fields are unset.
     pub(crate) fn init_with_defaults(&self, a: &mut AgentInternal) {
+        a.race_candidate_pairs = self.race_candidate_pairs;
+        a.one_candidate_per_type = self.one_candidate_per_type;
+        a.rtt_history = self.rtt_history.clone();
+        a.udp_byte_counts = self.udp_byte_counts.clone();
+
         if let Some(max_binding_requests) = self.max_binding_requests {
             a.max_binding_requests = max_binding_requests;
         } else {
@@ -4,7 +4,7 @@ use crate::webrtc::ice::util::*;
 
 use crate::webrtc::util::{vnet::net::*, Conn};
 
-use crate::webrtc::ice::candidate::candidate_base::CandidateBaseConfig;
+use crate::webrtc::ice::candidate::candidate_base::{compute_priority, CandidateBaseConfig};
 use crate::webrtc::ice::candidate::candidate_host::CandidateHostConfig;
 use crate::webrtc::ice::candidate::*;
 use std::sync::Arc;
@@ -16,7 +16,12 @@ pub(crate) struct GatherCandidatesInternalParams {
     pub(crate) mdns_mode: MulticastDnsMode,
     pub(crate) mdns_name: String,
     pub(crate) net: Arc<Net>,
+    pub(crate) dscp: Option<u8>,
+    pub(crate) buffer_sizes: SocketBufferSizes,
+    pub(crate) bind_address: Option<IpAddr>,
+    pub(crate) udp_byte_counts: Option<Arc<UdpByteCounts>>,
     pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+    pub(crate) candidate_preference: Option<CandidatePreferenceFn>,
     pub(crate) ext_ip_mapper: Arc<Option<ExternalIpMapper>>,
     pub(crate) agent_internal: Arc<AgentInternal>,
     pub(crate) gathering_state: Arc<AtomicU8>,
@@ -28,8 +33,13 @@ struct GatherCandidatesLocalParams {
     mdns_mode: MulticastDnsMode,
     mdns_name: String,
     interface_filter: Arc<Option<InterfaceFilterFn>>,
+    candidate_preference: Option<CandidatePreferenceFn>,
     ext_ip_mapper: Arc<Option<ExternalIpMapper>>,
     net: Arc<Net>,
+    dscp: Option<u8>,
+    buffer_sizes: SocketBufferSizes,
+    bind_address: Option<IpAddr>,
+    udp_byte_counts: Option<Arc<UdpByteCounts>>,
     agent_internal: Arc<AgentInternal>,
 }
 
@@ -52,8 +62,13 @@ impl Agent {
                         mdns_mode: params.mdns_mode,
                         mdns_name: params.mdns_name.clone(),
                         interface_filter: Arc::clone(&params.interface_filter),
+                        candidate_preference: params.candidate_preference.clone(),
                         ext_ip_mapper: Arc::clone(&params.ext_ip_mapper),
                         net: Arc::clone(&params.net),
+                        dscp: params.dscp,
+                        buffer_sizes: params.buffer_sizes,
+                        bind_address: params.bind_address,
+                        udp_byte_counts: params.udp_byte_counts.clone(),
                         agent_internal: Arc::clone(&params.agent_internal),
                     };
 
@@ -102,20 +117,33 @@ impl Agent {
             mdns_mode,
             mdns_name,
             interface_filter,
+            candidate_preference,
             ext_ip_mapper,
             net,
+            dscp,
+            buffer_sizes,
+            bind_address,
+            udp_byte_counts,
             agent_internal,
         ) = (
             params.network_types,
             params.mdns_mode,
             params.mdns_name,
             params.interface_filter,
+            params.candidate_preference,
             params.ext_ip_mapper,
             params.net,
+            params.dscp,
+            params.buffer_sizes,
+            params.bind_address,
+            params.udp_byte_counts,
             params.agent_internal,
         );
 
-        let ips = local_interfaces(&net, &*interface_filter, &network_types).await;
+        let ips = match bind_address {
+            Some(bind_address) => std::iter::once(bind_address).collect(),
+            None => local_interfaces(&net, &interface_filter, &network_types).await,
+        };
         for ip in ips {
             let mut mapped_ip = ip;
 
@@ -145,7 +173,9 @@ impl Agent {
             let network = UDP.to_owned();
 
             let conn: Arc<dyn Conn + Send + Sync> =
-                match listen_udp_in_port_range(&net, SocketAddr::new(ip, 0)).await {
+                match listen_udp_in_port_range(&net, SocketAddr::new(ip, 0), dscp, buffer_sizes)
+                    .await
+                {
                     Ok(conn) => conn,
                     Err(err) => {
                         log::warn!(
@@ -158,6 +188,10 @@ impl Agent {
                         continue;
                     }
                 };
+            let conn: Arc<dyn Conn + Send + Sync> = match &udp_byte_counts {
+                Some(counts) => Arc::new(CountingConn::new(conn, Arc::clone(counts))),
+                None => conn,
+            };
 
             let port = match conn.local_addr().await {
                 Ok(addr) => addr.port(),
@@ -171,12 +205,21 @@ impl Agent {
                 }
             };
 
+            // a priority of 0 leaves the candidate to compute its default priority
+            let priority = candidate_preference
+                .as_ref()
+                .and_then(|preference| preference(ip))
+                .map_or(0, |(type_preference, local_preference)| {
+                    compute_priority(type_preference, local_preference, COMPONENT_RTP)
+                });
+
             let host_config = CandidateHostConfig {
                 base_config: CandidateBaseConfig {
                     network: network.clone(),
                     address,
                     port,
                     component: COMPONENT_RTP,
+                    priority,
                     conn: Some(conn),
                     ..CandidateBaseConfig::default()
                 },
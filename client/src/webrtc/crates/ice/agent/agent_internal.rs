@@ -1,8 +1,13 @@
 use super::agent_transport::*;
 use super::*;
 use crate::webrtc::ice::util::*;
+use crate::webrtc::util::rng::with_rng;
 use std::sync::atomic::{AtomicBool, AtomicU64};
 
+// WSAEMSGSIZE is the error Windows fails the read of a datagram larger than the buffer with,
+// having discarded the datagram
+const WSAEMSGSIZE: i32 = 10040;
+
 pub(crate) type ChanCandidateTx =
     Arc<Mutex<Option<mpsc::Sender<Option<Arc<dyn Candidate + Send + Sync>>>>>>;
 
@@ -38,6 +43,7 @@ pub(crate) struct AgentInternal {
     pub(crate) tie_breaker: AtomicU64,
     pub(crate) is_controlling: AtomicBool,
     pub(crate) lite: AtomicBool,
+    pub(crate) name: String,
 
     pub(crate) start_time: Mutex<Instant>,
     pub(crate) nominated_pair: Mutex<Option<Arc<CandidatePair>>>,
@@ -63,6 +69,10 @@ pub(crate) struct AgentInternal {
     pub(crate) srflx_acceptance_min_wait: Duration,
     pub(crate) prflx_acceptance_min_wait: Duration,
     pub(crate) relay_acceptance_min_wait: Duration,
+    pub(crate) race_candidate_pairs: bool,
+    pub(crate) one_candidate_per_type: bool,
+    pub(crate) rtt_history: Option<Arc<RttHistory>>,
+    pub(crate) udp_byte_counts: Option<Arc<UdpByteCounts>>,
     // How long connectivity checks can fail before the ICE Agent
     // goes to disconnected
     pub(crate) disconnected_timeout: Duration,
@@ -105,9 +115,10 @@ impl AgentInternal {
             on_selected_candidate_pair_change_hdlr: Mutex::new(None),
             on_candidate_hdlr: Mutex::new(None),
 
-            tie_breaker: AtomicU64::new(rand::random::<u64>()),
+            tie_breaker: AtomicU64::new(with_rng(|rng| rng.next_u64())),
             is_controlling: AtomicBool::new(config.is_controlling),
             lite: AtomicBool::new(config.lite),
+            name: config.name.clone(),
 
             start_time: Mutex::new(Instant::now()),
             nominated_pair: Mutex::new(None),
@@ -121,6 +132,10 @@ impl AgentInternal {
             host_acceptance_min_wait: Duration::from_secs(0),
             srflx_acceptance_min_wait: Duration::from_secs(0),
             prflx_acceptance_min_wait: Duration::from_secs(0),
+            race_candidate_pairs: false,
+            one_candidate_per_type: false,
+            rtt_history: None,
+            udp_byte_counts: None,
             relay_acceptance_min_wait: Duration::from_secs(0),
 
             // How long connectivity checks can fail before the ICE Agent
@@ -252,7 +267,12 @@ impl AgentInternal {
             let ai = Arc::clone(self);
             tokio::spawn(async move {
                 loop {
-                    let mut interval = DEFAULT_CHECK_INTERVAL;
+                    let mut interval = match last_connection_state {
+                        // While connecting, check candidates at the configured pace, which may
+                        // also be slower than the default
+                        ConnectionState::New | ConnectionState::Checking => check_interval,
+                        _ => DEFAULT_CHECK_INTERVAL,
+                    };
 
                     let mut update_interval = |x: Duration| {
                         if x != ZERO_DURATION && (interval == ZERO_DURATION || interval > x) {
@@ -260,16 +280,11 @@ impl AgentInternal {
                         }
                     };
 
-                    match last_connection_state {
-                        ConnectionState::New | ConnectionState::Checking => {
-                            // While connecting, check candidates more frequently
-                            update_interval(check_interval);
-                        }
-                        ConnectionState::Connected | ConnectionState::Disconnected => {
-                            update_interval(keepalive_interval);
-                        }
-                        _ => {}
-                    };
+                    if let ConnectionState::Connected | ConnectionState::Disconnected =
+                        last_connection_state
+                    {
+                        update_interval(keepalive_interval);
+                    }
                     // Ensure we run our task loop as quickly as the minimum of our various configured timeouts
                     update_interval(disconnected_timeout);
                     update_interval(failed_timeout);
@@ -575,6 +590,26 @@ impl AgentInternal {
                         return Ok(());
                     }
                 }
+
+                if self.one_candidate_per_type
+                    && cands
+                        .iter()
+                        .any(|cand| cand.candidate_type() == c.candidate_type())
+                {
+                    log::debug!(
+                        "[{}]: Dropping candidate {}, one of its type was gathered already",
+                        self.get_name(),
+                        c
+                    );
+                    if let Err(err) = c.close().await {
+                        log::warn!(
+                            "[{}]: Failed to close dropped candidate: {}",
+                            self.get_name(),
+                            err
+                        );
+                    }
+                    return Ok(());
+                }
             }
 
             if let Some(cands) = local_candidates.get_mut(&network_type) {
@@ -816,6 +851,22 @@ impl AgentInternal {
         None
     }
 
+    /// Records the round trip time of `request`, which has just been answered on `p`, if `p`
+    /// is the selected pair.
+    pub(crate) async fn record_rtt(&self, p: &Arc<CandidatePair>, request: &BindingRequest) {
+        if let Some(rtt_history) = &self.rtt_history {
+            let is_selected = self
+                .agent_conn
+                .get_selected_pair()
+                .await
+                .is_some_and(|selected_pair| Arc::ptr_eq(&selected_pair, p));
+            if is_selected {
+                let now = Instant::now();
+                rtt_history.push(now.into_std(), now - request.timestamp);
+            }
+        }
+    }
+
     /// Processes STUN traffic from a remote candidate.
     pub(crate) async fn handle_inbound(
         &self,
@@ -1098,25 +1149,50 @@ impl AgentInternal {
             }
         }
 
-        let mut buffer = vec![0_u8; RECEIVE_MTU];
-        let mut n;
-        let mut src_addr;
+        // datagrams are read in batches where the platform allows, each keeping its own
+        // length and source. The byte past RECEIVE_MTU tells a datagram which fills the
+        // buffer from one which was cut short, as the platform discards what doesn't fit
+        let mut buffers = vec![vec![0_u8; RECEIVE_MTU + 1]; RECEIVE_BATCH_SIZE];
+        let mut received = [(0, addr); RECEIVE_BATCH_SIZE];
         loop {
-            tokio::select! {
-               result = conn.recv_from(&mut buffer) => {
-                   match result {
-                       Ok((num, src)) => {
-                            n = num;
-                            src_addr = src;
-                       }
-                       Err(err) => return Err(Error::Other(err.to_string())),
-                   }
-               },
+            let n = tokio::select! {
+                result = conn.recv_from_batch(&mut buffers, &mut received) => {
+                    match result {
+                        Ok(n) => n,
+                        Err(crate::webrtc::util::Error::Io(err))
+                            if cfg!(windows) && err.0.raw_os_error() == Some(WSAEMSGSIZE) =>
+                        {
+                            self.drop_oversized(None);
+                            continue;
+                        }
+                        Err(err) => return Err(Error::Other(err.to_string())),
+                    }
+                },
                 _  = closed_ch_rx.recv() => return Err(Error::ErrClosed),
+            };
+
+            for (buffer, &(len, src_addr)) in buffers.iter().zip(&received[..n]) {
+                if len > RECEIVE_MTU {
+                    self.drop_oversized(Some(src_addr));
+                    continue;
+                }
+                self.handle_inbound_candidate_msg(&candidate, &buffer[..len], src_addr, addr)
+                    .await;
             }
+        }
+    }
 
-            self.handle_inbound_candidate_msg(&candidate, &buffer[..n], src_addr, addr)
-                .await;
+    // drop_oversized drops a datagram larger than RECEIVE_MTU rather than handing on a
+    // truncated one, and counts it
+    fn drop_oversized(&self, src_addr: Option<SocketAddr>) {
+        log::warn!(
+            "[{}]: Dropped a datagram from {} larger than {} bytes",
+            self.get_name(),
+            src_addr.map_or_else(|| "unknown".to_owned(), |addr| addr.to_string()),
+            RECEIVE_MTU
+        );
+        if let Some(udp_byte_counts) = &self.udp_byte_counts {
+            udp_byte_counts.add_oversized();
         }
     }
 
@@ -1158,11 +1234,16 @@ impl AgentInternal {
         }
     }
 
-    pub(crate) fn get_name(&self) -> &str {
-        if self.is_controlling.load(Ordering::SeqCst) {
+    pub(crate) fn get_name(&self) -> String {
+        let role = if self.is_controlling.load(Ordering::SeqCst) {
             "controlling"
         } else {
             "controlled"
+        };
+        if self.name.is_empty() {
+            role.to_owned()
+        } else {
+            format!("{} {}", self.name, role)
         }
     }
 }
@@ -135,6 +135,30 @@ impl AgentInternal {
         }
     }
 
+    // nominate_first_valid_pair nominates p right away, without waiting for the next check
+    // or for pairs of a higher priority, unless a pair has been nominated already
+    async fn nominate_first_valid_pair(&self, p: Arc<CandidatePair>) {
+        if !(self.is_nominatable(&p.local).await && self.is_nominatable(&p.remote).await) {
+            return;
+        }
+        {
+            let mut nominated_pair = self.nominated_pair.lock().await;
+            if nominated_pair.is_some() {
+                return;
+            }
+            p.nominated.store(true, Ordering::SeqCst);
+            *nominated_pair = Some(Arc::clone(&p));
+        }
+
+        log::debug!(
+            "[{}]: first valid pair found, nominating ({}, {})",
+            self.get_name(),
+            p.local,
+            p.remote
+        );
+        self.nominate_pair().await;
+    }
+
     pub(crate) async fn start(&self) {
         if self.is_controlling.load(Ordering::SeqCst) {
             ControllingSelector::start(self).await;
@@ -318,7 +342,10 @@ impl ControllingSelector for AgentInternal {
                 );
                 if pending_request.is_use_candidate && selected_pair_is_none {
                     self.set_selected_pair(Some(Arc::clone(&p))).await;
+                } else if self.race_candidate_pairs && selected_pair_is_none {
+                    self.nominate_first_valid_pair(Arc::clone(&p)).await;
                 }
+                self.record_rtt(&p, &pending_request).await;
             } else {
                 // This shouldn't happen
                 log::error!("Success response from invalid candidate pair");
@@ -467,6 +494,7 @@ impl ControlledSelector for AgentInternal {
                 p.state
                     .store(CandidatePairState::Succeeded as u8, Ordering::SeqCst);
                 log::trace!("Found valid candidate pair: {}", p);
+                self.record_rtt(&p, &pending_request).await;
             } else {
                 // This shouldn't happen
                 log::error!("Success response from invalid candidate pair");
@@ -167,7 +167,7 @@ impl Conn for AgentConn {
 
         let n = match self.buffer.read(buf, None).await {
             Ok(n) => n,
-            Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err.to_string()).into()),
+            Err(err) => return Err(io::Error::other(err).into()),
         };
         self.bytes_received.fetch_add(n, Ordering::SeqCst);
 
@@ -210,7 +210,7 @@ impl Conn for AgentConn {
                 self.bytes_sent.fetch_add(buf.len(), Ordering::SeqCst);
                 Ok(n)
             }
-            Err(err) => Err(io::Error::new(io::ErrorKind::Other, err.to_string()).into()),
+            Err(err) => Err(io::Error::other(err).into()),
         }
     }
 
@@ -10,6 +10,7 @@ use crate::webrtc::ice::external_ip_mapper::*;
 use crate::webrtc::ice::mdns::*;
 use crate::webrtc::ice::network_type::*;
 use crate::webrtc::ice::state::*;
+use crate::webrtc::ice::util::{RttHistory, SocketBufferSizes, UdpByteCounts};
 use agent_config::*;
 use agent_internal::*;
 
@@ -18,7 +19,7 @@ use crate::webrtc::stun::{
 };
 use crate::webrtc::util::{vnet::net::*, Buffer};
 use std::collections::HashMap;
-use std::net::{Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 use crate::webrtc::ice::agent::agent_gather::GatherCandidatesInternalParams;
 use crate::webrtc::ice::rand::*;
@@ -82,9 +83,14 @@ pub(crate) struct Agent {
     pub(crate) internal: Arc<AgentInternal>,
 
     pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+    pub(crate) candidate_preference: Option<CandidatePreferenceFn>,
     pub(crate) mdns_mode: MulticastDnsMode,
     pub(crate) mdns_name: String,
     pub(crate) net: Arc<Net>,
+    pub(crate) dscp: Option<u8>,
+    pub(crate) buffer_sizes: SocketBufferSizes,
+    pub(crate) bind_address: Option<IpAddr>,
+    pub(crate) udp_byte_counts: Option<Arc<UdpByteCounts>>,
 
     // 1:1 D-NAT IP address mapping
     pub(crate) ext_ip_mapper: Arc<Option<ExternalIpMapper>>,
@@ -160,9 +166,17 @@ impl Agent {
         let agent = Self {
             internal: Arc::new(ai),
             interface_filter: Arc::clone(&config.interface_filter),
+            candidate_preference: config.candidate_preference.clone(),
             mdns_mode,
             mdns_name,
             net,
+            dscp: config.dscp,
+            buffer_sizes: SocketBufferSizes {
+                recv: config.udp_recv_buffer_size,
+                send: config.udp_send_buffer_size,
+            },
+            bind_address: config.bind_address,
+            udp_byte_counts: config.udp_byte_counts.clone(),
             ext_ip_mapper: Arc::new(ext_ip_mapper),
             gathering_state: Arc::new(AtomicU8::new(0)), //GatheringState::New,
             candidate_types,
@@ -263,6 +277,15 @@ impl Agent {
         Ok(res)
     }
 
+    /// Returns whether local candidates of `network_type` have been gathered, which remote
+    /// candidates of the same type are paired with.
+    pub(crate) async fn has_local_candidates(&self, network_type: NetworkType) -> bool {
+        let local_candidates = self.internal.local_candidates.lock().await;
+        local_candidates
+            .get(&network_type)
+            .is_some_and(|candidates| !candidates.is_empty())
+    }
+
     /// Returns the local user credentials.
     pub(crate) async fn get_local_user_credentials(&self) -> (String, String) {
         let ufrag_pwd = self.internal.ufrag_pwd.lock().await;
@@ -372,7 +395,12 @@ impl Agent {
             mdns_mode: self.mdns_mode,
             mdns_name: self.mdns_name.clone(),
             net: Arc::clone(&self.net),
+            dscp: self.dscp,
+            buffer_sizes: self.buffer_sizes,
+            bind_address: self.bind_address,
+            udp_byte_counts: self.udp_byte_counts.clone(),
             interface_filter: self.interface_filter.clone(),
+            candidate_preference: self.candidate_preference.clone(),
             ext_ip_mapper: Arc::clone(&self.ext_ip_mapper),
             agent_internal: Arc::clone(&self.internal),
             gathering_state: Arc::clone(&self.gathering_state),
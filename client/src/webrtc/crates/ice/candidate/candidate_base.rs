@@ -172,9 +172,11 @@ impl Candidate for CandidateBase {
         // candidates for a particular component for a particular data stream
         // that have the same type, the local preference MUST be unique for each
         // one.
-        (1 << 24) * u32::from(self.candidate_type().preference())
-            + (1 << 8) * u32::from(self.local_preference())
-            + (256 - u32::from(self.component()))
+        compute_priority(
+            self.candidate_type().preference() as u8,
+            self.local_preference(),
+            self.component(),
+        )
     }
 
     /// Returns `Option<CandidateRelatedAddress>`.
@@ -304,6 +306,14 @@ impl CandidateBase {
     }
 }
 
+/// Computes the priority of a candidate from its type preference, which is at most 126,
+/// local preference and component, as RFC 8445 section 5.1.2.1 does.
+pub(crate) fn compute_priority(type_preference: u8, local_preference: u16, component: u16) -> u32 {
+    (1 << 24) * u32::from(type_preference.min(126))
+        + (1 << 8) * u32::from(local_preference)
+        + (256 - u32::from(component))
+}
+
 /// Creates a Candidate from its string representation.
 pub(crate) async fn unmarshal_candidate(raw: &str) -> Result<impl Candidate> {
     let split: Vec<&str> = raw.split_whitespace().collect();
@@ -14,6 +14,8 @@ use std::time::SystemTime;
 use tokio::sync::{broadcast, Mutex};
 
 pub(crate) const RECEIVE_MTU: usize = 8192;
+/// The most datagrams read from a candidate's socket at once.
+pub(crate) const RECEIVE_BATCH_SIZE: usize = 16;
 pub(crate) const DEFAULT_LOCAL_PREFERENCE: u16 = 65535;
 
 /// Indicates that the candidate is used for RTP.
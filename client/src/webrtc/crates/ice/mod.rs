@@ -13,3 +13,4 @@ pub(crate) mod use_candidate;
 mod util;
 
 pub(crate) use error::Error;
+pub(crate) use util::{RttHistory, UdpByteCounts};
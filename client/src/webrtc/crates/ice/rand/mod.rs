@@ -1,4 +1,5 @@
-use rand::{thread_rng, Rng};
+use crate::webrtc::util::rng::with_rng;
+use rand::Rng;
 
 const RUNES_ALPHA: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 const RUNES_CANDIDATE_ID_FOUNDATION: &[u8] =
@@ -9,16 +10,14 @@ const LEN_PWD: usize = 32;
 
 //TODO: generates a random string for cryptographic usage.
 pub(crate) fn generate_crypto_random_string(n: usize, runes: &[u8]) -> String {
-    let mut rng = thread_rng();
-
-    let rand_string: String = (0..n)
-        .map(|_| {
-            let idx = rng.gen_range(0..runes.len());
-            runes[idx] as char
-        })
-        .collect();
-
-    rand_string
+    with_rng(|rng| {
+        (0..n)
+            .map(|_| {
+                let idx = rng.gen_range(0..runes.len());
+                runes[idx] as char
+            })
+            .collect()
+    })
 }
 
 /// https://tools.ietf.org/html/rfc5245#section-15.1
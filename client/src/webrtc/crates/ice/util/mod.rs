@@ -4,9 +4,14 @@ use crate::webrtc::ice::network_type::*;
 
 use crate::webrtc::stun::{attributes::*, integrity::*, message::*, textattrs::*};
 use crate::webrtc::util::{vnet::net::*, Conn};
-use std::collections::HashSet;
+use async_trait::async_trait;
+use socket2::SockRef;
+use std::collections::{HashSet, VecDeque};
 use std::net::{IpAddr, SocketAddr};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
 
 pub(crate) fn create_addr(_network: NetworkType, ip: IpAddr, port: u16) -> SocketAddr {
     /*if network.is_tcp(){
@@ -76,9 +81,299 @@ pub(crate) async fn local_interfaces(
     ips
 }
 
+/// The sizes of the kernel buffers of the ICE UDP sockets, SO_RCVBUF and SO_SNDBUF. Left to
+/// the operating system's defaults when None.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SocketBufferSizes {
+    pub(crate) recv: Option<usize>,
+    pub(crate) send: Option<usize>,
+}
+
 pub(crate) async fn listen_udp_in_port_range(
     vnet: &Arc<Net>,
     laddr: SocketAddr,
+    dscp: Option<u8>,
+    buffer_sizes: SocketBufferSizes,
 ) -> Result<Arc<dyn Conn + Send + Sync>> {
-    return Ok(vnet.bind(laddr).await?);
+    // the virtual network has no sockets to configure
+    if vnet.is_virtual() || (dscp.is_none() && buffer_sizes == SocketBufferSizes::default()) {
+        return Ok(vnet.bind(laddr).await?);
+    }
+
+    let socket = UdpSocket::bind(laddr).await?;
+    if let Some(dscp) = dscp {
+        if let Err(err) = set_dscp(&socket, laddr, dscp) {
+            log::warn!("could not set DSCP {} on {}: {}", dscp, laddr, err);
+        }
+    }
+    set_buffer_sizes(&socket, laddr, buffer_sizes);
+    Ok(Arc::new(socket))
+}
+
+/// UdpByteCounts counts the bytes of the datagrams sent and received on the host
+/// candidates' sockets, STUN and DTLS alike. The IP and UDP headers aren't included.
+/// It also counts the datagrams dropped for being larger than the receive buffers.
+#[derive(Debug, Default)]
+pub(crate) struct UdpByteCounts {
+    sent: AtomicU64,
+    received: AtomicU64,
+    oversized: AtomicU64,
+}
+
+impl UdpByteCounts {
+    pub(crate) fn get_sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn get_received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn add_oversized(&self) {
+        self.oversized.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn get_oversized(&self) -> u64 {
+        self.oversized.load(Ordering::Relaxed)
+    }
+}
+
+/// RttHistory keeps the round trip times of the latest connectivity checks answered on the
+/// selected candidate pair, each with when its response arrived, oldest first.
+#[derive(Debug)]
+pub(crate) struct RttHistory {
+    samples: Mutex<VecDeque<(Instant, Duration)>>,
+    capacity: usize,
+}
+
+impl RttHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        RttHistory {
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// push records a round trip time, dropping the oldest one once the history is full.
+    pub(crate) fn push(&self, at: Instant, rtt: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back((at, rtt));
+    }
+
+    pub(crate) fn samples(&self) -> Vec<(Instant, Duration)> {
+        self.samples.lock().unwrap().iter().copied().collect()
+    }
+}
+
+type UtilResult<T> = std::result::Result<T, crate::webrtc::util::Error>;
+
+/// CountingConn adds the datagrams sent and received on a connection to UdpByteCounts.
+pub(crate) struct CountingConn {
+    conn: Arc<dyn Conn + Send + Sync>,
+    counts: Arc<UdpByteCounts>,
+}
+
+impl CountingConn {
+    pub(crate) fn new(conn: Arc<dyn Conn + Send + Sync>, counts: Arc<UdpByteCounts>) -> Self {
+        CountingConn { conn, counts }
+    }
+
+    fn sent(&self, n: usize) -> usize {
+        self.counts.sent.fetch_add(n as u64, Ordering::Relaxed);
+        n
+    }
+
+    fn received(&self, n: usize) -> usize {
+        self.counts.received.fetch_add(n as u64, Ordering::Relaxed);
+        n
+    }
+}
+
+#[async_trait]
+impl Conn for CountingConn {
+    async fn connect(&self, addr: SocketAddr) -> UtilResult<()> {
+        self.conn.connect(addr).await
+    }
+
+    async fn recv(&self, buf: &mut [u8]) -> UtilResult<usize> {
+        Ok(self.received(self.conn.recv(buf).await?))
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> UtilResult<(usize, SocketAddr)> {
+        let (n, addr) = self.conn.recv_from(buf).await?;
+        Ok((self.received(n), addr))
+    }
+
+    async fn recv_from_batch(
+        &self,
+        bufs: &mut [Vec<u8>],
+        received: &mut [(usize, SocketAddr)],
+    ) -> UtilResult<usize> {
+        let n = self.conn.recv_from_batch(bufs, received).await?;
+        self.received(received[..n].iter().map(|&(len, _)| len).sum());
+        Ok(n)
+    }
+
+    async fn send(&self, buf: &[u8]) -> UtilResult<usize> {
+        Ok(self.sent(self.conn.send(buf).await?))
+    }
+
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> UtilResult<usize> {
+        Ok(self.sent(self.conn.send_to(buf, target).await?))
+    }
+
+    async fn local_addr(&self) -> UtilResult<SocketAddr> {
+        self.conn.local_addr().await
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        self.conn.remote_addr().await
+    }
+
+    async fn close(&self) -> UtilResult<()> {
+        self.conn.close().await
+    }
+}
+
+// set_buffer_sizes requests the buffer sizes of the socket, and logs the sizes the kernel
+// actually applied, as it may clamp them to its maximum
+fn set_buffer_sizes(socket: &UdpSocket, laddr: SocketAddr, buffer_sizes: SocketBufferSizes) {
+    let socket = SockRef::from(socket);
+    if let Some(size) = buffer_sizes.recv {
+        set_buffer_size(
+            laddr,
+            "receive",
+            "rmem_max",
+            size,
+            |size| socket.set_recv_buffer_size(size),
+            || socket.recv_buffer_size(),
+        );
+    }
+    if let Some(size) = buffer_sizes.send {
+        set_buffer_size(
+            laddr,
+            "send",
+            "wmem_max",
+            size,
+            |size| socket.set_send_buffer_size(size),
+            || socket.send_buffer_size(),
+        );
+    }
+}
+
+fn set_buffer_size(
+    laddr: SocketAddr,
+    direction: &str,
+    max_sysctl: &str,
+    size: usize,
+    set: impl FnOnce(usize) -> std::io::Result<()>,
+    get: impl FnOnce() -> std::io::Result<usize>,
+) {
+    if let Some(max) = max_buffer_size(max_sysctl) {
+        if size > max {
+            log::warn!(
+                "the {} buffer size {} for {} exceeds the system maximum of {}, raise net.core.{}",
+                direction,
+                size,
+                laddr,
+                max,
+                max_sysctl
+            );
+        }
+    }
+    if let Err(err) = set(size) {
+        log::warn!(
+            "could not set the {} buffer size {} on {}: {}",
+            direction,
+            size,
+            laddr,
+            err
+        );
+        return;
+    }
+    // Linux reports double the requested size, which it reserves for bookkeeping
+    match get() {
+        Ok(applied) if applied < size => log::warn!(
+            "the {} buffer of {} was clamped to {} bytes, {} were requested",
+            direction,
+            laddr,
+            applied,
+            size
+        ),
+        Ok(applied) => log::debug!(
+            "the {} buffer of {} is {} bytes, {} were requested",
+            direction,
+            laddr,
+            applied,
+            size
+        ),
+        Err(err) => log::warn!(
+            "could not read the {} buffer size of {}: {}",
+            direction,
+            laddr,
+            err
+        ),
+    }
+}
+
+// max_buffer_size reads the largest socket buffer size an unprivileged process may set,
+// where the platform tells it
+#[cfg(target_os = "linux")]
+fn max_buffer_size(sysctl: &str) -> Option<usize> {
+    std::fs::read_to_string(format!("/proc/sys/net/core/{}", sysctl))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn max_buffer_size(_sysctl: &str) -> Option<usize> {
+    None
+}
+
+// set_dscp marks the packets sent from the socket with the DSCP value, which occupies the
+// upper six bits of the IPv4 ToS and the IPv6 Traffic Class fields
+fn set_dscp(socket: &UdpSocket, laddr: SocketAddr, dscp: u8) -> std::io::Result<()> {
+    let socket = SockRef::from(socket);
+    let tos = u32::from(dscp) << 2;
+    if laddr.is_ipv4() {
+        socket.set_tos(tos)
+    } else {
+        set_tclass_v6(&socket, tos)
+    }
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn set_tclass_v6(socket: &SockRef<'_>, tclass: u32) -> std::io::Result<()> {
+    socket.set_tclass_v6(tclass)
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+)))]
+fn set_tclass_v6(_socket: &SockRef<'_>, _tclass: u32) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "setting the IPv6 traffic class is not supported on this platform",
+    ))
 }
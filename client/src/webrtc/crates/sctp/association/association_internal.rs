@@ -59,6 +59,9 @@ pub(crate) struct AssociationInternal {
 
     // RTX & Ack timer
     pub(crate) rto_mgr: RtoManager,
+    loss_events: Option<LossEventFn>,
+    up_events: Option<AssociationUpFn>,
+    heartbeat_acks: Option<HeartbeatAckFn>,
     pub(crate) t1init: Option<RtxTimer<AssociationInternal>>,
     pub(crate) t1cookie: Option<RtxTimer<AssociationInternal>>,
     pub(crate) t2shutdown: Option<RtxTimer<AssociationInternal>>,
@@ -113,6 +116,8 @@ impl AssociationInternal {
         }
         let mut a = AssociationInternal {
             name: config.name,
+            source_port: config.local_port,
+            destination_port: config.remote_port,
             max_receive_buffer_size,
             max_message_size: Arc::new(AtomicU32::new(max_message_size)),
 
@@ -129,7 +134,10 @@ impl AssociationInternal {
             my_next_rsn: tsn,
             min_tsn2measure_rtt: tsn,
             state: Arc::new(AtomicU8::new(AssociationState::Closed as u8)),
-            rto_mgr: RtoManager::new(),
+            rto_mgr: RtoManager::new(config.rto),
+            loss_events: config.loss_events,
+            up_events: config.up_events,
+            heartbeat_acks: config.heartbeat_acks,
             streams: HashMap::new(),
             reconfigs: HashMap::new(),
             reconfig_requests: HashMap::new(),
@@ -164,9 +172,6 @@ impl AssociationInternal {
         if let Some(stored_init) = self.stored_init.take() {
             log::debug!("[{}] sending INIT", self.name);
 
-            self.source_port = 5000; // Spec??
-            self.destination_port = 5000; // Spec??
-
             let outbound = Packet {
                 source_port: self.source_port,
                 destination_port: self.destination_port,
@@ -203,6 +208,29 @@ impl AssociationInternal {
         }
     }
 
+    /// caller must hold self.lock
+    pub(crate) fn send_heartbeat(&mut self, info: Bytes) -> Result<()> {
+        if self.get_state() != AssociationState::Established {
+            return Err(Error::ErrHeartbeatStateNotExist);
+        }
+        log::trace!("[{}] sending HEARTBEAT", self.name);
+
+        let outbound = Packet {
+            source_port: self.source_port,
+            destination_port: self.destination_port,
+            verification_tag: self.peer_verification_tag,
+            chunks: vec![Box::new(ChunkHeartbeat {
+                params: vec![Box::new(ParamHeartbeatInfo {
+                    heartbeat_information: info,
+                })],
+            })],
+        };
+
+        self.control_queue.push_back(outbound);
+        self.awake_write_loop();
+        Ok(())
+    }
+
     pub(crate) async fn close(&mut self) -> Result<()> {
         if self.get_state() != AssociationState::Closed {
             self.set_state(AssociationState::Closed);
@@ -463,6 +491,11 @@ impl AssociationInternal {
 
                 if let Some(c) = self.inflight_queue.get(tsn) {
                     self.check_partial_reliability_status(c);
+                    self.report_loss(LossEvent::Retransmit {
+                        tsn: c.tsn,
+                        size: c.user_data.len(),
+                        fast: true,
+                    });
                     to_fast_retrans.push(Box::new(c.clone()));
                     log::trace!(
                         "[{}] fast-retransmit: tsn={} sent={} htna={}",
@@ -638,6 +671,20 @@ impl AssociationInternal {
                 old_state,
                 new_state,
             );
+            if let Some(up_events) = &self.up_events {
+                match (old_state, new_state) {
+                    (_, AssociationState::Established) => up_events(true),
+                    (
+                        AssociationState::Established
+                        | AssociationState::ShutdownPending
+                        | AssociationState::ShutdownSent
+                        | AssociationState::ShutdownReceived
+                        | AssociationState::ShutdownAckSent,
+                        AssociationState::Closed,
+                    ) => up_events(false),
+                    _ => {}
+                }
+            }
         }
     }
 
@@ -693,6 +740,14 @@ impl AssociationInternal {
                         self.use_forward_tsn = true;
                     }
                 }
+            } else if param
+                .as_any()
+                .downcast_ref::<ParamForwardTsnSupported>()
+                .is_some()
+            {
+                // sent in the INIT of this client, see ChunkInit::set_forward_tsn_supported
+                log::debug!("[{}] use ForwardTSN (on init)", self.name);
+                self.use_forward_tsn = true;
             }
         }
         if !self.use_forward_tsn {
@@ -846,6 +901,21 @@ impl AssociationInternal {
         Ok(vec![])
     }
 
+    fn handle_heartbeat_ack(&self, c: &ChunkHeartbeatAck) -> Result<Vec<Packet>> {
+        log::trace!("[{}] chunkHeartbeatAck", self.name);
+        if let Some(hbi) = c
+            .params
+            .first()
+            .and_then(|p| p.as_any().downcast_ref::<ParamHeartbeatInfo>())
+        {
+            if let Some(heartbeat_acks) = &self.heartbeat_acks {
+                heartbeat_acks(&hbi.heartbeat_information);
+            }
+        }
+
+        Ok(vec![])
+    }
+
     async fn handle_cookie_echo(&mut self, c: &ChunkCookieEcho) -> Result<Vec<Packet>> {
         let state = self.get_state();
         log::debug!("[{}] COOKIE-ECHO received in state '{}'", self.name, state);
@@ -931,6 +1001,7 @@ impl AssociationInternal {
         let can_push = self.payload_queue.can_push(d, self.peer_last_tsn);
         let mut stream_handle_data = false;
         if can_push {
+            self.report_gap(d);
             if let Some(_s) = self.get_or_create_stream(d.stream_identifier) {
                 if self.get_my_receiver_window_credit().await > 0 {
                     // Pass the new chunk to stream level as soon as it arrives
@@ -972,6 +1043,31 @@ impl AssociationInternal {
         self.handle_peer_last_tsn_and_acknowledgement(immediate_sack)
     }
 
+    // report_gap reports the TSNs skipped by `d`, if it is beyond every TSN received so far
+    fn report_gap(&self, d: &ChunkPayloadData) {
+        if self.loss_events.is_none() {
+            return;
+        }
+        let highest = match self.payload_queue.get_last_tsn_received() {
+            Some(last_tsn) if sna32gt(*last_tsn, self.peer_last_tsn) => *last_tsn,
+            _ => self.peer_last_tsn,
+        };
+        let missing = d.tsn.wrapping_sub(highest).wrapping_sub(1);
+        if sna32gt(d.tsn, highest) && missing > 0 {
+            self.report_loss(LossEvent::Gap {
+                tsn: d.tsn,
+                size: d.user_data.len(),
+                missing,
+            });
+        }
+    }
+
+    fn report_loss(&self, event: LossEvent) {
+        if let Some(loss_events) = &self.loss_events {
+            loss_events(event);
+        }
+    }
+
     /// A common routine for handle_data and handle_forward_tsn routines
     fn handle_peer_last_tsn_and_acknowledgement(
         &mut self,
@@ -1041,6 +1137,22 @@ impl AssociationInternal {
         }
     }
 
+    /// flight_size returns the number of DATA bytes sent but not yet acknowledged.
+    pub(crate) fn flight_size(&self) -> usize {
+        self.inflight_queue.get_num_bytes()
+    }
+
+    /// pending_bytes returns the number of DATA bytes written by the streams but not yet
+    /// sent, e.g. because cwnd or the peer's rwnd doesn't allow more in flight.
+    pub(crate) fn pending_bytes(&self) -> usize {
+        self.pending_queue.get_num_bytes()
+    }
+
+    /// peer_rwnd returns the peer's receiver window, as calculated from its last SACK.
+    pub(crate) fn peer_rwnd(&self) -> u32 {
+        self.rwnd
+    }
+
     pub(crate) fn open_stream(&mut self, stream_identifier: u16) -> Result<Arc<Stream>> {
         if self.streams.contains_key(&stream_identifier) {
             return Err(Error::ErrStreamAlreadyExist);
@@ -1101,8 +1213,9 @@ impl AssociationInternal {
     async fn process_selective_ack(
         &mut self,
         d: &ChunkSelectiveAck,
-    ) -> Result<(HashMap<u16, i64>, u32)> {
-        let mut bytes_acked_per_stream = HashMap::new();
+    ) -> Result<(HashMap<u16, (i64, usize)>, u32)> {
+        // per stream, the bytes acknowledged and the messages whose last fragment was
+        let mut bytes_acked_per_stream: HashMap<u16, (i64, usize)> = HashMap::new();
 
         // New ack point, so pop all ACKed packets from inflight_queue
         // We add 1 because the "currentAckPoint" has already been popped from the inflight queue
@@ -1127,10 +1240,12 @@ impl AssociationInternal {
                     let n_bytes_acked = c.user_data.len() as i64;
 
                     // Sum the number of bytes acknowledged per stream
-                    if let Some(amount) = bytes_acked_per_stream.get_mut(&c.stream_identifier) {
-                        *amount += n_bytes_acked;
-                    } else {
-                        bytes_acked_per_stream.insert(c.stream_identifier, n_bytes_acked);
+                    let amount = bytes_acked_per_stream
+                        .entry(c.stream_identifier)
+                        .or_default();
+                    amount.0 += n_bytes_acked;
+                    if c.ending_fragment {
+                        amount.1 += 1;
                     }
 
                     // RFC 4960 sec 6.3.1.  RTO Calculation
@@ -1191,10 +1306,12 @@ impl AssociationInternal {
                 if let Some(c) = self.inflight_queue.get(tsn) {
                     if !is_acked {
                         // Sum the number of bytes acknowledged per stream
-                        if let Some(amount) = bytes_acked_per_stream.get_mut(&c.stream_identifier) {
-                            *amount += n_bytes_acked;
-                        } else {
-                            bytes_acked_per_stream.insert(c.stream_identifier, n_bytes_acked);
+                        let amount = bytes_acked_per_stream
+                            .entry(c.stream_identifier)
+                            .or_default();
+                        amount.0 += n_bytes_acked;
+                        if c.ending_fragment {
+                            amount.1 += 1;
                         }
 
                         log::trace!("[{}] tsn={} has been sacked", self.name, c.tsn);
@@ -1415,7 +1532,7 @@ impl AssociationInternal {
         let (bytes_acked_per_stream, htna) = self.process_selective_ack(d).await?;
 
         let mut total_bytes_acked = 0;
-        for n_bytes_acked in bytes_acked_per_stream.values() {
+        for (n_bytes_acked, _) in bytes_acked_per_stream.values() {
             total_bytes_acked += *n_bytes_acked;
         }
 
@@ -1434,9 +1551,10 @@ impl AssociationInternal {
                 .await;
         }
 
-        for (si, n_bytes_acked) in &bytes_acked_per_stream {
+        for (si, (n_bytes_acked, n_messages_acked)) in &bytes_acked_per_stream {
             if let Some(s) = self.streams.get_mut(si) {
-                s.on_buffer_released(*n_bytes_acked).await;
+                s.on_buffer_released(*n_bytes_acked, *n_messages_acked)
+                    .await;
             }
         }
 
@@ -1764,6 +1882,7 @@ impl AssociationInternal {
             for id in &p.stream_identifiers {
                 if let Some(s) = self.streams.get(id) {
                     let stream_identifier = s.stream_identifier;
+                    s.reset_by_peer.store(true, Ordering::SeqCst);
                     self.unregister_stream(stream_identifier);
                 }
             }
@@ -1947,9 +2066,17 @@ impl AssociationInternal {
             return;
         }
 
-        // PR-SCTP
-        if self.streams.contains_key(&c.stream_identifier) {
+        // a message written with a deadline is given up once it has passed, on any stream
+        if c.abandon_at.is_some_and(|at| SystemTime::now() >= at) {
             c.set_abandoned(true);
+            return;
+        }
+
+        // PR-SCTP
+        if let Some(s) = self.streams.get(&c.stream_identifier) {
+            if !s.reliable.load(Ordering::SeqCst) {
+                c.set_abandoned(true);
+            }
         } else {
             log::error!("[{}] stream {} not found)", self.name, c.stream_identifier);
         }
@@ -1990,6 +2117,11 @@ impl AssociationInternal {
 
             if let Some(c) = self.inflight_queue.get(tsn) {
                 self.check_partial_reliability_status(c);
+                self.report_loss(LossEvent::Retransmit {
+                    tsn: c.tsn,
+                    size: c.user_data.len(),
+                    fast: false,
+                });
 
                 log::trace!(
                     "[{}] retransmitting tsn={} ssn={} sent={}",
@@ -2071,6 +2203,8 @@ impl AssociationInternal {
             return Err(Error::ErrChunk);
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkHeartbeat>() {
             self.handle_heartbeat(c).await?
+        } else if let Some(c) = chunk_any.downcast_ref::<ChunkHeartbeatAck>() {
+            self.handle_heartbeat_ack(c)?
         } else if let Some(c) = chunk_any.downcast_ref::<ChunkCookieEcho>() {
             self.handle_cookie_echo(c).await?
         } else if chunk_any.downcast_ref::<ChunkCookieAck>().is_some() {
@@ -55,6 +55,9 @@ pub(crate) const INITIAL_RECV_BUF_SIZE: u32 = 1024 * 1024;
 pub(crate) const COMMON_HEADER_SIZE: u32 = 12;
 pub(crate) const DATA_CHUNK_HEADER_SIZE: u32 = 16;
 pub(crate) const DEFAULT_MAX_MESSAGE_SIZE: u32 = 65536;
+/// The SCTP port used by WebRTC endpoints unless an `a=sctp-port` attribute says otherwise.
+/// <https://www.rfc-editor.org/rfc/rfc8841#section-5>
+pub(crate) const DEFAULT_SCTP_PORT: u16 = 5000;
 
 /// other constants
 pub(crate) const ACCEPT_CH_SIZE: usize = 16;
@@ -179,6 +182,28 @@ impl fmt::Display for AckState {
     }
 }
 
+/// LossEvent reports a DATA chunk sent again, or a gap in the TSNs received, as it happens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LossEvent {
+    /// The chunk `tsn` of `size` bytes of user data was retransmitted, on gap reports if
+    /// `fast`, or else because the T3-rtx timer expired
+    Retransmit { tsn: u32, size: usize, fast: bool },
+    /// The chunk `tsn` of `size` bytes of user data arrived while the `missing` TSNs before
+    /// it haven't
+    Gap { tsn: u32, size: usize, missing: u32 },
+}
+
+/// LossEventFn is called with every LossEvent of an association
+pub(crate) type LossEventFn = Arc<dyn Fn(LossEvent) + Send + Sync>;
+
+/// AssociationUpFn is called with true once an association is established, and with false
+/// once an established association closes
+pub(crate) type AssociationUpFn = Arc<dyn Fn(bool) + Send + Sync>;
+
+/// HeartbeatAckFn is called with the Heartbeat Info of every HEARTBEAT ACK received, which
+/// is what was sent in the HEARTBEAT it answers
+pub(crate) type HeartbeatAckFn = Arc<dyn Fn(&Bytes) + Send + Sync>;
+
 /// Config collects the arguments to create_association construction into
 /// a single structure
 pub(crate) struct Config {
@@ -186,6 +211,14 @@ pub(crate) struct Config {
     pub(crate) max_receive_buffer_size: u32,
     pub(crate) max_message_size: u32,
     pub(crate) name: String,
+    /// The SCTP port of this end, advertised in the local `a=sctp-port`
+    pub(crate) local_port: u16,
+    /// The SCTP port of the peer, advertised in the remote `a=sctp-port`
+    pub(crate) remote_port: u16,
+    pub(crate) rto: RtoConfig,
+    pub(crate) loss_events: Option<LossEventFn>,
+    pub(crate) up_events: Option<AssociationUpFn>,
+    pub(crate) heartbeat_acks: Option<HeartbeatAckFn>,
 }
 
 ///Association represents an SCTP association
@@ -210,6 +243,9 @@ pub(crate) struct Association {
     net_conn: Arc<dyn Conn + Send + Sync>,
 
     pub(crate) association_internal: Arc<Mutex<AssociationInternal>>,
+    // only kept for the server role, so a client drops streams opened by its peer
+    #[cfg_attr(not(feature = "echo-answerer"), allow(dead_code))]
+    accept_ch_rx: Mutex<Option<mpsc::Receiver<Arc<Stream>>>>,
 }
 
 impl Association {
@@ -217,6 +253,23 @@ impl Association {
     pub(crate) async fn client(config: Config) -> Result<Self> {
         let (a, mut handshake_completed_ch_rx) = Association::new(config, true).await?;
 
+        let err = match handshake_completed_ch_rx.recv().await {
+            Some(None) => return Ok(a),
+            Some(Some(err)) => err,
+            None => Error::ErrAssociationHandshakeClosed,
+        };
+
+        // stop the loops without closing net_conn, which another attempt may reuse
+        let mut ai = a.association_internal.lock().await;
+        let _ = ai.close().await;
+        Err(err)
+    }
+
+    /// Server accepts a SCTP stream over a conn
+    #[cfg_attr(not(feature = "echo-answerer"), allow(dead_code))]
+    pub(crate) async fn server(config: Config) -> Result<Self> {
+        let (a, mut handshake_completed_ch_rx) = Association::new(config, false).await?;
+
         if let Some(err_opt) = handshake_completed_ch_rx.recv().await {
             if let Some(err) = err_opt {
                 Err(err)
@@ -228,6 +281,14 @@ impl Association {
         }
     }
 
+    /// accept_stream accepts a stream opened by the peer. Returns None once the association
+    /// has closed, or if this is a client association
+    #[cfg_attr(not(feature = "echo-answerer"), allow(dead_code))]
+    pub(crate) async fn accept_stream(&self) -> Option<Arc<Stream>> {
+        let mut accept_ch_rx = self.accept_ch_rx.lock().await;
+        accept_ch_rx.as_mut()?.recv().await
+    }
+
     /// Close ends the SCTP Association and cleans up any state
     pub(crate) async fn close(&self) -> Result<()> {
         log::debug!("[{}] closing association..", self.name);
@@ -242,7 +303,7 @@ impl Association {
         let net_conn = Arc::clone(&config.net_conn);
 
         let (awake_write_loop_ch_tx, awake_write_loop_ch_rx) = mpsc::channel(1);
-        let (accept_ch_tx, _accept_ch_rx) = mpsc::channel(ACCEPT_CH_SIZE);
+        let (accept_ch_tx, accept_ch_rx) = mpsc::channel(ACCEPT_CH_SIZE);
         let (handshake_completed_ch_tx, handshake_completed_ch_rx) = mpsc::channel(1);
         let (close_loop_ch_tx, _) = broadcast::channel(1);
         let (close_loop_ch_rx1, close_loop_ch_rx2) =
@@ -288,30 +349,36 @@ impl Association {
             let association_internal3 = Arc::clone(&association_internal);
 
             let mut ai = association_internal.lock().await;
+            let rto_max = ai.rto_mgr.config.max;
             ai.t1init = Some(RtxTimer::new(
                 Arc::downgrade(&association_internal3),
                 RtxTimerId::T1Init,
                 MAX_INIT_RETRANS,
+                rto_max,
             ));
             ai.t1cookie = Some(RtxTimer::new(
                 Arc::downgrade(&association_internal3),
                 RtxTimerId::T1Cookie,
                 MAX_INIT_RETRANS,
+                rto_max,
             ));
             ai.t2shutdown = Some(RtxTimer::new(
                 Arc::downgrade(&association_internal3),
                 RtxTimerId::T2Shutdown,
                 NO_MAX_RETRANS,
+                rto_max,
             )); // retransmit forever
             ai.t3rtx = Some(RtxTimer::new(
                 Arc::downgrade(&association_internal3),
                 RtxTimerId::T3RTX,
                 NO_MAX_RETRANS,
+                rto_max,
             )); // retransmit forever
             ai.treconfig = Some(RtxTimer::new(
                 Arc::downgrade(&association_internal3),
                 RtxTimerId::Reconfig,
                 NO_MAX_RETRANS,
+                rto_max,
             )); // retransmit forever
             ai.ack_timer = Some(AckTimer::new(
                 Arc::downgrade(&association_internal3),
@@ -358,6 +425,7 @@ impl Association {
                 name,
                 net_conn,
                 association_internal,
+                accept_ch_rx: Mutex::new(if is_client { None } else { Some(accept_ch_rx) }),
             },
             handshake_completed_ch_rx,
         ))
@@ -477,4 +545,11 @@ impl Association {
         let mut ai = self.association_internal.lock().await;
         ai.open_stream(stream_identifier)
     }
+
+    /// send_heartbeat sends a HEARTBEAT carrying `info`, which the peer echoes back in its
+    /// HEARTBEAT ACK
+    pub(crate) async fn send_heartbeat(&self, info: Bytes) -> Result<()> {
+        let mut ai = self.association_internal.lock().await;
+        ai.send_heartbeat(info)
+    }
 }
@@ -114,6 +114,9 @@ pub(crate) struct ChunkPayloadData {
     pub(crate) since: SystemTime,
     /// number of transmission made for this chunk
     pub(crate) nsent: u32,
+    /// when the chunk is abandoned unless acknowledged, for a message written with a
+    /// deadline
+    pub(crate) abandon_at: Option<SystemTime>,
 
     /// valid only with the first fragment
     pub(crate) abandoned: Arc<AtomicBool>,
@@ -141,6 +144,7 @@ impl Default for ChunkPayloadData {
             miss_indicator: 0,
             since: SystemTime::now(),
             nsent: 0,
+            abandon_at: None,
             abandoned: Arc::new(AtomicBool::new(false)),
             all_inflight: Arc::new(AtomicBool::new(false)),
             retransmit: false,
@@ -219,6 +223,7 @@ impl Chunk for ChunkPayloadData {
             miss_indicator: 0,
             since: SystemTime::now(),
             nsent: 0,
+            abandon_at: None,
             abandoned: Arc::new(AtomicBool::new(false)),
             all_inflight: Arc::new(AtomicBool::new(false)),
             retransmit: false,
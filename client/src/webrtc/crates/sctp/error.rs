@@ -144,6 +144,8 @@ pub(crate) enum Error {
     ErrParamterType,
     #[error("sending payload data in non-Established state")]
     ErrPayloadDataStateNotExist,
+    #[error("sending heartbeat in non-Established state")]
+    ErrHeartbeatStateNotExist,
     #[error("unhandled chunk type")]
     ErrChunkTypeUnhandled,
     #[error("handshake failed (INIT ACK)")]
@@ -164,14 +166,14 @@ pub(crate) enum Error {
     ErrInvalidSystemTime,
 }
 
+// the error is kept as the source of the io::Error, so it can be recovered with downcast
 impl From<Error> for io::Error {
     fn from(error: Error) -> Self {
-        match error {
-            e @ Error::ErrEof => io::Error::new(io::ErrorKind::UnexpectedEof, e.to_string()),
-            e @ Error::ErrStreamClosed => {
-                io::Error::new(io::ErrorKind::ConnectionAborted, e.to_string())
-            }
-            e => io::Error::new(io::ErrorKind::Other, e.to_string()),
-        }
+        let kind = match error {
+            Error::ErrEof => io::ErrorKind::UnexpectedEof,
+            Error::ErrStreamClosed => io::ErrorKind::ConnectionAborted,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, error)
     }
 }
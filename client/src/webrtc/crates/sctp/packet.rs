@@ -7,6 +7,7 @@ use crate::webrtc::sctp::chunk::chunk_error::ChunkError;
 use crate::webrtc::sctp::chunk::chunk_forward_tsn::ChunkForwardTsn;
 use crate::webrtc::sctp::chunk::chunk_header::*;
 use crate::webrtc::sctp::chunk::chunk_heartbeat::ChunkHeartbeat;
+use crate::webrtc::sctp::chunk::chunk_heartbeat_ack::ChunkHeartbeatAck;
 use crate::webrtc::sctp::chunk::chunk_init::ChunkInit;
 use crate::webrtc::sctp::chunk::chunk_payload_data::ChunkPayloadData;
 use crate::webrtc::sctp::chunk::chunk_reconfig::ChunkReconfig;
@@ -116,6 +117,7 @@ impl Packet {
                 CT_COOKIE_ECHO => Box::new(ChunkCookieEcho::unmarshal(&raw.slice(offset..))?),
                 CT_COOKIE_ACK => Box::new(ChunkCookieAck::unmarshal(&raw.slice(offset..))?),
                 CT_HEARTBEAT => Box::new(ChunkHeartbeat::unmarshal(&raw.slice(offset..))?),
+                CT_HEARTBEAT_ACK => Box::new(ChunkHeartbeatAck::unmarshal(&raw.slice(offset..))?),
                 CT_PAYLOAD_DATA => Box::new(ChunkPayloadData::unmarshal(&raw.slice(offset..))?),
                 CT_SACK => Box::new(ChunkSelectiveAck::unmarshal(&raw.slice(offset..))?),
                 CT_RECONFIG => Box::new(ChunkReconfig::unmarshal(&raw.slice(offset..))?),
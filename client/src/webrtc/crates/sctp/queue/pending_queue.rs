@@ -122,4 +122,8 @@ impl PendingQueue {
     pub(crate) fn len(&self) -> usize {
         self.queue_len.load(Ordering::SeqCst)
     }
+
+    pub(crate) fn get_num_bytes(&self) -> usize {
+        self.n_bytes.load(Ordering::SeqCst)
+    }
 }
@@ -13,6 +13,7 @@ use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::SystemTime;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::sync::{mpsc, Mutex, Notify};
 
@@ -35,7 +36,16 @@ pub(crate) struct Stream {
     pub(crate) sequence_number: AtomicU16,
     pub(crate) read_notifier: Notify,
     pub(crate) closed: AtomicBool,
+    // set once the outgoing direction has been reset, while reading may go on
+    pub(crate) write_closed: AtomicBool,
+    // set when the peer reset its outgoing direction, rather than the association closing
+    pub(crate) reset_by_peer: AtomicBool,
+    // reliable streams send ordered and retransmit until acknowledged, even with PR-SCTP
+    pub(crate) reliable: AtomicBool,
     pub(crate) buffered_amount: AtomicUsize,
+    // messages written but not yet fully acknowledged, and notified when some are
+    pub(crate) buffered_messages: AtomicUsize,
+    pub(crate) messages_released: Notify,
     pub(crate) buffered_amount_low: AtomicUsize,
     pub(crate) on_buffered_amount_low: Mutex<Option<OnBufferedAmountLowFn>>,
     pub(crate) name: String,
@@ -52,6 +62,9 @@ impl fmt::Debug for Stream {
             .field("reassembly_queue", &self.reassembly_queue)
             .field("sequence_number", &self.sequence_number)
             .field("closed", &self.closed)
+            .field("write_closed", &self.write_closed)
+            .field("reset_by_peer", &self.reset_by_peer)
+            .field("reliable", &self.reliable)
             .field("buffered_amount", &self.buffered_amount)
             .field("buffered_amount_low", &self.buffered_amount_low)
             .field("name", &self.name)
@@ -81,7 +94,12 @@ impl Stream {
             sequence_number: AtomicU16::new(0),
             read_notifier: Notify::new(),
             closed: AtomicBool::new(false),
+            write_closed: AtomicBool::new(false),
+            reset_by_peer: AtomicBool::new(false),
+            reliable: AtomicBool::new(false),
             buffered_amount: AtomicUsize::new(0),
+            buffered_messages: AtomicUsize::new(0),
+            messages_released: Notify::new(),
             buffered_amount_low: AtomicUsize::new(0),
             on_buffered_amount_low: Mutex::new(None),
             name,
@@ -104,7 +122,11 @@ impl Stream {
         &self,
         p: &mut [u8],
     ) -> Result<(usize, PayloadProtocolIdentifier)> {
-        while !self.closed.load(Ordering::SeqCst) {
+        loop {
+            let notified = self.read_notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
             let result = {
                 let mut reassembly_queue = self.reassembly_queue.lock().await;
                 reassembly_queue.read(p)
@@ -118,10 +140,12 @@ impl Stream {
                 }
             }
 
-            self.read_notifier.notified().await;
+            // messages received before the stream closed are read first
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(Error::ErrStreamClosed);
+            }
+            notified.await;
         }
-
-        Err(Error::ErrStreamClosed)
     }
 
     pub(crate) async fn handle_data(&self, pd: ChunkPayloadData) {
@@ -163,15 +187,32 @@ impl Stream {
         self.write_sctp(p, PayloadProtocolIdentifier::Binary).await
     }
 
-    /// write_sctp writes len(p) bytes from p to the DTLS connection
+    /// write_sctp writes len(p) bytes from p to the DTLS connection. The message is queued
+    /// whole or not at all, so a successful write always returns len(p).
     pub(crate) async fn write_sctp(
         &self,
         p: &Bytes,
         ppi: PayloadProtocolIdentifier,
+    ) -> Result<usize> {
+        self.write_sctp_with_deadline(p, ppi, None).await
+    }
+
+    /// write_sctp_with_deadline writes like write_sctp, and gives the message up once
+    /// `abandon_at` has passed without the peer acknowledging it, if the association
+    /// negotiated partial reliability. The peer then skips it, so the messages written after
+    /// it on an ordered stream are still delivered.
+    pub(crate) async fn write_sctp_with_deadline(
+        &self,
+        p: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        abandon_at: Option<SystemTime>,
     ) -> Result<usize> {
         if p.len() > self.max_message_size.load(Ordering::SeqCst) as usize {
             return Err(Error::ErrOutboundPacketTooLarge);
         }
+        if self.write_closed.load(Ordering::SeqCst) {
+            return Err(Error::ErrStreamClosed);
+        }
 
         let state: AssociationState = self.state.load(Ordering::SeqCst).into();
         match state {
@@ -182,20 +223,26 @@ impl Stream {
             _ => {}
         };
 
-        let chunks = self.packetize(p, ppi);
+        let chunks = self.packetize(p, ppi, abandon_at);
         self.send_payload_data(chunks).await?;
 
         Ok(p.len())
     }
 
-    fn packetize(&self, raw: &Bytes, ppi: PayloadProtocolIdentifier) -> Vec<ChunkPayloadData> {
+    fn packetize(
+        &self,
+        raw: &Bytes,
+        ppi: PayloadProtocolIdentifier,
+        abandon_at: Option<SystemTime>,
+    ) -> Vec<ChunkPayloadData> {
         let mut i = 0;
         let mut remaining = raw.len();
 
         // From draft-ietf-rtcweb-data-protocol-09, section 6:
         //   All Data Channel Establishment Protocol messages MUST be sent using
         //   ordered delivery and reliable transmission.
-        let unordered = ppi != PayloadProtocolIdentifier::Dcep;
+        let unordered =
+            ppi != PayloadProtocolIdentifier::Dcep && !self.reliable.load(Ordering::SeqCst);
 
         let mut chunks = vec![];
 
@@ -217,6 +264,7 @@ impl Stream {
                 immediate_sack: false,
                 payload_type: ppi,
                 stream_sequence_number: self.sequence_number.load(Ordering::SeqCst),
+                abandon_at,
                 abandoned: head_abandoned.clone(), // all fragmented chunks use the same abandoned
                 all_inflight: head_all_inflight.clone(), // all fragmented chunks use the same all_inflight
                 ..Default::default()
@@ -236,6 +284,9 @@ impl Stream {
             self.sequence_number.fetch_add(1, Ordering::SeqCst);
         }
 
+        if !chunks.is_empty() {
+            self.buffered_messages.fetch_add(1, Ordering::SeqCst);
+        }
         let old_value = self.buffered_amount.fetch_add(raw.len(), Ordering::SeqCst);
         log::trace!("[{}] bufferedAmount = {}", self.name, old_value + raw.len());
 
@@ -246,9 +297,7 @@ impl Stream {
     /// Future calls to write are not permitted after calling Close.
     pub(crate) async fn close(&self) -> Result<()> {
         if !self.closed.load(Ordering::SeqCst) {
-            // Reset the outgoing stream
-            // https://tools.ietf.org/html/rfc6525
-            self.send_reset_request(self.stream_identifier).await?;
+            self.shutdown_write().await?;
         }
         self.closed.store(true, Ordering::SeqCst);
         self.read_notifier.notify_waiters(); // broadcast regardless
@@ -256,6 +305,17 @@ impl Stream {
         Ok(())
     }
 
+    /// shutdown_write closes the write-direction of the stream only, so that the peer's
+    /// messages can still be read. Messages written before are sent ahead of the reset.
+    pub(crate) async fn shutdown_write(&self) -> Result<()> {
+        if !self.write_closed.swap(true, Ordering::SeqCst) {
+            // Reset the outgoing stream
+            // https://tools.ietf.org/html/rfc6525
+            self.send_reset_request(self.stream_identifier).await?;
+        }
+        Ok(())
+    }
+
     /// set_buffered_amount_low_threshold is used to update the threshold.
     /// See buffered_amount_low_threshold().
     pub(crate) fn set_buffered_amount_low_threshold(&self, th: usize) {
@@ -269,13 +329,41 @@ impl Stream {
         *on_buffered_amount_low = Some(f);
     }
 
+    /// wait_buffered_messages_below waits until fewer than `limit` written messages are
+    /// waiting to be acknowledged by the peer.
+    pub(crate) async fn wait_buffered_messages_below(&self, limit: usize) {
+        loop {
+            let released = self.messages_released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+            if self.buffered_messages.load(Ordering::SeqCst) < limit {
+                return;
+            }
+            released.await;
+        }
+    }
+
     /// This method is called by association's read_loop (go-)routine to notify this stream
-    /// of the specified amount of outgoing data has been delivered to the peer.
-    pub(crate) async fn on_buffer_released(&self, n_bytes_released: i64) {
+    /// of the specified amount of outgoing data, making up `n_messages_released` whole
+    /// messages, has been delivered to the peer.
+    pub(crate) async fn on_buffer_released(
+        &self,
+        n_bytes_released: i64,
+        n_messages_released: usize,
+    ) {
         if n_bytes_released <= 0 {
             return;
         }
 
+        if n_messages_released > 0 {
+            let _ = self.buffered_messages.fetch_update(
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                |buffered| Some(buffered.saturating_sub(n_messages_released)),
+            );
+            self.messages_released.notify_waiters();
+        }
+
         let from_amount = self.buffered_amount.load(Ordering::SeqCst);
         let new_amount = if from_amount < n_bytes_released as usize {
             self.buffered_amount.store(0, Ordering::SeqCst);
@@ -13,6 +13,25 @@ pub(crate) const RTO_BASE: u64 = 8;
 pub(crate) const MAX_INIT_RETRANS: usize = 8;
 pub(crate) const NO_MAX_RETRANS: usize = 0;
 
+/// RtoConfig holds the RTO.Initial, RTO.Min and RTO.Max protocol parameters in msec.
+/// See RFC 4960 sec 15.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct RtoConfig {
+    pub(crate) initial: u64,
+    pub(crate) min: u64,
+    pub(crate) max: u64,
+}
+
+impl Default for RtoConfig {
+    fn default() -> Self {
+        RtoConfig {
+            initial: RTO_INITIAL,
+            min: RTO_MIN,
+            max: RTO_MAX,
+        }
+    }
+}
+
 /// rtoManager manages Rtx timeout values.
 /// This is an implementation of RFC 4960 sec 6.3.1.
 #[derive(Default, Debug)]
@@ -21,13 +40,15 @@ pub(crate) struct RtoManager {
     pub(crate) rttvar: f64,
     pub(crate) rto: u64,
     pub(crate) no_update: bool,
+    pub(crate) config: RtoConfig,
 }
 
 impl RtoManager {
     /// newRTOManager creates a new rtoManager.
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(config: RtoConfig) -> Self {
         RtoManager {
-            rto: RTO_INITIAL,
+            rto: config.initial,
+            config,
             ..Default::default()
         }
     }
@@ -51,8 +72,8 @@ impl RtoManager {
         }
 
         self.rto = std::cmp::min(
-            std::cmp::max(self.srtt + (4.0 * self.rttvar) as u64, RTO_MIN),
-            RTO_MAX,
+            std::cmp::max(self.srtt + (4.0 * self.rttvar) as u64, self.config.min),
+            self.config.max,
         );
 
         self.srtt
@@ -64,16 +85,16 @@ impl RtoManager {
     }
 }
 
-pub(crate) fn calculate_next_timeout(rto: u64, n_rtos: usize) -> u64 {
+pub(crate) fn calculate_next_timeout(rto: u64, n_rtos: usize, rto_max: u64) -> u64 {
     // RFC 4096 sec 6.3.3.  Handle T3-rtx Expiration
     //   E2)  For the destination address for which the timer expires, set RTO
     //        <- RTO * 2 ("back off the timer").  The maximum value discussed
     //        in rule C7 above (RTO.max) may be used to provide an upper bound
     //        to this doubling operation.
     if n_rtos < 31 {
-        std::cmp::min(rto << n_rtos, RTO_MAX)
+        std::cmp::min(rto << n_rtos, rto_max)
     } else {
-        RTO_MAX
+        rto_max
     }
 }
 
@@ -92,6 +113,7 @@ pub(crate) struct RtxTimer<T: 'static + RtxTimerObserver + Send> {
     pub(crate) timeout_observer: Weak<Mutex<T>>,
     pub(crate) id: RtxTimerId,
     pub(crate) max_retrans: usize,
+    pub(crate) rto_max: u64,
     pub(crate) close_tx: Arc<Mutex<Option<mpsc::Sender<()>>>>,
 }
 
@@ -103,11 +125,13 @@ impl<T: 'static + RtxTimerObserver + Send> RtxTimer<T> {
         timeout_observer: Weak<Mutex<T>>,
         id: RtxTimerId,
         max_retrans: usize,
+        rto_max: u64,
     ) -> Self {
         RtxTimer {
             timeout_observer,
             id,
             max_retrans,
+            rto_max,
             close_tx: Arc::new(Mutex::new(None)),
         }
     }
@@ -133,6 +157,7 @@ impl<T: 'static + RtxTimerObserver + Send> RtxTimer<T> {
 
         let id = self.id;
         let max_retrans = self.max_retrans;
+        let rto_max = self.rto_max;
         let close_tx = Arc::clone(&self.close_tx);
         let timeout_observer = self.timeout_observer.clone();
 
@@ -140,7 +165,7 @@ impl<T: 'static + RtxTimerObserver + Send> RtxTimer<T> {
             let mut n_rtos = 0;
 
             loop {
-                let interval = calculate_next_timeout(rto, n_rtos);
+                let interval = calculate_next_timeout(rto, n_rtos, rto_max);
                 let timer = tokio::time::sleep(Duration::from_millis(interval));
                 tokio::pin!(timer);
 
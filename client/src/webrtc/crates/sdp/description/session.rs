@@ -1,10 +1,10 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fmt, io};
 use url::Url;
 
 use crate::webrtc::sdp::error::{Error, Result};
 use crate::webrtc::sdp::lexer::*;
 use crate::webrtc::sdp::util::*;
+use crate::webrtc::util::rng::with_rng;
 
 use super::common::*;
 use super::media::*;
@@ -226,10 +226,7 @@ impl SessionDescription {
             origin: Origin {
                 username: "-".to_string(),
                 session_id: new_session_id(),
-                session_version: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_else(|_| Duration::from_secs(0))
-                    .subsec_nanos() as u64,
+                session_version: with_rng(|rng| rng.next_u32()) as u64,
                 network_type: "IN".to_string(),
                 address_type: "IP4".to_string(),
                 unicast_address: "0.0.0.0".to_string(),
@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::webrtc::util::rng::with_rng;
+
 /// ConnectionRole indicates which of the end points should initiate the connection establishment
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub(crate) enum ConnectionRole {
@@ -72,7 +74,7 @@ impl From<&str> for ConnectionRole {
 /// being cryptographically random.
 pub(crate) fn new_session_id() -> u64 {
     let c = u64::MAX ^ (1u64 << 63);
-    rand::random::<u64>() & c
+    with_rng(|rng| rng.next_u64()) & c
 }
 
 // Codec represents a codec
@@ -1,7 +1,7 @@
 use crate::webrtc::stun::error::*;
 use crate::webrtc::stun::message::*;
 
-use rand::Rng;
+use crate::webrtc::util::rng::with_rng;
 use tokio::time::Instant;
 
 #[derive(Debug, Clone)]
@@ -23,7 +23,7 @@ impl TransactionId {
     /// as source.
     pub(crate) fn new() -> Self {
         let mut b = TransactionId([0u8; TRANSACTION_ID_SIZE]);
-        rand::thread_rng().fill(&mut b.0);
+        with_rng(|rng| rng.fill_bytes(&mut b.0));
         b
     }
 }
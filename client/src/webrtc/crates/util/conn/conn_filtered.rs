@@ -0,0 +1,66 @@
+use super::*;
+
+/// SendFilterFn returns whether a datagram is sent, or silently dropped.
+pub(crate) type SendFilterFn = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// FilteredConn drops the datagrams its filter rejects, reporting them as sent, as if they
+/// were lost on the way.
+pub(crate) struct FilteredConn {
+    conn: Arc<dyn Conn + Send + Sync>,
+    filter: SendFilterFn,
+}
+
+impl FilteredConn {
+    pub(crate) fn new(conn: Arc<dyn Conn + Send + Sync>, filter: SendFilterFn) -> Self {
+        FilteredConn { conn, filter }
+    }
+}
+
+#[async_trait]
+impl Conn for FilteredConn {
+    async fn connect(&self, addr: SocketAddr) -> Result<()> {
+        self.conn.connect(addr).await
+    }
+
+    async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.conn.recv(buf).await
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
+        self.conn.recv_from(buf).await
+    }
+
+    async fn recv_from_batch(
+        &self,
+        bufs: &mut [Vec<u8>],
+        received: &mut [(usize, SocketAddr)],
+    ) -> Result<usize> {
+        self.conn.recv_from_batch(bufs, received).await
+    }
+
+    async fn send(&self, buf: &[u8]) -> Result<usize> {
+        if !(self.filter)(buf) {
+            return Ok(buf.len());
+        }
+        self.conn.send(buf).await
+    }
+
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> Result<usize> {
+        if !(self.filter)(buf) {
+            return Ok(buf.len());
+        }
+        self.conn.send_to(buf, target).await
+    }
+
+    async fn local_addr(&self) -> Result<SocketAddr> {
+        self.conn.local_addr().await
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        self.conn.remote_addr().await
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.conn.close().await
+    }
+}
@@ -16,6 +16,28 @@ impl Conn for UdpSocket {
         Ok(self.recv_from(buf).await?)
     }
 
+    /// recv_from_batch reads the datagrams already queued on the socket with a single
+    /// recvmmsg call, once the socket is readable.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    async fn recv_from_batch(
+        &self,
+        bufs: &mut [Vec<u8>],
+        received: &mut [(usize, SocketAddr)],
+    ) -> Result<usize> {
+        use std::os::unix::io::AsRawFd;
+        use tokio::io::Interest;
+
+        let fd = self.as_raw_fd();
+        loop {
+            self.readable().await?;
+            match self.try_io(Interest::READABLE, || recvmmsg(fd, bufs, received)) {
+                Ok(n) => return Ok(n),
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
     async fn send(&self, buf: &[u8]) -> Result<usize> {
         Ok(self.send(buf).await?)
     }
@@ -36,3 +58,44 @@ impl Conn for UdpSocket {
         Ok(())
     }
 }
+
+// recvmmsg reads up to as many datagrams as there are buffers without blocking, failing
+// with WouldBlock if none is queued
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn recvmmsg(
+    fd: std::os::unix::io::RawFd,
+    bufs: &mut [Vec<u8>],
+    received: &mut [(usize, SocketAddr)],
+) -> std::io::Result<usize> {
+    use nix::sys::{
+        socket::{self, MsgFlags, RecvMmsgData, SockAddr},
+        uio::IoVec,
+    };
+
+    let mut data: Vec<_> = bufs
+        .iter_mut()
+        .take(received.len())
+        .map(|buf| RecvMmsgData {
+            iov: [IoVec::from_mut_slice(&mut buf[..])],
+            cmsg_buffer: None,
+        })
+        .collect();
+    let messages = socket::recvmmsg(fd, &mut data, MsgFlags::MSG_DONTWAIT, None)
+        .map_err(std::io::Error::from)?;
+
+    let n = messages.len();
+    for (message, received) in messages.into_iter().zip(received.iter_mut()) {
+        // a UDP socket only receives from IP addresses
+        let addr = match message.address {
+            Some(SockAddr::Inet(addr)) => addr.to_std(),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "received a datagram without an IP source address",
+                ))
+            }
+        };
+        *received = (message.bytes, addr);
+    }
+    Ok(n)
+}
@@ -1,5 +1,6 @@
 pub(crate) mod conn_bridge;
 pub(crate) mod conn_disconnected_packet;
+pub(crate) mod conn_filtered;
 pub(crate) mod conn_pipe;
 pub(crate) mod conn_udp;
 pub(crate) mod conn_udp_listener;
@@ -15,6 +16,23 @@ pub(crate) trait Conn {
     async fn connect(&self, addr: SocketAddr) -> Result<()>;
     async fn recv(&self, buf: &mut [u8]) -> Result<usize>;
     async fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)>;
+    /// recv_from_batch reads one or more datagrams, each into the next of `bufs`, and
+    /// records the length and source of each in `received`. It returns how many were read,
+    /// at most the length of the shorter of the two slices. Connections which can't read
+    /// several at once read a single datagram, with recv_from.
+    async fn recv_from_batch(
+        &self,
+        bufs: &mut [Vec<u8>],
+        received: &mut [(usize, SocketAddr)],
+    ) -> Result<usize> {
+        match (bufs.first_mut(), received.first_mut()) {
+            (Some(buf), Some(first)) => {
+                *first = self.recv_from(buf).await?;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
     async fn send(&self, buf: &[u8]) -> Result<usize>;
     async fn send_to(&self, buf: &[u8], target: SocketAddr) -> Result<usize>;
     async fn local_addr(&self) -> Result<SocketAddr>;
@@ -1,7 +1,20 @@
 use std::fmt;
 
-// FixedBigInt is the fix-sized multi-word integer.
-pub(crate) struct FixedBigInt {
+/// A fixed-size multi-word integer, used as the bitset of a sliding window.
+///
+/// Bit 0 is the least significant one, and bits shifted past the size are discarded.
+///
+/// ```
+/// use webrtc_unreliable_client::FixedBigInt;
+///
+/// let mut window = FixedBigInt::new(128);
+/// window.set_bit(0);
+/// window.lsh(70);
+/// assert_eq!(window.bit(70), 1);
+/// window.lsh(60);
+/// assert_eq!(window.bit(0), 0); // shifted out
+/// ```
+pub struct FixedBigInt {
     bits: Vec<u64>,
     n: usize,
     msb_mask: u64,
@@ -19,7 +32,8 @@ impl fmt::Display for FixedBigInt {
 }
 
 impl FixedBigInt {
-    pub(crate) fn new(n: usize) -> Self {
+    /// Creates an integer of `n` bits, all zero
+    pub fn new(n: usize) -> Self {
         let mut chunk_size = (n + 63) / 64;
         if chunk_size == 0 {
             chunk_size = 1;
@@ -36,8 +50,8 @@ impl FixedBigInt {
         }
     }
 
-    // lsh is the left shift operation.
-    pub(crate) fn lsh(&mut self, n: usize) {
+    /// Shifts the integer left by `n` bits
+    pub fn lsh(&mut self, n: usize) {
         if n == 0 {
             return;
         }
@@ -71,8 +85,8 @@ impl FixedBigInt {
         self.bits[last] &= self.msb_mask;
     }
 
-    // bit returns i-th bit of the fixedBigInt.
-    pub(crate) fn bit(&self, i: usize) -> usize {
+    /// Returns the `i`-th bit, which is 0 beyond the size
+    pub fn bit(&self, i: usize) -> usize {
         if i >= self.n {
             return 0;
         }
@@ -85,8 +99,8 @@ impl FixedBigInt {
         }
     }
 
-    // set_bit sets i-th bit to 1.
-    pub(crate) fn set_bit(&mut self, i: usize) {
+    /// Sets the `i`-th bit to 1. Does nothing beyond the size
+    pub fn set_bit(&mut self, i: usize) {
         if i >= self.n {
             return;
         }
@@ -13,6 +13,7 @@ const MAX_DNS_SUFFIX_STRING_LENGTH: usize = 256;
 
 pub(crate) const IP_ADAPTER_IPV4_ENABLED: DWORD = 0x0080;
 pub(crate) const IP_ADAPTER_IPV6_ENABLED: DWORD = 0x0100;
+const IF_TYPE_SOFTWARE_LOOPBACK: DWORD = 24;
 
 use std::io;
 use std::mem;
@@ -312,6 +313,12 @@ unsafe fn map_adapter_addresses(mut adapter_addr: *const IpAdapterAddresses) ->
 
     while !adapter_addr.is_null() {
         let curr_adapter_addr = &*adapter_addr;
+        let up = matches!(
+            curr_adapter_addr.all.oper_status,
+            IfOperStatus::IfOperStatusUp
+        );
+        let loopback = curr_adapter_addr.all.if_type == IF_TYPE_SOFTWARE_LOOPBACK;
+        let name = wide_to_string(curr_adapter_addr.all.friendly_name);
 
         let mut unicast_addr = curr_adapter_addr.all.first_unicast_address;
         while !unicast_addr.is_null() {
@@ -322,18 +329,22 @@ unsafe fn map_adapter_addresses(mut adapter_addr: *const IpAdapterAddresses) ->
             if curr_unicast_addr.dad_state != IpDadState::IpDadStateDeprecated {
                 if is_ipv4_enabled(&curr_unicast_addr) {
                     adapter_addresses.push(Interface {
-                        name: "".to_string(),
+                        name: name.clone(),
                         addr: Some(SocketAddr::V4(v4_socket_from_adapter(&curr_unicast_addr))),
                         mask: None,
+                        up,
+                        loopback,
                     });
                 } else if is_ipv6_enabled(&curr_unicast_addr) {
                     let mut v6_sock = v6_socket_from_adapter(&curr_unicast_addr);
                     // Make sure the scope id is set for ALL interfaces, not just link-local
                     v6_sock.set_scope_id(curr_adapter_addr.xp.ipv6_if_index);
                     adapter_addresses.push(Interface {
-                        name: "".to_string(),
+                        name: name.clone(),
                         addr: Some(SocketAddr::V6(v6_sock)),
                         mask: None,
+                        up,
+                        loopback,
                     });
                 }
             }
@@ -347,6 +358,18 @@ unsafe fn map_adapter_addresses(mut adapter_addr: *const IpAdapterAddresses) ->
     adapter_addresses
 }
 
+// wide_to_string converts a NUL-terminated UTF-16 string, e.g. an adapter's friendly name
+unsafe fn wide_to_string(wide: PWCHAR) -> String {
+    if wide.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while *wide.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(wide, len))
+}
+
 /// Query the local system for all interface addresses.
 pub(crate) fn ifaces() -> Result<Vec<Interface>, ::std::io::Error> {
     let mut adapters_list = Vec::with_capacity(PREALLOC_ADAPTERS_LEN);
@@ -6,4 +6,6 @@ pub(crate) struct Interface {
     pub(crate) name: String,
     pub(crate) addr: Option<::std::net::SocketAddr>,
     pub(crate) mask: Option<::std::net::SocketAddr>,
+    pub(crate) up: bool,
+    pub(crate) loopback: bool,
 }
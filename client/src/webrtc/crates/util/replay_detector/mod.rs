@@ -1,14 +1,37 @@
 use super::fixed_big_int::*;
 
-// ReplayDetector is the interface of sequence replay detector.
-pub(crate) trait ReplayDetector {
-    // Check returns true if given sequence number is not replayed.
-    // Call accept() to mark the packet is received properly.
+/// Detects replayed sequence numbers.
+///
+/// Each packet is checked first, then accepted once it has been authenticated, so a
+/// forged packet can't advance the window:
+///
+/// ```
+/// use webrtc_unreliable_client::{ReplayDetector, SlidingWindowDetector};
+///
+/// let mut detector = SlidingWindowDetector::new(64, u64::MAX);
+/// for seq in [1, 3, 2, 3] {
+///     if !detector.check(seq) {
+///         println!("dropping replayed packet {}", seq);
+///         continue;
+///     }
+///     // authenticate the packet here, then
+///     detector.accept();
+/// }
+/// assert!(!detector.check(2));
+/// assert!(detector.check(4));
+/// ```
+pub trait ReplayDetector {
+    /// Returns true if `seq` hasn't been accepted yet and is within the window. Call
+    /// [`accept`](Self::accept) to mark the packet as received properly.
     fn check(&mut self, seq: u64) -> bool;
+    /// Marks the sequence number of the last successful [`check`](Self::check) as
+    /// received. Does nothing if that check failed
     fn accept(&mut self);
 }
 
-pub(crate) struct SlidingWindowDetector {
+/// A [`ReplayDetector`] for monotonically increasing sequence numbers which don't wrap,
+/// up to the full 64 bits. It is what DTLS uses for its replay protection
+pub struct SlidingWindowDetector {
     accepted: bool,
     seq: u64,
     latest_seq: u64,
@@ -18,11 +41,9 @@ pub(crate) struct SlidingWindowDetector {
 }
 
 impl SlidingWindowDetector {
-    // New creates ReplayDetector.
-    // Created ReplayDetector doesn't allow wrapping.
-    // It can handle monotonically increasing sequence number up to
-    // full 64bit number. It is suitable for DTLS replay protection.
-    pub(crate) fn new(window_size: usize, max_seq: u64) -> Self {
+    /// Creates a detector remembering the last `window_size` sequence numbers. Sequence
+    /// numbers above `max_seq` are always rejected
+    pub fn new(window_size: usize, max_seq: u64) -> Self {
         SlidingWindowDetector {
             accepted: false,
             seq: 0,
@@ -73,7 +94,20 @@ impl ReplayDetector for SlidingWindowDetector {
     }
 }
 
-pub(crate) struct WrappedSlidingWindowDetector {
+/// A [`ReplayDetector`] for sequence numbers which wrap around to 0 after `max_seq`, like
+/// the 16 bit ones of RTP
+///
+/// ```
+/// use webrtc_unreliable_client::{ReplayDetector, WrappedSlidingWindowDetector};
+///
+/// let mut detector = WrappedSlidingWindowDetector::new(64, u16::MAX as u64);
+/// for seq in [65534, 65535, 0, 1] {
+///     assert!(detector.check(seq));
+///     detector.accept();
+/// }
+/// assert!(!detector.check(65535));
+/// ```
+pub struct WrappedSlidingWindowDetector {
     accepted: bool,
     seq: u64,
     latest_seq: u64,
@@ -83,6 +117,22 @@ pub(crate) struct WrappedSlidingWindowDetector {
     init: bool,
 }
 
+impl WrappedSlidingWindowDetector {
+    /// Creates a detector remembering the last `window_size` sequence numbers, which wrap
+    /// after `max_seq`
+    pub fn new(window_size: usize, max_seq: u64) -> Self {
+        WrappedSlidingWindowDetector {
+            accepted: false,
+            seq: 0,
+            latest_seq: 0,
+            max_seq,
+            window_size,
+            mask: FixedBigInt::new(window_size),
+            init: false,
+        }
+    }
+}
+
 impl ReplayDetector for WrappedSlidingWindowDetector {
     fn check(&mut self, seq: u64) -> bool {
         self.accepted = false;
@@ -147,8 +197,9 @@ impl ReplayDetector for WrappedSlidingWindowDetector {
     }
 }
 
+/// A [`ReplayDetector`] which accepts every sequence number
 #[derive(Default)]
-pub(crate) struct NoOpReplayDetector;
+pub struct NoOpReplayDetector;
 
 impl ReplayDetector for NoOpReplayDetector {
     fn check(&mut self, _: u64) -> bool {
@@ -0,0 +1,26 @@
+use rand::RngCore;
+#[cfg(feature = "deterministic-rng")]
+use rand::{rngs::StdRng, SeedableRng};
+#[cfg(feature = "deterministic-rng")]
+use std::sync::Mutex;
+
+#[cfg(feature = "deterministic-rng")]
+static SEEDED: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// with_rng runs `f` with the RNG behind STUN transaction IDs, ICE credentials, candidate
+/// ids, ICE tie breakers and SDP session ids and versions. It's the thread-local CSPRNG,
+/// unless a seed has been set with `seed`.
+pub(crate) fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    #[cfg(feature = "deterministic-rng")]
+    if let Some(rng) = SEEDED.lock().unwrap().as_mut() {
+        return f(rng);
+    }
+    f(&mut rand::thread_rng())
+}
+
+/// seed replaces the RNG of `with_rng` for the whole process with one seeded with `seed`.
+/// Only for tests: the values it yields are predictable.
+#[cfg(feature = "deterministic-rng")]
+pub(crate) fn seed(seed: u64) {
+    *SEEDED.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+}
@@ -0,0 +1,11 @@
+/// DataChannelConfig can be used to configure properties of the underlying
+/// channel such as data reliability.
+#[derive(Default, Debug, Clone, Copy)]
+pub(crate) struct RTCDataChannelInit {
+    /// id overrides the default selection of ID for this channel.
+    pub(crate) id: u16,
+
+    /// reliable makes the channel retransmit messages until they are delivered, in
+    /// order, instead of sending them once, unordered.
+    pub(crate) reliable: bool,
+}
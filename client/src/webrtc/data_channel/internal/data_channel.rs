@@ -8,7 +8,9 @@ use crate::webrtc::util::marshal::*;
 
 use bytes::{Buf, Bytes};
 use derive_builder::Builder;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 /// Config is used to configure the data channel.
 #[derive(Eq, PartialEq, Default, Clone, Debug, Builder)]
@@ -17,6 +19,10 @@ pub(crate) struct Config {
     pub(crate) label: String,
     #[builder(default)]
     pub(crate) protocol: String,
+    /// Reliable channels retransmit messages until they are delivered, in order. Others
+    /// send every message once and deliver them unordered
+    #[builder(default)]
+    pub(crate) reliable: bool,
 }
 
 /// DataChannel represents a data channel
@@ -46,12 +52,21 @@ impl DataChannel {
 
     /// Client opens a data channel over an SCTP stream
     async fn client(stream: Arc<Stream>, config: Config) -> Result<Self> {
+        let channel_type = if config.reliable {
+            ChannelType::Reliable
+        } else {
+            ChannelType::PartialReliableRexmitUnordered
+        };
         let msg = Message::DataChannelOpen(DataChannelOpen {
+            channel_type,
+            priority: 0,
+            reliability_parameter: 0,
             label: config.label.bytes().collect(),
             protocol: config.protocol.bytes().collect(),
         })
         .marshal()?;
 
+        stream.reliable.store(config.reliable, Ordering::SeqCst);
         stream
             .write_sctp(&msg, PayloadProtocolIdentifier::Dcep)
             .await?;
@@ -59,13 +74,12 @@ impl DataChannel {
         Ok(DataChannel::new(stream))
     }
 
-    /// Read reads a packet of len(p) bytes as binary data
-    pub(crate) async fn read(&self, buf: &mut [u8]) -> Result<usize> {
-        self.read_data_channel(buf).await.map(|(n, _)| n)
-    }
-
-    /// ReadDataChannel reads a packet of len(p) bytes
-    pub(crate) async fn read_data_channel(&self, buf: &mut [u8]) -> Result<(usize, bool)> {
+    /// ReadPayload reads a packet of len(p) bytes along with its Payload Protocol
+    /// Identifier, handling DCEP messages
+    pub(crate) async fn read_payload(
+        &self,
+        buf: &mut [u8],
+    ) -> Result<(usize, PayloadProtocolIdentifier)> {
         loop {
             //TODO: add handling of cancel read_data_channel
             let (mut n, ppi) = match self.stream.read_sctp(buf).await {
@@ -79,23 +93,16 @@ impl DataChannel {
                 }
             };
 
-            let mut is_string = false;
-            match ppi {
-                PayloadProtocolIdentifier::Dcep => {
-                    let mut data = &buf[..n];
-                    match self.handle_dcep(&mut data).await {
-                        Ok(()) => {}
-                        Err(err) => {
-                            log::error!("Failed to handle DCEP: {:?}", err);
-                        }
+            if ppi == PayloadProtocolIdentifier::Dcep {
+                let mut data = &buf[..n];
+                match self.handle_dcep(&mut data).await {
+                    Ok(()) => {}
+                    Err(err) => {
+                        log::error!("Failed to handle DCEP: {:?}", err);
                     }
-                    continue;
-                }
-                PayloadProtocolIdentifier::String | PayloadProtocolIdentifier::StringEmpty => {
-                    is_string = true;
                 }
-                _ => {}
-            };
+                continue;
+            }
 
             match ppi {
                 PayloadProtocolIdentifier::StringEmpty | PayloadProtocolIdentifier::BinaryEmpty => {
@@ -104,7 +111,7 @@ impl DataChannel {
                 _ => {}
             };
 
-            return Ok((n, is_string));
+            return Ok((n, ppi));
         }
     }
 
@@ -128,13 +135,16 @@ impl DataChannel {
         Ok(())
     }
 
-    /// Write writes len(p) bytes from p as binary data
-    pub(crate) async fn write(&self, data: &Bytes) -> Result<usize> {
-        self.write_data_channel(data, false).await
-    }
-
-    /// WriteDataChannel writes len(p) bytes from p
-    pub(crate) async fn write_data_channel(&self, data: &Bytes, is_string: bool) -> Result<usize> {
+    /// WriteDataChannel writes len(p) bytes from p, as text if is_string and as binary data
+    /// otherwise. Like Stream::write_sctp, it writes the whole message or fails, and never
+    /// writes part of it. With abandon_at, the message is given up once it has passed, as
+    /// Stream::write_sctp_with_deadline does.
+    pub(crate) async fn write_data_channel(
+        &self,
+        data: &Bytes,
+        is_string: bool,
+        abandon_at: Option<SystemTime>,
+    ) -> Result<usize> {
         let data_len = data.len();
 
         // https://tools.ietf.org/html/draft-ietf-rtcweb-data-channel-12#section-6.6
@@ -154,14 +164,56 @@ impl DataChannel {
         if data_len == 0 {
             let _ = self
                 .stream
-                .write_sctp(&Bytes::from_static(&[0]), ppi)
+                .write_sctp_with_deadline(&Bytes::from_static(&[0]), ppi, abandon_at)
                 .await?;
             Ok(0)
         } else {
-            Ok(self.stream.write_sctp(data, ppi).await?)
+            Ok(self
+                .stream
+                .write_sctp_with_deadline(data, ppi, abandon_at)
+                .await?)
         }
     }
 
+    /// Close closes the DataChannel and the underlying SCTP stream.
+    pub(crate) async fn close(&self) -> Result<()> {
+        // https://tools.ietf.org/html/draft-ietf-rtcweb-data-channel-13#section-6.7
+        // Closing of a data channel MUST be signaled by resetting the
+        // corresponding outgoing streams [RFC6525].  This means that if one
+        // side decides to close the data channel, it resets the corresponding
+        // outgoing stream.
+        Ok(self.stream.close().await?)
+    }
+
+    /// CloseWrite resets the outgoing SCTP stream of the DataChannel, once the messages
+    /// written before have been sent, while the peer's messages can still be read.
+    pub(crate) async fn close_write(&self) -> Result<()> {
+        Ok(self.stream.shutdown_write().await?)
+    }
+
+    /// ResetByPeer returns whether the peer has reset its outgoing stream, so that nothing
+    /// more is read from the DataChannel.
+    pub(crate) fn reset_by_peer(&self) -> bool {
+        self.stream.reset_by_peer.load(Ordering::SeqCst)
+    }
+
+    /// StreamIdentifier returns the identifier of the SCTP stream carrying the channel.
+    pub(crate) fn stream_identifier(&self) -> u16 {
+        self.stream.stream_identifier
+    }
+
+    /// BufferedAmount returns the number of bytes of outgoing data which have been
+    /// written but not yet acknowledged by the peer.
+    pub(crate) fn buffered_amount(&self) -> usize {
+        self.stream.buffered_amount.load(Ordering::SeqCst)
+    }
+
+    /// wait_buffered_messages_below waits until fewer than `limit` written messages are
+    /// waiting to be acknowledged by the peer.
+    pub(crate) async fn wait_buffered_messages_below(&self, limit: usize) {
+        self.stream.wait_buffered_messages_below(limit).await
+    }
+
     /// SetBufferedAmountLowThreshold is used to update the threshold.
     /// See BufferedAmountLowThreshold().
     pub(crate) fn set_buffered_amount_low_threshold(&self, threshold: usize) {
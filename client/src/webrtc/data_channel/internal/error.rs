@@ -12,6 +12,8 @@ pub(crate) enum Error {
     UnexpectedEndOfBuffer { expected: usize, actual: usize },
     #[error("Unknown MessageType {0}")]
     InvalidMessageType(u8),
+    #[error("Unknown ChannelType {0}")]
+    InvalidChannelType(u8),
 
     #[error("{0}")]
     Util(#[from] crate::webrtc::util::Error),
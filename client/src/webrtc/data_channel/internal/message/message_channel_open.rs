@@ -5,6 +5,76 @@ type Result<T> = std::result::Result<T, crate::webrtc::util::Error>;
 
 const CHANNEL_OPEN_HEADER_LEN: usize = 11;
 
+/// ChannelType determines the reliability of the WebRTC DataChannel.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub(crate) enum ChannelType {
+    // `Reliable` determines the Data Channel provides a
+    // reliable in-order bi-directional communication.
+    Reliable,
+    // `ReliableUnordered` determines the Data Channel
+    // provides a reliable unordered bi-directional communication.
+    ReliableUnordered,
+    // `PartialReliableRexmit` determines the Data Channel
+    // provides a partially-reliable in-order bi-directional communication.
+    // User messages will not be retransmitted more times than specified in the Reliability Parameter.
+    PartialReliableRexmit,
+    // `PartialReliableRexmitUnordered` determines
+    //  the Data Channel provides a partial reliable unordered bi-directional communication.
+    // User messages will not be retransmitted more times than specified in the Reliability Parameter.
+    PartialReliableRexmitUnordered,
+    // `PartialReliableTimed` determines the Data Channel
+    // provides a partial reliable in-order bi-directional communication.
+    // User messages might not be transmitted or retransmitted after
+    // a specified life-time given in milli- seconds in the Reliability Parameter.
+    // This life-time starts when providing the user message to the protocol stack.
+    PartialReliableTimed,
+    // The Data Channel provides a partial reliable unordered bi-directional
+    // communication.  User messages might not be transmitted or retransmitted
+    // after a specified life-time given in milli- seconds in the Reliability Parameter.
+    // This life-time starts when providing the user message to the protocol stack.
+    PartialReliableTimedUnordered,
+}
+
+const CHANNEL_TYPE_RELIABLE: u8 = 0x00;
+const CHANNEL_TYPE_RELIABLE_UNORDERED: u8 = 0x80;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT: u8 = 0x01;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT_UNORDERED: u8 = 0x81;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED: u8 = 0x02;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED_UNORDERED: u8 = 0x82;
+
+impl ChannelType {
+    fn to_u8(self) -> u8 {
+        match self {
+            ChannelType::Reliable => CHANNEL_TYPE_RELIABLE,
+            ChannelType::ReliableUnordered => CHANNEL_TYPE_RELIABLE_UNORDERED,
+            ChannelType::PartialReliableRexmit => CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT,
+            ChannelType::PartialReliableRexmitUnordered => {
+                CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT_UNORDERED
+            }
+            ChannelType::PartialReliableTimed => CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED,
+            ChannelType::PartialReliableTimedUnordered => {
+                CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED_UNORDERED
+            }
+        }
+    }
+
+    fn from_u8(b: u8) -> std::result::Result<Self, Error> {
+        match b {
+            CHANNEL_TYPE_RELIABLE => Ok(ChannelType::Reliable),
+            CHANNEL_TYPE_RELIABLE_UNORDERED => Ok(ChannelType::ReliableUnordered),
+            CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT => Ok(ChannelType::PartialReliableRexmit),
+            CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT_UNORDERED => {
+                Ok(ChannelType::PartialReliableRexmitUnordered)
+            }
+            CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED => Ok(ChannelType::PartialReliableTimed),
+            CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED_UNORDERED => {
+                Ok(ChannelType::PartialReliableTimedUnordered)
+            }
+            _ => Err(Error::InvalidChannelType(b)),
+        }
+    }
+}
+
 /// The data-part of an data-channel OPEN message without the message type.
 ///
 /// # Memory layout
@@ -30,6 +100,9 @@ const CHANNEL_OPEN_HEADER_LEN: usize = 11;
 /// ```
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub(crate) struct DataChannelOpen {
+    pub(crate) channel_type: ChannelType,
+    pub(crate) priority: u16,
+    pub(crate) reliability_parameter: u32,
     pub(crate) label: Vec<u8>,
     pub(crate) protocol: Vec<u8>,
 }
@@ -54,6 +127,9 @@ impl Marshal for DataChannelOpen {
             .into());
         }
 
+        buf.put_u8(self.channel_type.to_u8());
+        buf.put_u16(self.priority);
+        buf.put_u32(self.reliability_parameter);
         buf.put_u16(self.label.len() as u16);
         buf.put_u16(self.protocol.len() as u16);
         buf.put_slice(self.label.as_slice());
@@ -76,6 +152,9 @@ impl Unmarshal for DataChannelOpen {
             .into());
         }
 
+        let channel_type = ChannelType::from_u8(buf.get_u8())?;
+        let priority = buf.get_u16();
+        let reliability_parameter = buf.get_u32();
         let label_len = buf.get_u16() as usize;
         let protocol_len = buf.get_u16() as usize;
 
@@ -94,6 +173,12 @@ impl Unmarshal for DataChannelOpen {
         buf.copy_to_slice(&mut label[..]);
         buf.copy_to_slice(&mut protocol[..]);
 
-        Ok(Self { label, protocol })
+        Ok(Self {
+            channel_type,
+            priority,
+            reliability_parameter,
+            label,
+            protocol,
+        })
     }
 }
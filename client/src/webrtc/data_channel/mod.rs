@@ -1,3 +1,4 @@
+pub(crate) mod data_channel_init;
 pub(crate) mod data_channel_state;
 pub(crate) mod internal;
 
@@ -9,6 +10,7 @@ use std::sync::{Arc, Weak};
 use crate::webrtc::sctp::stream::OnBufferedAmountLowFn;
 use tokio::sync::Mutex;
 
+use data_channel_init::RTCDataChannelInit;
 use data_channel_state::RTCDataChannelState;
 
 use crate::webrtc::error::{Error, OnErrorHdlrFn, Result};
@@ -24,6 +26,8 @@ pub(crate) type OnOpenHdlrFn =
 pub(crate) struct RTCDataChannel {
     label: String,
     protocol: String,
+    id: u16,
+    reliable: bool,
 
     ready_state: Arc<AtomicU8>, // DataChannelState
     buffered_amount_low_threshold: AtomicUsize,
@@ -47,10 +51,12 @@ pub(crate) struct RTCDataChannel {
 
 impl RTCDataChannel {
     // create the DataChannel object before the networking is set up.
-    pub(crate) fn new(label: &str, protocol: &str) -> Self {
+    pub(crate) fn new(label: &str, protocol: &str, options: RTCDataChannelInit) -> Self {
         RTCDataChannel {
             label: label.to_string(),
             protocol: protocol.to_string(),
+            id: options.id,
+            reliable: options.reliable,
             ready_state: Arc::new(AtomicU8::new(RTCDataChannelState::Connecting as u8)),
             detach_called: Arc::new(AtomicBool::new(false)),
             ..Default::default()
@@ -72,10 +78,15 @@ impl RTCDataChannel {
             let cfg = crate::webrtc::internal::data_channel::Config {
                 label: self.label.clone(),
                 protocol: self.protocol.clone(),
+                reliable: self.reliable,
             };
 
-            let dc = crate::webrtc::internal::data_channel::DataChannel::dial(&association, 0, cfg)
-                .await?;
+            let dc = crate::webrtc::internal::data_channel::DataChannel::dial(
+                &association,
+                self.id,
+                cfg,
+            )
+            .await?;
 
             // buffered_amount_low_threshold and on_buffered_amount_low might be set earlier
             dc.set_buffered_amount_low_threshold(
@@ -1,4 +1,8 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
 
 /// DTLSFingerprint specifies the hash function algorithm and certificate
 /// fingerprint as described in <https://tools.ietf.org/html/rfc4572>.
@@ -13,3 +17,39 @@ pub(crate) struct RTCDtlsFingerprint {
     /// <https://tools.ietf.org/html/rfc4572#section-5>.
     pub(crate) value: String,
 }
+
+impl RTCDtlsFingerprint {
+    /// from_der returns the sha-256 fingerprint of a DER encoded certificate
+    pub(crate) fn from_der(certificate: &[u8]) -> Self {
+        RTCDtlsFingerprint {
+            algorithm: "sha-256".to_owned(),
+            value: to_hex(&Sha256::digest(certificate)),
+        }
+    }
+
+    /// matches tells whether a DER encoded certificate hashes to this fingerprint. A
+    /// fingerprint of an unsupported hash function matches no certificate
+    pub(crate) fn matches(&self, certificate: &[u8]) -> bool {
+        let hashed = match self.algorithm.to_ascii_lowercase().as_str() {
+            "sha-1" => Sha1::digest(certificate).to_vec(),
+            "sha-256" => Sha256::digest(certificate).to_vec(),
+            "sha-384" => Sha384::digest(certificate).to_vec(),
+            "sha-512" => Sha512::digest(certificate).to_vec(),
+            _ => return false,
+        };
+        to_hex(&hashed).eq_ignore_ascii_case(&self.value)
+    }
+}
+
+// to_hex formats a hash as colon separated lowercase hex bytes
+fn to_hex(hashed: &[u8]) -> String {
+    let values: Vec<String> = hashed.iter().map(|x| format! {"{:02x}", x}).collect();
+    values.join(":")
+}
+
+/// Formats the fingerprint like the value of an SDP `a=fingerprint` attribute
+impl fmt::Display for RTCDtlsFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.algorithm, self.value.to_uppercase())
+    }
+}
@@ -3,6 +3,8 @@ use std::pin::Pin;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
 
+use crate::webrtc::api::setting_engine::SettingEngine;
+use crate::webrtc::dtls::cipher_suite::PSK_CIPHER_SUITES;
 use crate::webrtc::dtls::config::ClientAuthType;
 use crate::webrtc::dtls::conn::DTLSConn;
 use crate::webrtc::util::Conn;
@@ -12,7 +14,7 @@ use dtls_role::*;
 
 use crate::webrtc::dtls_transport::dtls_parameters::DTLSParameters;
 use crate::webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
-use crate::webrtc::error::{Error, Result};
+use crate::webrtc::error::{flatten_errs, Error, Result};
 use crate::webrtc::ice_transport::ice_transport_state::RTCIceTransportState;
 use crate::webrtc::ice_transport::RTCIceTransport;
 use crate::webrtc::mux::mux_func::match_dtls;
@@ -42,16 +44,19 @@ pub(crate) struct RTCDtlsTransport {
     pub(crate) state: AtomicU8, //DTLSTransportState,
     pub(crate) on_state_change_handler: Arc<Mutex<Option<OnDTLSTransportStateChangeHdlrFn>>>,
     pub(crate) conn: Mutex<Option<Arc<DTLSConn>>>,
+    setting_engine: Arc<SettingEngine>,
 }
 
 impl RTCDtlsTransport {
     pub(crate) fn new(
         ice_transport: Arc<RTCIceTransport>,
         certificates: Vec<RTCCertificate>,
+        setting_engine: Arc<SettingEngine>,
     ) -> Self {
         RTCDtlsTransport {
             ice_transport,
             certificates,
+            setting_engine,
             state: AtomicU8::new(RTCDtlsTransportState::New as u8),
             ..Default::default()
         }
@@ -71,6 +76,13 @@ impl RTCDtlsTransport {
         }
     }
 
+    /// on_state_change sets a handler that is fired when the DTLS
+    /// connection state changes.
+    pub(crate) async fn on_state_change(&self, f: OnDTLSTransportStateChangeHdlrFn) {
+        let mut on_state_change_handler = self.on_state_change_handler.lock().await;
+        *on_state_change_handler = Some(f);
+    }
+
     /// state returns the current dtls_transport transport state.
     pub(crate) fn state(&self) -> RTCDtlsTransportState {
         self.state.load(Ordering::SeqCst).into()
@@ -98,48 +110,93 @@ impl RTCDtlsTransport {
         };
         self.state_change(RTCDtlsTransportState::Connecting).await;
 
-        Ok((
-            DTLSRole::Client,
-            crate::webrtc::dtls::config::Config {
-                certificates: vec![certificate],
-                srtp_protection_profiles: vec![],
-                client_auth: ClientAuthType::RequireAnyClientCert,
-                insecure_skip_verify: true,
-                ..Default::default()
-            },
-        ))
+        let config = crate::webrtc::dtls::config::Config {
+            certificates: vec![certificate],
+            srtp_protection_profiles: vec![],
+            client_auth: ClientAuthType::RequireAnyClientCert,
+            insecure_skip_verify: true,
+            mtu: self.setting_engine.dtls_mtu.unwrap_or_default(),
+            on_flight_retransmit: self.setting_engine.dtls_flight_retransmit.clone(),
+            ..Default::default()
+        };
+        let config = match &self.setting_engine.dtls_psk {
+            // with a psk and no certificate, only the PSK cipher suites are offered
+            Some((identity, key)) => {
+                let key = key.clone();
+                crate::webrtc::dtls::config::Config {
+                    certificates: vec![],
+                    cipher_suites: PSK_CIPHER_SUITES.to_vec(),
+                    client_auth: ClientAuthType::NoClientCert,
+                    psk: Some(Arc::new(move |_hint: &[u8]| Ok(key.clone()))),
+                    psk_identity_hint: Some(identity.clone()),
+                    ..config
+                }
+            }
+            None => config,
+        };
+        Ok((DTLSRole::Client, config))
     }
 
     /// start DTLS transport negotiation with the parameters of the remote DTLS transport
     pub(crate) async fn start(&self, remote_parameters: DTLSParameters) -> Result<()> {
-        let dtls_conn_result = if let Some(dtls_endpoint) =
-            self.ice_transport.new_endpoint(Box::new(match_dtls)).await
-        {
-            let (_, dtls_config) = self.prepare_transport(remote_parameters).await?;
+        let (_, dtls_config) = self.prepare_transport(remote_parameters).await?;
+
+        let mut retries = self.setting_engine.handshake_retries;
+        let dtls_conn = loop {
+            let dtls_endpoint = match self.ice_transport.new_endpoint(Box::new(match_dtls)).await {
+                Some(dtls_endpoint) => dtls_endpoint,
+                None => {
+                    self.state_change(RTCDtlsTransportState::Failed).await;
+                    return Err(crate::webrtc::dtls::Error::Other(
+                        "ice_transport.new_endpoint failed".to_owned(),
+                    )
+                    .into());
+                }
+            };
 
             // Connect as DTLS Client/Server, function is blocking and we
             // must not hold the DTLSTransport lock
-            crate::webrtc::dtls::conn::DTLSConn::new(
-                dtls_endpoint as Arc<dyn Conn + Send + Sync>,
-                dtls_config,
+            let dtls_conn_result = crate::webrtc::dtls::conn::DTLSConn::new(
+                Arc::clone(&dtls_endpoint) as Arc<dyn Conn + Send + Sync>,
+                dtls_config.clone(),
                 true,
                 None,
             )
-            .await
-        } else {
-            Err(crate::webrtc::dtls::Error::Other(
-                "ice_transport.new_endpoint failed".to_owned(),
-            ))
-        };
+            .await;
 
-        let dtls_conn = match dtls_conn_result {
-            Ok(dtls_conn) => dtls_conn,
-            Err(err) => {
-                self.state_change(RTCDtlsTransportState::Failed).await;
-                return Err(err.into());
+            match dtls_conn_result {
+                Ok(dtls_conn) => break dtls_conn,
+                Err(err) if retries > 0 => {
+                    log::warn!(
+                        "DTLS handshake failed, retrying ({} left): {}",
+                        retries,
+                        err
+                    );
+                    retries -= 1;
+                    // a fresh endpoint, so no stale flights reach the next attempt
+                    self.ice_transport.remove_endpoint(&dtls_endpoint).await;
+                    tokio::time::sleep(self.setting_engine.handshake_retry_delay).await;
+                }
+                Err(err) => {
+                    self.state_change(RTCDtlsTransportState::Failed).await;
+                    return Err(err.into());
+                }
             }
         };
 
+        // a psk authenticates the server instead of its certificate, which it doesn't send
+        let validated = match self.setting_engine.dtls_psk {
+            Some(_) => Ok(()),
+            None => self.validate_fingerprint(&dtls_conn).await,
+        };
+        if let Err(err) = validated {
+            if let Err(close_err) = dtls_conn.close().await {
+                log::warn!("Failed to close the DTLS connection: {}", close_err);
+            }
+            self.state_change(RTCDtlsTransportState::Failed).await;
+            return Err(err);
+        }
+
         {
             let mut conn = self.conn.lock().await;
             *conn = Some(Arc::new(dtls_conn));
@@ -149,6 +206,48 @@ impl RTCDtlsTransport {
         Ok(())
     }
 
+    /// validate_fingerprint checks the certificate the peer presented in the handshake
+    /// against the fingerprints of its session description, which binds the DTLS session
+    /// to the signaling
+    async fn validate_fingerprint(&self, conn: &DTLSConn) -> Result<()> {
+        let certificate = conn
+            .peer_certificates()
+            .first()
+            .ok_or(Error::ErrNoRemoteCertificate)?;
+        let remote_parameters = self.remote_parameters.lock().await;
+        if remote_parameters
+            .fingerprints
+            .iter()
+            .any(|fingerprint| fingerprint.matches(certificate))
+        {
+            Ok(())
+        } else {
+            Err(Error::ErrNoMatchingCertificateFingerprint)
+        }
+    }
+
+    /// stop stops and closes the DTLSTransport object.
+    pub(crate) async fn stop(&self) -> Result<()> {
+        // Try closing everything and collect the errors
+        let mut close_errs: Vec<Error> = vec![];
+
+        if let Some(conn) = self.conn.lock().await.take() {
+            // dtls_transport connection may be closed on sctp close.
+            match conn.close().await {
+                Ok(_) => {}
+                Err(err) => {
+                    if err != crate::webrtc::dtls::Error::ErrConnClosed {
+                        close_errs.push(err.into());
+                    }
+                }
+            }
+        }
+
+        self.state_change(RTCDtlsTransportState::Closed).await;
+
+        flatten_errs(close_errs)
+    }
+
     pub(crate) fn ensure_ice_conn(&self) -> Result<()> {
         if self.ice_transport.state() == RTCIceTransportState::New {
             Err(Error::ErrICEConnectionNotStarted)
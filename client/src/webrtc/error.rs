@@ -20,6 +20,16 @@ pub(crate) enum Error {
     #[error("no certificate")]
     ErrNonCertificate,
 
+    /// ErrNoRemoteCertificate indicates that the peer presented no certificate in the DTLS
+    /// handshake
+    #[error("the peer presented no certificate")]
+    ErrNoRemoteCertificate,
+
+    /// ErrNoMatchingCertificateFingerprint indicates that the certificate the peer presented
+    /// in the DTLS handshake doesn't match any fingerprint of its session description
+    #[error("the peer's certificate doesn't match the fingerprint of its description")]
+    ErrNoMatchingCertificateFingerprint,
+
     /// ErrNoRemoteDescription indicates that an operation was rejected because
     /// the remote description is not set
     #[error("remote description is not set")]
@@ -127,6 +137,15 @@ pub(crate) enum Error {
 pub(crate) type OnErrorHdlrFn =
     Box<dyn (FnMut(Error) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync>;
 
+pub(crate) fn flatten_errs(errs: Vec<Error>) -> Result<()> {
+    if errs.is_empty() {
+        Ok(())
+    } else {
+        let errs_strs: Vec<String> = errs.into_iter().map(|e| e.to_string()).collect();
+        Err(Error::new(errs_strs.join("\n")))
+    }
+}
+
 // Because Tokio SendError is parameterized, we sadly lose the backtrace.
 impl<T> From<MpscSendError<T>> for Error {
     fn from(e: MpscSendError<T>) -> Self {
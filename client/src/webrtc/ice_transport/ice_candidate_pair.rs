@@ -31,4 +31,12 @@ impl RTCIceCandidatePair {
             remote,
         }
     }
+
+    pub(crate) fn local(&self) -> &RTCIceCandidate {
+        &self.local
+    }
+
+    pub(crate) fn remote(&self) -> &RTCIceCandidate {
+        &self.remote
+    }
 }
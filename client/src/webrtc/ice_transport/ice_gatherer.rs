@@ -1,10 +1,12 @@
+use crate::webrtc::api::setting_engine::SettingEngine;
 use crate::webrtc::error::{Error, Result};
 use crate::webrtc::ice_transport::ice_candidate::*;
 use crate::webrtc::ice_transport::ice_gatherer_state::RTCIceGathererState;
 use crate::webrtc::ice_transport::ice_parameters::RTCIceParameters;
 
+use crate::webrtc::ice::agent::agent_config::InterfaceFilterFn;
 use crate::webrtc::ice::agent::Agent;
-use crate::webrtc::ice::candidate::{Candidate, CandidateType};
+use crate::webrtc::ice::candidate::Candidate;
 
 use crate::webrtc::ice::mdns::MulticastDnsMode;
 use std::future::Future;
@@ -42,12 +44,15 @@ pub(crate) struct RTCIceGatherer {
 
     // Used for gathering_complete_promise
     pub(crate) on_gathering_complete_handler: Arc<Mutex<Option<OnGatheringCompleteHdlrFn>>>,
+
+    pub(crate) setting_engine: Arc<SettingEngine>,
 }
 
 impl RTCIceGatherer {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(setting_engine: Arc<SettingEngine>) -> Self {
         RTCIceGatherer {
             state: Arc::new(AtomicU8::new(RTCIceGathererState::New as u8)),
+            setting_engine,
             ..Default::default()
         }
     }
@@ -68,23 +73,47 @@ impl RTCIceGatherer {
             mdns_mode = crate::webrtc::ice::mdns::MulticastDnsMode::QueryOnly;
         }
 
-        let mut config = crate::webrtc::ice::agent::agent_config::AgentConfig {
-            lite: false,
-            disconnected_timeout: None,
-            failed_timeout: None,
-            keepalive_interval: None,
-            candidate_types: Vec::new(),
-            host_acceptance_min_wait: None,
-            srflx_acceptance_min_wait: None,
-            prflx_acceptance_min_wait: None,
-            relay_acceptance_min_wait: None,
-            nat_1to1_ip_candidate_type: CandidateType::Unspecified,
-            net: None,
-            multicast_dns_mode: mdns_mode,
-            //TODO: TCPMux:                 self.setting_engine.iceTCPMux,
-            //TODO: ProxyDialer:            self.setting_engine.iceProxyDialer,
-            ..Default::default()
-        };
+        let (local_ufrag, local_pwd) = self
+            .setting_engine
+            .ice_credentials
+            .clone()
+            .unwrap_or_default();
+        let mut config =
+            crate::webrtc::ice::agent::agent_config::AgentConfig {
+                lite: false,
+                disconnected_timeout: None,
+                failed_timeout: None,
+                keepalive_interval: None,
+                candidate_types: Vec::new(),
+                host_acceptance_min_wait: None,
+                srflx_acceptance_min_wait: None,
+                prflx_acceptance_min_wait: None,
+                relay_acceptance_min_wait: None,
+                interface_filter: Arc::new(self.setting_engine.interface_filter.clone().map(
+                    |filter| -> InterfaceFilterFn { Box::new(move |name: &str| filter(name)) },
+                )),
+                candidate_preference: self.setting_engine.candidate_preference.clone(),
+                nat_1to1_ips: self.setting_engine.nat_1to1_ips.clone(),
+                nat_1to1_ip_candidate_type: self.setting_engine.nat_1to1_ip_candidate_type,
+                net: None,
+                multicast_dns_mode: mdns_mode,
+                dscp: self.setting_engine.dscp,
+                udp_recv_buffer_size: self.setting_engine.udp_recv_buffer_size,
+                udp_send_buffer_size: self.setting_engine.udp_send_buffer_size,
+                bind_address: self.setting_engine.bind_address,
+                udp_byte_counts: self.setting_engine.udp_byte_counts.clone(),
+                rtt_history: self.setting_engine.rtt_history.clone(),
+                race_candidate_pairs: self.setting_engine.race_candidate_pairs,
+                one_candidate_per_type: self.setting_engine.one_candidate_per_type,
+                check_interval: self.setting_engine.ice_check_interval.unwrap_or_default(),
+                max_binding_requests: self.setting_engine.ice_max_binding_requests,
+                name: self.setting_engine.name.clone(),
+                local_ufrag,
+                local_pwd,
+                //TODO: TCPMux:                 self.setting_engine.iceTCPMux,
+                //TODO: ProxyDialer:            self.setting_engine.iceProxyDialer,
+                ..Default::default()
+            };
 
         let requested_network_types = crate::webrtc::ice::network_type::supported_network_types();
 
@@ -110,6 +139,7 @@ impl RTCIceGatherer {
             let on_local_candidate_handler = Arc::clone(&self.on_local_candidate_handler);
             let on_state_change_handler = Arc::clone(&self.on_state_change_handler);
             let on_gathering_complete_handler = Arc::clone(&self.on_gathering_complete_handler);
+            let candidate_rewrite = self.setting_engine.candidate_rewrite.clone();
 
             agent
                 .on_candidate(Box::new(
@@ -120,10 +150,17 @@ impl RTCIceGatherer {
                         let on_state_change_handler_clone = Arc::clone(&on_state_change_handler);
                         let on_gathering_complete_handler_clone =
                             Arc::clone(&on_gathering_complete_handler);
+                        let candidate_rewrite_clone = candidate_rewrite.clone();
 
                         Box::pin(async move {
                             if let Some(cand) = candidate {
-                                let c = RTCIceCandidate::from(&cand);
+                                let mut c = RTCIceCandidate::from(&cand);
+                                // the handler sees the candidate as it is offered
+                                if let Some(rewrite) = &candidate_rewrite_clone {
+                                    if !rewrite(&mut c) {
+                                        return;
+                                    }
+                                }
 
                                 let mut on_local_candidate_handler =
                                     on_local_candidate_handler_clone.lock().await;
@@ -195,7 +232,49 @@ impl RTCIceGatherer {
             return Err(Error::ErrICEAgentNotExist);
         };
 
-        Ok(rtc_ice_candidates_from_ice_candidates(&ice_candidates))
+        let mut candidates = rtc_ice_candidates_from_ice_candidates(&ice_candidates);
+        if let Some(rewrite) = &self.setting_engine.candidate_rewrite {
+            candidates.retain_mut(|candidate| rewrite(candidate));
+        }
+        if self.setting_engine.sort_candidates {
+            // highest priority first, as ICE would pair them
+            candidates.sort_by(|a, b| {
+                b.priority
+                    .cmp(&a.priority)
+                    .then_with(|| a.address.cmp(&b.address))
+                    .then_with(|| a.port.cmp(&b.port))
+            });
+        }
+        Ok(candidates)
+    }
+
+    /// close prunes all local candidates, and closes the ports.
+    pub(crate) async fn close(&self) -> Result<()> {
+        self.set_state(RTCIceGathererState::Closed).await;
+
+        let agent = {
+            let mut agent_opt = self.agent.lock().await;
+            agent_opt.take()
+        };
+
+        if let Some(agent) = agent {
+            agent.close().await?;
+        }
+
+        Ok(())
+    }
+
+    /// on_state_change sets an event handler which fires any time the ICEGatherer changes
+    /// on_local_candidate sets an event handler which fires when a new local ICE candidate
+    /// is available, and with `None` once gathering has completed.
+    pub(crate) async fn on_local_candidate(&self, f: OnLocalCandidateHdlrFn) {
+        let mut on_local_candidate_handler = self.on_local_candidate_handler.lock().await;
+        *on_local_candidate_handler = Some(f);
+    }
+
+    pub(crate) async fn on_state_change(&self, f: OnICEGathererStateChangeHdlrFn) {
+        let mut on_state_change_handler = self.on_state_change_handler.lock().await;
+        *on_state_change_handler = Some(f);
     }
 
     /// State indicates the current state of the ICE gatherer.
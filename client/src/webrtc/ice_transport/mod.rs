@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use crate::webrtc::ice::candidate::Candidate;
 use crate::webrtc::ice::state::ConnectionState;
+use crate::webrtc::util::conn::conn_filtered::FilteredConn;
 use crate::webrtc::util::Conn;
 use tokio::sync::{mpsc, Mutex};
 
@@ -128,10 +129,10 @@ impl RTCIceTransport {
                 ))
                 .await;
 
-            let role = if let Some(role) = role {
-                role
-            } else {
-                RTCIceRole::Controlled
+            // a role forced through the setting engine wins over the negotiated one
+            let role = match self.gatherer.setting_engine.ice_role.or(role) {
+                Some(role) => role,
+                None => RTCIceRole::Controlled,
             };
 
             let (cancel_tx, cancel_rx) = mpsc::channel(1);
@@ -164,6 +165,11 @@ impl RTCIceTransport {
 
                 _ => return Err(Error::ErrICERoleUnknown),
             };
+            let conn: Arc<dyn Conn + Send + Sync> = match &self.gatherer.setting_engine.send_filter
+            {
+                Some(filter) => Arc::new(FilteredConn::new(conn, Arc::clone(filter))),
+                None => conn,
+            };
 
             let config = Config {
                 conn: Arc::clone(&conn),
@@ -181,6 +187,22 @@ impl RTCIceTransport {
         }
     }
 
+    /// stop irreversibly stops the ICETransport.
+    pub(crate) async fn stop(&self) -> Result<()> {
+        // Dropping the Mux ends its read loop
+        {
+            let mut internal = self.internal.lock().await;
+            internal.cancel_tx.take();
+            internal.mux.take();
+            internal.conn.take();
+        }
+
+        self.state
+            .store(RTCIceTransportState::Closed as u8, Ordering::SeqCst);
+
+        self.gatherer.close().await
+    }
+
     /// on_connection_state_change sets a handler that is fired when the ICE
     /// connection state changes.
     pub(crate) async fn on_connection_state_change(&self, f: OnConnectionStateChangeHdlrFn) {
@@ -189,6 +211,17 @@ impl RTCIceTransport {
         *on_connection_state_change_handler = Some(f);
     }
 
+    /// on_selected_candidate_pair_change sets a handler that is invoked when a new
+    /// ICE candidate pair is selected
+    pub(crate) async fn on_selected_candidate_pair_change(
+        &self,
+        f: OnSelectedCandidatePairChangeHdlrFn,
+    ) {
+        let mut on_selected_candidate_pair_change_handler =
+            self.on_selected_candidate_pair_change_handler.lock().await;
+        *on_selected_candidate_pair_change_handler = Some(f);
+    }
+
     /// adds a candidate associated with the remote ICETransport.
     pub(crate) async fn add_remote_candidate(
         &self,
@@ -213,6 +246,11 @@ impl RTCIceTransport {
         RTCIceTransportState::from(self.state.load(Ordering::SeqCst))
     }
 
+    /// role returns the role the ICE agent plays, which is unspecified until start.
+    pub(crate) async fn role(&self) -> RTCIceRole {
+        self.internal.lock().await.role
+    }
+
     pub(crate) async fn new_endpoint(&self, f: MatchFunc) -> Option<Arc<Endpoint>> {
         let internal = self.internal.lock().await;
         if let Some(mux) = &internal.mux {
@@ -222,6 +260,13 @@ impl RTCIceTransport {
         }
     }
 
+    pub(crate) async fn remove_endpoint(&self, e: &Endpoint) {
+        let internal = self.internal.lock().await;
+        if let Some(mux) = &internal.mux {
+            mux.remove_endpoint(e).await;
+        }
+    }
+
     pub(crate) async fn ensure_gatherer(&self) -> Result<()> {
         if self.gatherer.get_agent().await.is_none() {
             self.gatherer.create_agent().await
@@ -27,7 +27,7 @@ impl Conn for Endpoint {
     async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
         match self.buffer.read(buf, None).await {
             Ok(n) => Ok(n),
-            Err(err) => Err(io::Error::new(io::ErrorKind::Other, err.to_string()).into()),
+            Err(err) => Err(io::Error::other(err).into()),
         }
     }
     async fn recv_from(&self, _buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
@@ -5,8 +5,8 @@ use crate::webrtc::error::Result;
 use crate::webrtc::mux::endpoint::Endpoint;
 use crate::webrtc::mux::mux_func::MatchFunc;
 
+use crate::webrtc::ice::candidate::RECEIVE_MTU;
 use crate::webrtc::util::{Buffer, Conn};
-use crate::webrtc::RECEIVE_MTU;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -73,21 +73,30 @@ impl Mux {
         e
     }
 
+    /// removes an Endpoint, so that packets are no longer dispatched to it
+    pub(crate) async fn remove_endpoint(&self, e: &Endpoint) {
+        let mut endpoints = self.endpoints.lock().await;
+        endpoints.remove(&e.id);
+    }
+
     async fn read_loop(
         next_conn: Arc<dyn Conn + Send + Sync>,
         mut closed_ch_rx: mpsc::Receiver<()>,
         endpoints: Arc<Mutex<HashMap<usize, Arc<Endpoint>>>>,
     ) {
+        // as large as the datagrams ICE hands on, larger ones being dropped there, so that
+        // none is cut short here
         let mut buf = vec![0u8; RECEIVE_MTU];
-        let mut n = 0usize;
         loop {
-            tokio::select! {
+            let n = tokio::select! {
                 _ = closed_ch_rx.recv() => break,
-                result = next_conn.recv(&mut buf) => {
-                    if let Ok(m) = result{
-                        n = m;
+                result = next_conn.recv(&mut buf) => match result {
+                    Ok(n) => n,
+                    Err(err) => {
+                        log::debug!("mux: ending readLoop recv error {:?}", err);
+                        break;
                     }
-                }
+                },
             };
 
             if let Err(err) = Mux::dispatch(&buf[..n], &endpoints).await {
@@ -5,9 +5,9 @@ use crate::webrtc::peer_connection::math_rand_alpha;
 use crate::webrtc::dtls::crypto::{CryptoPrivateKey, CryptoPrivateKeyKind};
 use rcgen::{CertificateParams, KeyPair, RcgenError};
 use ring::signature::{EcdsaKeyPair, Ed25519KeyPair, RsaKeyPair};
-use sha2::{Digest, Sha256};
 
 /// Certificate represents a x509Cert used to authenticate WebRTC communications.
+#[derive(Clone)]
 pub(crate) struct RTCCertificate {
     pub(crate) certificate: crate::webrtc::dtls::crypto::Certificate,
 }
@@ -34,37 +34,7 @@ impl RTCCertificate {
             KeyPair::generate(params.alg)?
         };
 
-        let serialized_der = key_pair.serialize_der();
-        let private_key = if key_pair.is_compatible(&rcgen::PKCS_ED25519) {
-            CryptoPrivateKey {
-                kind: CryptoPrivateKeyKind::Ed25519(
-                    Ed25519KeyPair::from_pkcs8(&serialized_der)
-                        .map_err(|e| Error::new(e.to_string()))?,
-                ),
-                serialized_der,
-            }
-        } else if key_pair.is_compatible(&rcgen::PKCS_ECDSA_P256_SHA256) {
-            CryptoPrivateKey {
-                kind: CryptoPrivateKeyKind::Ecdsa256(
-                    EcdsaKeyPair::from_pkcs8(
-                        &ring::signature::ECDSA_P256_SHA256_ASN1_SIGNING,
-                        &serialized_der,
-                    )
-                    .map_err(|e| Error::new(e.to_string()))?,
-                ),
-                serialized_der,
-            }
-        } else if key_pair.is_compatible(&rcgen::PKCS_RSA_SHA256) {
-            CryptoPrivateKey {
-                kind: CryptoPrivateKeyKind::Rsa256(
-                    RsaKeyPair::from_pkcs8(&serialized_der)
-                        .map_err(|e| Error::new(e.to_string()))?,
-                ),
-                serialized_der,
-            }
-        } else {
-            return Err(Error::new("Unsupported key_pair".to_owned()));
-        };
+        let private_key = crypto_private_key(&key_pair)?;
         params.key_pair = Some(key_pair);
 
         let x509_cert = rcgen::Certificate::from_params(params)?;
@@ -78,21 +48,24 @@ impl RTCCertificate {
         })
     }
 
+    /// from_existing uses an already issued x509 certificate, in DER, whose subject key is
+    /// the public key of `key_pair`. The caller checks that the two belong together.
+    pub(crate) fn from_existing(key_pair: &KeyPair, certificate: Vec<u8>) -> Result<Self> {
+        Ok(RTCCertificate {
+            certificate: crate::webrtc::dtls::crypto::Certificate {
+                certificate: vec![rustls::Certificate(certificate)],
+                private_key: crypto_private_key(key_pair)?,
+            },
+        })
+    }
+
     /// get_fingerprints returns certificate fingerprints, one of which
     /// is computed with the digest algorithm used in the certificate signature.
     pub(crate) fn get_fingerprints(&self) -> Result<Vec<RTCDtlsFingerprint>> {
         let mut fingerpints = vec![];
 
         for certificate in &self.certificate.certificate {
-            let mut h = Sha256::new();
-            h.update(&certificate.0);
-            let hashed = h.finalize();
-            let values: Vec<String> = hashed.iter().map(|x| format! {"{:02x}", x}).collect();
-
-            fingerpints.push(RTCDtlsFingerprint {
-                algorithm: "sha-256".to_owned(),
-                value: values.join(":"),
-            });
+            fingerpints.push(RTCDtlsFingerprint::from_der(&certificate.0));
         }
 
         Ok(fingerpints)
@@ -117,3 +90,37 @@ impl RTCCertificate {
         RTCCertificate::from_params(params)
     }
 }
+
+/// crypto_private_key converts `key_pair` into the private key DTLS signs with.
+fn crypto_private_key(key_pair: &KeyPair) -> Result<CryptoPrivateKey> {
+    let serialized_der = key_pair.serialize_der();
+    Ok(if key_pair.is_compatible(&rcgen::PKCS_ED25519) {
+        CryptoPrivateKey {
+            kind: CryptoPrivateKeyKind::Ed25519(
+                Ed25519KeyPair::from_pkcs8(&serialized_der)
+                    .map_err(|e| Error::new(e.to_string()))?,
+            ),
+            serialized_der,
+        }
+    } else if key_pair.is_compatible(&rcgen::PKCS_ECDSA_P256_SHA256) {
+        CryptoPrivateKey {
+            kind: CryptoPrivateKeyKind::Ecdsa256(
+                EcdsaKeyPair::from_pkcs8(
+                    &ring::signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+                    &serialized_der,
+                )
+                .map_err(|e| Error::new(e.to_string()))?,
+            ),
+            serialized_der,
+        }
+    } else if key_pair.is_compatible(&rcgen::PKCS_RSA_SHA256) {
+        CryptoPrivateKey {
+            kind: CryptoPrivateKeyKind::Rsa256(
+                RsaKeyPair::from_pkcs8(&serialized_der).map_err(|e| Error::new(e.to_string()))?,
+            ),
+            serialized_der,
+        }
+    } else {
+        return Err(Error::new("Unsupported key_pair".to_owned()));
+    })
+}
@@ -6,7 +6,8 @@ pub(crate) mod policy;
 pub(crate) mod sdp;
 pub(crate) mod signaling_state;
 
-use crate::webrtc::api::API;
+use crate::webrtc::api::Api;
+use crate::webrtc::data_channel::data_channel_init::RTCDataChannelInit;
 use crate::webrtc::data_channel::data_channel_state::RTCDataChannelState;
 use crate::webrtc::data_channel::RTCDataChannel;
 use crate::webrtc::dtls_transport::dtls_fingerprint::RTCDtlsFingerprint;
@@ -14,10 +15,12 @@ use crate::webrtc::dtls_transport::dtls_parameters::DTLSParameters;
 use crate::webrtc::dtls_transport::dtls_role::{DTLSRole, DEFAULT_DTLS_ROLE_OFFER};
 use crate::webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use crate::webrtc::dtls_transport::RTCDtlsTransport;
-use crate::webrtc::error::{Error, Result};
+use crate::webrtc::error::{flatten_errs, Error, Result};
 use crate::webrtc::ice_transport::ice_candidate::RTCIceCandidate;
 use crate::webrtc::ice_transport::ice_connection_state::RTCIceConnectionState;
-use crate::webrtc::ice_transport::ice_gatherer::RTCIceGatherer;
+use crate::webrtc::ice_transport::ice_gatherer::{
+    OnICEGathererStateChangeHdlrFn, OnLocalCandidateHdlrFn, RTCIceGatherer,
+};
 use crate::webrtc::ice_transport::ice_gatherer_state::RTCIceGathererState;
 use crate::webrtc::ice_transport::ice_gathering_state::RTCIceGatheringState;
 use crate::webrtc::ice_transport::ice_parameters::RTCIceParameters;
@@ -40,8 +43,9 @@ use crate::webrtc::ice::candidate::candidate_base::unmarshal_candidate;
 use crate::webrtc::ice::candidate::Candidate;
 use crate::webrtc::sdp::description::session::*;
 use crate::webrtc::sdp::util::ConnectionRole;
+use crate::webrtc::util::rng::with_rng;
 use peer_connection_internal::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
@@ -54,16 +58,14 @@ const RUNES_ALPHA: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXY
 
 /// math_rand_alpha generates a mathmatical random alphabet sequence of the requested length.
 pub(crate) fn math_rand_alpha(n: usize) -> String {
-    let mut rng = thread_rng();
-
-    let rand_string: String = (0..n)
-        .map(|_| {
-            let idx = rng.gen_range(0..RUNES_ALPHA.len());
-            RUNES_ALPHA[idx] as char
-        })
-        .collect();
-
-    rand_string
+    with_rng(|rng| {
+        (0..n)
+            .map(|_| {
+                let idx = rng.gen_range(0..RUNES_ALPHA.len());
+                RUNES_ALPHA[idx] as char
+            })
+            .collect()
+    })
 }
 
 pub(crate) type OnSignalingStateChangeHdlrFn = Box<
@@ -106,8 +108,8 @@ impl RTCPeerConnection {
     /// If you wish to customize the set of available codecs or the set of
     /// active interceptors, create a MediaEngine and call api.new_peer_connection
     /// instead of this function.
-    pub(crate) async fn new() -> Arc<RTCPeerConnection> {
-        let internal = PeerConnectionInternal::new()
+    pub(crate) async fn new(api: &Api) -> Arc<RTCPeerConnection> {
+        let internal = PeerConnectionInternal::new(api)
             .await
             .expect("can't create peer connection");
 
@@ -156,6 +158,29 @@ impl RTCPeerConnection {
         }
     }
 
+    /// on_ice_connection_state_change sets an event handler which is called
+    /// when an ICE connection state is changed.
+    pub(crate) async fn on_ice_connection_state_change(&self, f: OnICEConnectionStateChangeHdlrFn) {
+        let mut on_ice_connection_state_change_handler = self
+            .internal
+            .on_ice_connection_state_change_handler
+            .lock()
+            .await;
+        *on_ice_connection_state_change_handler = Some(f);
+    }
+
+    /// on_ice_candidate sets an event handler which is called when a local ICE candidate
+    /// has been gathered, and with `None` once gathering has completed.
+    pub(crate) async fn on_ice_candidate(&self, f: OnLocalCandidateHdlrFn) {
+        self.internal.ice_gatherer.on_local_candidate(f).await
+    }
+
+    /// on_ice_gathering_state_change sets an event handler which is called
+    /// when the ICE gatherer changes state.
+    pub(crate) async fn on_ice_gathering_state_change(&self, f: OnICEGathererStateChangeHdlrFn) {
+        self.internal.ice_gatherer.on_state_change(f).await
+    }
+
     /// create_offer starts the PeerConnection and generates the localDescription
     /// <https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-createoffer>
     pub(crate) async fn create_offer(&self) -> Result<RTCSessionDescription> {
@@ -669,6 +694,20 @@ impl RTCPeerConnection {
             .await
     }
 
+    /// can_pair_ice_candidate returns whether local candidates have been gathered on the
+    /// network of an ICE candidate string, so that ICE can pair them with it.
+    pub(crate) async fn can_pair_ice_candidate(&self, candidate_str: &str) -> Result<bool> {
+        let candidate_value = candidate_str
+            .strip_prefix("candidate:")
+            .unwrap_or(candidate_str);
+        let network_type = unmarshal_candidate(candidate_value).await?.network_type();
+
+        Ok(match self.internal.ice_gatherer.get_agent().await {
+            Some(agent) => agent.has_local_candidates(network_type).await,
+            None => false,
+        })
+    }
+
     /// create_data_channel creates a new DataChannel object with the given label
     /// and optional DataChannelInit used to configure properties of the
     /// underlying channel such as data reliability.
@@ -676,13 +715,14 @@ impl RTCPeerConnection {
         &self,
         label: &str,
         protocol: &str,
+        options: RTCDataChannelInit,
     ) -> Result<Arc<RTCDataChannel>> {
         // https://w3c.github.io/webrtc-pc/#peer-to-peer-data-api (Step #2)
         if self.internal.is_closed.load(Ordering::SeqCst) {
             return Err(Error::ErrConnectionClosed);
         }
 
-        let d = Arc::new(RTCDataChannel::new(label, protocol));
+        let d = Arc::new(RTCDataChannel::new(label, protocol, options));
 
         {
             let mut data_channels = self.internal.sctp_transport.data_channels.lock().await;
@@ -731,6 +771,51 @@ impl RTCPeerConnection {
         populate_local_candidates(local_description.as_ref(), ice_gather, ice_gathering_state).await
     }
 
+    /// sctp returns the SCTPTransport for this PeerConnection
+    ///
+    /// The SCTP transport over which SCTP data is sent and received. If SCTP has not been
+    /// negotiated, the value is nil.
+    /// <https://www.w3.org/TR/webrtc/#attributes-15>
+    pub(crate) fn sctp(&self) -> Arc<RTCSctpTransport> {
+        Arc::clone(&self.internal.sctp_transport)
+    }
+
+    /// close ends the PeerConnection
+    pub(crate) async fn close(&self) -> Result<()> {
+        // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-close (step #1)
+        if self.internal.is_closed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-close (step #2)
+        self.internal.is_closed.store(true, Ordering::SeqCst);
+
+        // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-close (step #3)
+        self.internal
+            .signaling_state
+            .store(RTCSignalingState::Closed as u8, Ordering::SeqCst);
+
+        // Try closing everything and collect the errors
+        let mut close_errs: Vec<Error> = vec![];
+
+        // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-close (step #8, #9, #10)
+        if let Err(err) = self.internal.sctp_transport.stop().await {
+            close_errs.push(err);
+        }
+
+        // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-close (step #11)
+        if let Err(err) = self.internal.dtls_transport.stop().await {
+            close_errs.push(err);
+        }
+
+        // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-close (step #12)
+        if let Err(err) = self.internal.ice_transport.stop().await {
+            close_errs.push(err);
+        }
+
+        flatten_errs(close_errs)
+    }
+
     /// signaling_state attribute returns the signaling state of the
     /// PeerConnection instance.
     pub(crate) fn signaling_state(&self) -> RTCSignalingState {
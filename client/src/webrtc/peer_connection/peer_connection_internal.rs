@@ -43,7 +43,7 @@ pub(crate) struct PeerConnectionInternal {
 }
 
 impl PeerConnectionInternal {
-    pub(crate) async fn new() -> Result<Arc<Self>> {
+    pub(crate) async fn new(api: &Api) -> Result<Arc<Self>> {
         let mut pc = PeerConnectionInternal {
             greater_mid: AtomicIsize::new(-1),
             sdp_origin: Mutex::new(Default::default()),
@@ -69,16 +69,16 @@ impl PeerConnectionInternal {
         };
 
         // Create the ice gatherer
-        pc.ice_gatherer = Arc::new(API::new_ice_gatherer()?);
+        pc.ice_gatherer = Arc::new(api.new_ice_gatherer()?);
 
         // Create the ice transport
-        pc.ice_transport = pc.create_ice_transport().await;
+        pc.ice_transport = pc.create_ice_transport(api).await;
 
         // Create the DTLS transport
-        pc.dtls_transport = Arc::new(API::new_dtls_transport(Arc::clone(&pc.ice_transport))?);
+        pc.dtls_transport = Arc::new(api.new_dtls_transport(Arc::clone(&pc.ice_transport))?);
 
         // Create the SCTP transport
-        pc.sctp_transport = Arc::new(API::new_sctp_transport(Arc::clone(&pc.dtls_transport))?);
+        pc.sctp_transport = Arc::new(api.new_sctp_transport(Arc::clone(&pc.dtls_transport))?);
 
         // Wire up the on datachannel handler
         let on_data_channel_handler = Arc::clone(&pc.on_data_channel_handler);
@@ -112,7 +112,8 @@ impl PeerConnectionInternal {
 
         if let Some(parsed) = &remote_desc.parsed {
             if have_application_media_section(parsed) {
-                self.start_sctp().await;
+                self.start_sctp(get_max_message_size(parsed), get_sctp_port(parsed))
+                    .await;
             }
         }
 
@@ -120,12 +121,13 @@ impl PeerConnectionInternal {
     }
 
     /// Start SCTP subsystem
-    async fn start_sctp(&self) {
+    async fn start_sctp(&self, max_message_size: u32, port: u16) {
         // Start sctp
         if let Err(err) = self
             .sctp_transport
             .start(SCTPTransportCapabilities {
-                max_message_size: 0,
+                max_message_size,
+                port,
             })
             .await
         {
@@ -257,6 +259,7 @@ impl PeerConnectionInternal {
             is_icelite: false,
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: self.ice_gathering_state(),
+            sctp_port: self.sctp_transport.local_port(),
         };
         populate_sdp(
             d,
@@ -329,6 +332,7 @@ impl PeerConnectionInternal {
             is_icelite: false,
             connection_role,
             ice_gathering_state: self.ice_gathering_state(),
+            sctp_port: self.sctp_transport.local_port(),
         };
         populate_sdp(
             d,
@@ -349,8 +353,8 @@ impl PeerConnectionInternal {
         }
     }
 
-    pub(crate) async fn create_ice_transport(&self) -> Arc<RTCIceTransport> {
-        let ice_transport = Arc::new(API::new_ice_transport(Arc::clone(&self.ice_gatherer)));
+    pub(crate) async fn create_ice_transport(&self, api: &Api) -> Arc<RTCIceTransport> {
+        let ice_transport = Arc::new(api.new_ice_transport(Arc::clone(&self.ice_gatherer)));
 
         let ice_connection_state = Arc::clone(&self.ice_connection_state);
         let peer_connection_state = Arc::clone(&self.peer_connection_state);
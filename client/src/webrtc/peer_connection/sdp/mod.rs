@@ -11,6 +11,7 @@ pub(crate) mod session_description;
 use crate::webrtc::ice::candidate::candidate_base::unmarshal_candidate;
 use crate::webrtc::ice::candidate::Candidate;
 use crate::webrtc::peer_connection::MEDIA_SECTION_APPLICATION;
+use crate::webrtc::sctp::association::DEFAULT_SCTP_PORT;
 use crate::webrtc::sdp::description::common::{Address, ConnectionInformation};
 use crate::webrtc::sdp::description::media::{MediaDescription, MediaName, RangedPort};
 use crate::webrtc::sdp::description::session::*;
@@ -69,6 +70,7 @@ pub(crate) struct AddDataMediaSectionParams {
     ice_params: RTCIceParameters,
     dtls_role: ConnectionRole,
     ice_gathering_state: RTCIceGatheringState,
+    sctp_port: u16,
 }
 
 pub(crate) async fn add_data_media_section(
@@ -107,7 +109,7 @@ pub(crate) async fn add_data_media_section(
     )
     .with_value_attribute(ATTR_KEY_MID.to_owned(), params.mid_value)
     .with_property_attribute("sendrecv".to_owned())
-    .with_property_attribute("sctp-port:5000".to_owned())
+    .with_property_attribute(format!("sctp-port:{}", params.sctp_port))
     .with_ice_credentials(
         params.ice_params.username_fragment,
         params.ice_params.password,
@@ -176,6 +178,7 @@ pub(crate) struct PopulateSdpParams {
     pub(crate) is_icelite: bool,
     pub(crate) connection_role: ConnectionRole,
     pub(crate) ice_gathering_state: RTCIceGatheringState,
+    pub(crate) sctp_port: u16,
 }
 
 /// populate_sdp serializes a PeerConnections state into an SDP
@@ -206,6 +209,7 @@ pub(crate) async fn populate_sdp(
                 ice_params: ice_params.clone(),
                 dtls_role: params.connection_role,
                 ice_gathering_state: params.ice_gathering_state,
+                sctp_port: params.sctp_port,
             };
             d = add_data_media_section(d, &media_dtls_fingerprints, candidates, params).await?;
             true
@@ -358,6 +362,44 @@ pub(crate) fn have_application_media_section(desc: &SessionDescription) -> bool
     false
 }
 
+/// get_max_message_size returns the max-message-size advertised for the application media
+/// section, where 0 means that any size is accepted. Absent the attribute, the default of
+/// 64K applies.
+/// <https://www.rfc-editor.org/rfc/rfc8841#section-6>
+pub(crate) fn get_max_message_size(desc: &SessionDescription) -> u32 {
+    const DEFAULT_REMOTE_MAX_MESSAGE_SIZE: u32 = 65536;
+
+    for m in &desc.media_descriptions {
+        if m.media_name.media == MEDIA_SECTION_APPLICATION {
+            if let Some(Some(value)) = m.attribute("max-message-size") {
+                if let Ok(max_message_size) = value.trim().parse::<u32>() {
+                    return max_message_size;
+                }
+            }
+        }
+    }
+
+    DEFAULT_REMOTE_MAX_MESSAGE_SIZE
+}
+
+/// get_sctp_port returns the sctp-port advertised for the application media section, which
+/// is the port the remote SCTP association uses. Absent the attribute, the default of 5000
+/// applies.
+/// <https://www.rfc-editor.org/rfc/rfc8841#section-5>
+pub(crate) fn get_sctp_port(desc: &SessionDescription) -> u16 {
+    for m in &desc.media_descriptions {
+        if m.media_name.media == MEDIA_SECTION_APPLICATION {
+            if let Some(Some(value)) = m.attribute("sctp-port") {
+                if let Ok(port) = value.trim().parse::<u16>() {
+                    return port;
+                }
+            }
+        }
+    }
+
+    DEFAULT_SCTP_PORT
+}
+
 /// update_sdp_origin saves sdp.Origin in PeerConnection when creating 1st local SDP;
 /// for subsequent calling, it updates Origin for SessionDescription from saved one
 /// and increments session version by one.
@@ -3,12 +3,13 @@ pub(crate) mod sctp_transport_state;
 
 use sctp_transport_state::RTCSctpTransportState;
 
+use crate::webrtc::api::setting_engine::SettingEngine;
 use crate::webrtc::data_channel::RTCDataChannel;
 use crate::webrtc::dtls_transport::*;
 use crate::webrtc::error::*;
 use crate::webrtc::sctp_transport::sctp_transport_capabilities::SCTPTransportCapabilities;
 
-use crate::webrtc::sctp::association::Association;
+use crate::webrtc::sctp::association::{Association, DEFAULT_MAX_MESSAGE_SIZE, DEFAULT_SCTP_PORT};
 
 use crate::webrtc::util::Conn;
 use std::future::Future;
@@ -26,11 +27,8 @@ pub(crate) type OnDataChannelHdlrFn = Box<
 /// SCTPTransport provides details about the SCTP transport.
 #[derive(Default)]
 pub(crate) struct RTCSctpTransport {
-    // removing this causes compile panic, last checked
-    #[allow(dead_code)]
-    max_message_size: bool,
-    #[allow(dead_code)]
-    setting_engine: bool,
+    max_message_size: AtomicU32,
+    setting_engine: Arc<SettingEngine>,
 
     pub(crate) dtls_transport: Arc<RTCDtlsTransport>,
 
@@ -54,10 +52,13 @@ pub(crate) struct RTCSctpTransport {
 }
 
 impl RTCSctpTransport {
-    pub(crate) fn new(dtls_transport: Arc<RTCDtlsTransport>) -> Self {
+    pub(crate) fn new(
+        dtls_transport: Arc<RTCDtlsTransport>,
+        setting_engine: Arc<SettingEngine>,
+    ) -> Self {
         RTCSctpTransport {
-            setting_engine: true,
-            max_message_size: true,
+            setting_engine,
+            max_message_size: AtomicU32::new(0),
 
             dtls_transport,
             state: AtomicU8::new(RTCSctpTransportState::Connecting as u8),
@@ -71,6 +72,32 @@ impl RTCSctpTransport {
         }
     }
 
+    /// max_message_size returns the largest message which can be sent, once the transport
+    /// has been started, and 0 before.
+    pub(crate) fn max_message_size(&self) -> u32 {
+        self.max_message_size.load(Ordering::SeqCst)
+    }
+
+    // calc_message_size caps the local limit, which defaults to DEFAULT_MAX_MESSAGE_SIZE, by the
+    // limit advertised by the remote, where 0 means that any size is accepted
+    fn calc_message_size(remote_max_message_size: u32, can_send_size: u32) -> u32 {
+        let can_send_size = if can_send_size == 0 {
+            DEFAULT_MAX_MESSAGE_SIZE
+        } else {
+            can_send_size
+        };
+        if remote_max_message_size == 0 {
+            can_send_size
+        } else {
+            can_send_size.min(remote_max_message_size)
+        }
+    }
+
+    /// local_port returns the SCTP port of this end, which goes into the local `a=sctp-port`.
+    pub(crate) fn local_port(&self) -> u16 {
+        self.setting_engine.sctp_port.unwrap_or(DEFAULT_SCTP_PORT)
+    }
+
     /// transport returns the DTLSTransport instance the SCTPTransport is sending over.
     pub(crate) fn transport(&self) -> Arc<RTCDtlsTransport> {
         Arc::clone(&self.dtls_transport)
@@ -79,25 +106,54 @@ impl RTCSctpTransport {
     /// Start the SCTPTransport. Since both local and remote parties must mutually
     /// create an SCTPTransport, SCTP SO (Simultaneous Open) is used to establish
     /// a connection over SCTP.
-    pub(crate) async fn start(&self, _remote_caps: SCTPTransportCapabilities) -> Result<()> {
+    pub(crate) async fn start(&self, remote_caps: SCTPTransportCapabilities) -> Result<()> {
         if self.is_started.load(Ordering::SeqCst) {
             return Ok(());
         }
         self.is_started.store(true, Ordering::SeqCst);
 
+        let max_message_size = RTCSctpTransport::calc_message_size(
+            remote_caps.max_message_size,
+            self.setting_engine.sctp_max_message_size,
+        );
+        self.max_message_size
+            .store(max_message_size, Ordering::SeqCst);
+
         let dtls_transport = self.transport();
         if let Some(net_conn) = &dtls_transport.conn().await {
-            let sctp_association = Arc::new(
-                crate::webrtc::sctp::association::Association::client(
+            let mut retries = self.setting_engine.handshake_retries;
+            let sctp_association = loop {
+                let association_result = crate::webrtc::sctp::association::Association::client(
                     crate::webrtc::sctp::association::Config {
                         net_conn: Arc::clone(net_conn) as Arc<dyn Conn + Send + Sync>,
                         max_receive_buffer_size: 0,
-                        max_message_size: 0,
-                        name: String::new(),
+                        max_message_size,
+                        name: self.setting_engine.name.clone(),
+                        local_port: self.local_port(),
+                        remote_port: remote_caps.port,
+                        rto: self.setting_engine.sctp_rto,
+                        loss_events: self.setting_engine.sctp_loss_events.clone(),
+                        up_events: self.setting_engine.sctp_up_events.clone(),
+                        heartbeat_acks: self.setting_engine.sctp_heartbeat_acks.clone(),
                     },
                 )
-                .await?,
-            );
+                .await;
+
+                match association_result {
+                    Ok(association) => break Arc::new(association),
+                    Err(err) if retries > 0 => {
+                        log::warn!(
+                            "[{}] SCTP association failed, retrying ({} left): {}",
+                            self.setting_engine.name,
+                            retries,
+                            err
+                        );
+                        retries -= 1;
+                        tokio::time::sleep(self.setting_engine.handshake_retry_delay).await;
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+            };
 
             {
                 let mut sa = self.sctp_association.lock().await;
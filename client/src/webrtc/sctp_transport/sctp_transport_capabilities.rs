@@ -4,4 +4,5 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub(crate) struct SCTPTransportCapabilities {
     pub(crate) max_message_size: u32,
+    pub(crate) port: u16,
 }
@@ -0,0 +1,82 @@
+use reqwest::{header, Client as HttpClient, StatusCode};
+use tracing::warn;
+use url::Url;
+
+use crate::{
+    error::SocketConnectionError,
+    signaling_retry::{self, SignalingRetry},
+};
+
+const SDP_CONTENT_TYPE: &str = "application/sdp";
+
+// WhipAnswer is the response of a WHIP endpoint to an offer
+pub(crate) struct WhipAnswer {
+    pub(crate) sdp: String,
+    // the session's resource, which is deleted to end the session
+    pub(crate) resource: Option<Url>,
+}
+
+// post_offer sends the offer to a WHIP endpoint and reads back the answer. Like the default
+// signaling, sending is retried as configured with `retry`.
+// https://www.rfc-editor.org/rfc/rfc9725#section-4.2
+pub(crate) async fn post_offer(
+    http_client: &HttpClient,
+    endpoint: &Url,
+    offer: String,
+    retry: Option<&SignalingRetry>,
+) -> Result<WhipAnswer, SocketConnectionError> {
+    let response = signaling_retry::send(retry, || {
+        http_client
+            .post(endpoint.clone())
+            .header(header::CONTENT_TYPE, SDP_CONTENT_TYPE)
+            .body(offer.clone())
+    })
+    .await?;
+
+    let status = response.status();
+    if status != StatusCode::CREATED {
+        return Err(SocketConnectionError::Signaling {
+            reason: format!("the WHIP endpoint responded with {}", status),
+        });
+    }
+
+    // a relative location is resolved against the endpoint
+    let resource = response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .and_then(|location| endpoint.join(location).ok());
+    if resource.is_none() {
+        warn!("The WHIP endpoint returned no resource, so the session won't be deleted");
+    }
+
+    let sdp = response
+        .text()
+        .await
+        .map_err(|err| SocketConnectionError::Signaling {
+            reason: format!("could not read the WHIP answer: {}", err),
+        })?;
+    Ok(WhipAnswer { sdp, resource })
+}
+
+// delete_resource ends the session of a WHIP resource.
+// https://www.rfc-editor.org/rfc/rfc9725#section-4.2
+pub(crate) async fn delete_resource(
+    http_client: &HttpClient,
+    resource: Url,
+) -> reqwest::Result<()> {
+    http_client
+        .delete(resource)
+        .send()
+        .await?
+        .error_for_status()
+        .map(|_| ())
+}
+
+// first_candidate returns the first candidate of an answer, without the `a=` prefix
+pub(crate) fn first_candidate(sdp: &str) -> Option<&str> {
+    sdp.lines()
+        .map(str::trim)
+        .filter_map(|line| line.strip_prefix("a="))
+        .find(|attribute| attribute.starts_with("candidate:"))
+}
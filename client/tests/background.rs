@@ -0,0 +1,72 @@
+// Checks that a background connect queues messages until the data channel opens, and
+// reports a failed connection through ready and the events
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{
+    EchoAnswerer, HandshakePhase, Socket, SocketConfig, SocketConnectionError, SocketEvent,
+};
+
+use crate::common::TIMEOUT;
+
+#[tokio::test]
+async fn queued_messages_are_sent_once_open() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (_, mut socket_io) = Socket::connect_background(answerer.url()).unwrap();
+    // sent before the offer has even been answered
+    socket_io.send(b"early".to_vec().into()).await.unwrap();
+
+    tokio::time::timeout(TIMEOUT, socket_io.ready())
+        .await
+        .expect("the data channel didn't open")
+        .unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"early"[..]));
+}
+
+#[tokio::test]
+async fn failure_is_reported() {
+    // nothing answers on the port once the answerer is gone
+    let server_url = {
+        let answerer = EchoAnswerer::start().await.unwrap();
+        answerer.url().to_owned()
+    };
+    let mut config = SocketConfig::default();
+    config.set_signaling_retry(1, Duration::from_millis(10), Duration::from_millis(10));
+    let (_, socket_io) = Socket::connect_background_with_config(&server_url, config).unwrap();
+    let mut events = socket_io.events();
+
+    let result = tokio::time::timeout(TIMEOUT, socket_io.ready())
+        .await
+        .expect("the connection attempt didn't end");
+    assert!(matches!(
+        result,
+        Err(SocketConnectionError::Signaling { .. })
+    ));
+    // the error is returned once
+    assert!(matches!(
+        socket_io.ready().await,
+        Err(SocketConnectionError::Closed)
+    ));
+    loop {
+        match events.recv().await.unwrap() {
+            SocketEvent::ConnectFailed { phase, .. } => {
+                assert_eq!(phase, Some(HandshakePhase::Signaling));
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert!(matches!(
+        socket_io.send(b"late".to_vec().into()).await,
+        Err(SocketConnectionError::Closed)
+    ));
+}
+
+#[tokio::test]
+async fn invalid_urls_fail_right_away() {
+    assert!(matches!(
+        Socket::connect_background("localhost:1234"),
+        Err(SocketConnectionError::InvalidServerUrl { .. })
+    ));
+}
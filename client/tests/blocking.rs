@@ -0,0 +1,59 @@
+// Checks that a BlockingSocket gets back the messages it sends, and that dropping it sends
+// the messages still queued, for as long as the drain timeout allows
+
+use std::time::{Duration, Instant};
+
+use webrtc_unreliable_client::{BlockingSocket, EchoAnswerer, SocketConfig};
+
+use crate::common::TIMEOUT;
+
+// as many as the queue holds, so that queuing them doesn't wait for the pacing
+const MESSAGES: u64 = 8;
+const MESSAGE_SIZE: usize = 500;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn messages_round_trip_until_dropped() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let url = answerer.url().to_owned();
+    tokio::task::spawn_blocking(move || {
+        let mut socket = BlockingSocket::connect(&url).unwrap();
+        for payload in [&b"hello"[..], &[0xa5; 1000]] {
+            socket.send(payload).unwrap();
+            assert_eq!(socket.recv().as_deref(), Some(payload));
+        }
+        // dropping returns once the connection has closed
+        let started = Instant::now();
+        drop(socket);
+        assert!(started.elapsed() < TIMEOUT, "{:?}", started.elapsed());
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queued_messages_are_sent_on_drop() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    // sending everything takes about 2s, longer than the default drain timeout
+    config.set_send_byte_rate(2_000, MESSAGE_SIZE as u64);
+    config.set_drain_timeout(TIMEOUT);
+    let url = answerer.url().to_owned();
+    tokio::task::spawn_blocking(move || {
+        let mut socket = BlockingSocket::connect_with_config(&url, config).unwrap();
+        // an echo tells that the data channel has opened
+        socket.send(&[7; MESSAGE_SIZE]).unwrap();
+        assert!(socket.recv().is_some());
+        for _ in 0..MESSAGES {
+            socket.send(&[7; MESSAGE_SIZE]).unwrap();
+        }
+    })
+    .await
+    .unwrap();
+
+    // the last messages may still be on their way from the association to the echo loop
+    let deadline = Instant::now() + TIMEOUT;
+    while answerer.received_messages() < MESSAGES + 1 && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(answerer.received_messages(), MESSAGES + 1);
+}
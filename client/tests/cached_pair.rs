@@ -0,0 +1,56 @@
+// Checks that the first connection goes over a cached candidate pair, and gathers on every
+// interface again when the cached pair doesn't connect
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use webrtc_unreliable_client::{CandidatePair, SocketConfig};
+
+use crate::common::{connect, TIMEOUT};
+
+// cached_pair_config caches a pair whose local candidate is on `local`
+fn cached_pair_config(local: &str, timeout: Duration) -> SocketConfig {
+    let local: SocketAddr = local.parse().unwrap();
+    let remote: SocketAddr = "127.0.0.1:9".parse().unwrap();
+    let mut config = SocketConfig::default();
+    config.set_cached_candidate_pair(CandidatePair::new(local, remote), timeout);
+    config
+}
+
+#[tokio::test]
+async fn cached_pair_is_tried_first() {
+    // loopback is never gathered on otherwise
+    let (_answerer, mut socket_io) = connect(cached_pair_config("127.0.0.1:0", TIMEOUT)).await;
+
+    let pair = socket_io.selected_candidate_pair().unwrap();
+    assert!(pair.local.ip().is_loopback(), "{:?}", pair);
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn dead_cached_pair_falls_back_to_gathering() {
+    // the answerer only listens on IPv4, which an IPv6 candidate can't reach
+    let cached_timeout = Duration::from_millis(500);
+    let started = Instant::now();
+    let (_answerer, mut socket_io) = connect(cached_pair_config("[::1]:0", cached_timeout)).await;
+
+    // the cached pair was given its time before gathering again
+    assert!(
+        started.elapsed() >= cached_timeout,
+        "{:?}",
+        started.elapsed()
+    );
+
+    let pair = socket_io.selected_candidate_pair().unwrap();
+    assert!(pair.local.is_ipv4(), "{:?}", pair);
+    assert!(!pair.local.ip().is_loopback(), "{:?}", pair);
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    socket_io.close().await;
+}
@@ -0,0 +1,35 @@
+// Checks that a reliable message cancelled while it is still queued is never sent, and that
+// one which has been sent can't be cancelled anymore
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig};
+
+use crate::common::TIMEOUT;
+
+#[tokio::test]
+async fn cancelled_message_is_not_sent() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    let (_, mut socket_io) =
+        Socket::connect_background_with_config(answerer.url(), config).unwrap();
+
+    // queued until the data channels open
+    let first = socket_io.send_reliable_cancellable(b"first").await.unwrap();
+    let stale = socket_io.send_reliable_cancellable(b"stale").await.unwrap();
+    socket_io.send_reliable(b"fresh").await.unwrap();
+    assert!(stale.cancel());
+    assert!(stale.cancel());
+    assert!(!stale.is_taken());
+
+    tokio::time::timeout(TIMEOUT, socket_io.ready())
+        .await
+        .expect("the data channel didn't open")
+        .unwrap();
+    // reliable messages are echoed in order, so the stale one would come in between
+    for expected in [&b"first"[..], b"fresh"] {
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(echo.as_deref(), Some(expected));
+    }
+    assert!(first.is_taken());
+    assert!(!first.cancel());
+}
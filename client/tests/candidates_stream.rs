@@ -0,0 +1,56 @@
+// Checks that the candidates stream yields the gathered candidates, rewritten as they are
+// offered, and ends once gathering has completed
+
+use std::{future::poll_fn, pin::Pin};
+
+use futures_core::Stream;
+use webrtc_unreliable_client::{CandidateInfo, CandidateStream, SocketConfig};
+
+use crate::common::{connect, TIMEOUT};
+
+async fn next(stream: &mut CandidateStream) -> Option<CandidateInfo> {
+    tokio::time::timeout(TIMEOUT, poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)))
+        .await
+        .expect("the stream didn't end")
+}
+
+#[tokio::test]
+async fn yields_gathered_candidates_then_ends() {
+    let mut config = SocketConfig::default();
+    let mut stream = config.candidates_stream();
+    let (_answerer, mut socket_io) = connect(config).await;
+
+    let mut candidates = Vec::new();
+    while let Some(candidate) = next(&mut stream).await {
+        candidates.push(candidate);
+    }
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    // the connection goes through one of them
+    let local = socket_io.selected_candidate_pair().unwrap().local;
+    assert!(
+        candidates
+            .iter()
+            .any(|candidate| candidate.address == local.ip().to_string()
+                && candidate.port == local.port()),
+        "{} isn't among {:?}",
+        local,
+        candidates
+    );
+}
+
+#[tokio::test]
+async fn yields_rewritten_candidates() {
+    let mut config = SocketConfig::default();
+    config.on_gathered_candidate(|mut candidate| {
+        candidate.port = 4242;
+        Some(candidate)
+    });
+    let mut stream = config.candidates_stream();
+    let (_answerer, socket_io) = connect(config).await;
+
+    let candidate = next(&mut stream).await.unwrap();
+    assert_eq!(candidate.port, 4242);
+    socket_io.close().await;
+}
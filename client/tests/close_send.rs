@@ -0,0 +1,30 @@
+// Checks that close_send fails later sends with SendClosed, while the messages sent before
+// it are still echoed back
+
+use webrtc_unreliable_client::{SocketConfig, SocketConnectionError};
+
+use crate::common::{connect, TIMEOUT};
+
+const MESSAGES: u8 = 8;
+
+#[tokio::test]
+async fn messages_in_flight_are_echoed_after_close_send() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+    for i in 0..MESSAGES {
+        socket_io.send(vec![i].into()).await.unwrap();
+    }
+    socket_io.close_send();
+
+    match socket_io.send(b"late".to_vec().into()).await {
+        Err(SocketConnectionError::SendClosed) => {}
+        result => panic!("sending after close_send gave {:?}", result),
+    }
+    let mut echoes = Vec::new();
+    for _ in 0..MESSAGES {
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        echoes.push(echo.expect("receiving ended before every echo arrived"));
+    }
+    echoes.sort();
+    let sent: Vec<Box<[u8]>> = (0..MESSAGES).map(|i| vec![i].into()).collect();
+    assert_eq!(echoes, sent);
+}
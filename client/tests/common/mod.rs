@@ -0,0 +1,100 @@
+// Fixtures shared by the integration tests: connecting to an echo answerer, and standing in
+// for its signaling endpoint
+
+use std::time::Duration;
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketIo};
+
+// TIMEOUT bounds connecting, and waiting for whatever a test expects to happen
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+// connect starts an echo answerer and connects to it with `config`
+pub async fn connect(config: SocketConfig) -> (EchoAnswerer, SocketIo) {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let socket_io = connect_to(&answerer, config).await;
+    (answerer, socket_io)
+}
+
+// connect_to connects to `answerer` with `config`, and waits for the data channel to open
+pub async fn connect_to(answerer: &EchoAnswerer, config: SocketConfig) -> SocketIo {
+    let (_, socket_io) =
+        tokio::time::timeout(TIMEOUT, Socket::connect_with_config(answerer.url(), config))
+            .await
+            .expect("connecting timed out")
+            .unwrap();
+    tokio::time::timeout(TIMEOUT, socket_io.ready())
+        .await
+        .expect("the data channel didn't open")
+        .unwrap();
+    socket_io
+}
+
+// signaling_proxy serves the signaling endpoint of `answerer` on a url of its own, passing
+// every answer through `rewrite`
+pub async fn signaling_proxy<F>(answerer: &EchoAnswerer, rewrite: F) -> String
+where
+    F: Fn(String) -> String + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/rtc_session", listener.local_addr().unwrap());
+    let answerer_url = answerer.url().to_owned();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (_, offer) = read_request(&mut stream).await;
+            let answer = rewrite(fetch_answer(&answerer_url, offer).await);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                answer.len(),
+                answer
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+    url
+}
+
+// read_request reads the head and body of an HTTP request, and returns both
+pub async fn read_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        assert_ne!(n, 0, "the request ended early");
+        request.extend_from_slice(&buf[..n]);
+        if let Some(pos) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+    let content_length: usize = head
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .map_or(0, |length| length.trim().parse().unwrap());
+    while request.len() < head_end + content_length {
+        let n = stream.read(&mut buf).await.unwrap();
+        assert_ne!(n, 0, "the request ended early");
+        request.extend_from_slice(&buf[..n]);
+    }
+    let body = request.split_off(head_end);
+    (String::from_utf8_lossy(&request).into_owned(), body)
+}
+
+// fetch_answer posts `offer` to the signaling endpoint at `answerer_url`, and returns the
+// answer
+pub async fn fetch_answer(answerer_url: &str, offer: Vec<u8>) -> String {
+    reqwest::Client::new()
+        .post(answerer_url)
+        .body(offer)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+}
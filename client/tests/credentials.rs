@@ -0,0 +1,53 @@
+// Checks that a reconnection offers the credentials of an earlier connection while they're
+// young enough, and new ones once they're too old
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, LocalDescription, SessionCredentials, SocketConfig};
+
+use crate::common::connect_to;
+
+// connect connects to `answerer` with `config`, returning the offer it was sent and the
+// credentials it was offered with
+async fn connect(
+    answerer: &EchoAnswerer,
+    config: SocketConfig,
+) -> (LocalDescription, SessionCredentials) {
+    let socket_io = connect_to(answerer, config).await;
+    let description = socket_io.local_description().expect("no offer was made");
+    let credentials = socket_io
+        .session_credentials()
+        .expect("no credentials were offered");
+    socket_io.close().await;
+    (description, credentials)
+}
+
+#[tokio::test]
+async fn reconnection_reuses_young_credentials() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (first, credentials) = connect(&answerer, SocketConfig::default()).await;
+    assert_eq!(credentials.ice_ufrag(), first.ice_ufrag);
+    assert_eq!(credentials.certificate().fingerprint(), first.fingerprint);
+
+    let mut config = SocketConfig::default();
+    config.set_session_credentials(credentials.clone(), Duration::from_secs(60));
+    let (second, reused) = connect(&answerer, config).await;
+    assert_eq!(second.ice_ufrag, first.ice_ufrag);
+    assert_eq!(second.ice_pwd, first.ice_pwd);
+    assert_eq!(second.fingerprint, first.fingerprint);
+    // the age counts from the first offer
+    let age = credentials.age();
+    assert!(reused.age() >= age);
+}
+
+#[tokio::test]
+async fn reconnection_replaces_old_credentials() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (first, credentials) = connect(&answerer, SocketConfig::default()).await;
+
+    let mut config = SocketConfig::default();
+    config.set_session_credentials(credentials, Duration::ZERO);
+    let (second, _) = connect(&answerer, config).await;
+    assert_ne!(second.ice_ufrag, first.ice_ufrag);
+    assert_ne!(second.fingerprint, first.fingerprint);
+}
@@ -0,0 +1,47 @@
+// Checks that seeding the RNG makes the offer repeat byte for byte, apart from the DTLS
+// fingerprint, and that another seed gives other ICE credentials
+
+use webrtc_unreliable_client::{EchoAnswerer, LocalDescription, SocketConfig};
+
+use crate::common::{connect_to, TIMEOUT};
+
+// offer connects with the RNG seeded with `seed` and returns the offer that was signaled
+async fn offer(answerer: &EchoAnswerer, seed: u64) -> LocalDescription {
+    let mut config = SocketConfig::default();
+    config.set_rng_seed(seed);
+    let mut socket_io = connect_to(answerer, config).await;
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    let description = socket_io.local_description().unwrap();
+    socket_io.close().await;
+    description
+}
+
+fn without_fingerprint(sdp: &str) -> String {
+    sdp.lines()
+        .filter(|line| !line.starts_with("a=fingerprint:"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The RNG is shared by the process, so the connections are made one after another in a
+// single test
+#[tokio::test]
+async fn seeded_offer_repeats() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+
+    let first = offer(&answerer, 42).await;
+    let second = offer(&answerer, 42).await;
+    assert_eq!(first.ice_ufrag, second.ice_ufrag);
+    assert_eq!(first.ice_pwd, second.ice_pwd);
+    assert_eq!(
+        without_fingerprint(&first.sdp),
+        without_fingerprint(&second.sdp)
+    );
+
+    let other = offer(&answerer, 43).await;
+    assert_ne!(first.ice_ufrag, other.ice_ufrag);
+    assert_ne!(first.ice_pwd, other.ice_pwd);
+}
@@ -0,0 +1,55 @@
+// Checks that close sends the messages still queued before shutting the connection down,
+// and gives up on them once the drain timeout has passed
+
+use std::time::{Duration, Instant};
+
+use webrtc_unreliable_client::{EchoAnswerer, SocketConfig, SocketIo};
+
+use crate::common::{connect_to, TIMEOUT};
+
+// as many as the queue holds, so that queuing them doesn't wait for the pacing
+const MESSAGES: u64 = 8;
+const MESSAGE_SIZE: usize = 500;
+
+// connect_paced connects with sends paced to `bytes_per_second`, and queues MESSAGES
+// messages, which take a while to go out
+async fn connect_paced(
+    answerer: &EchoAnswerer,
+    mut config: SocketConfig,
+    bytes_per_second: u64,
+) -> SocketIo {
+    config.set_send_byte_rate(bytes_per_second, MESSAGE_SIZE as u64);
+    let socket_io = connect_to(answerer, config).await;
+    for _ in 0..MESSAGES {
+        socket_io.send(vec![7; MESSAGE_SIZE].into()).await.unwrap();
+    }
+    socket_io
+}
+
+#[tokio::test]
+async fn queued_messages_are_sent_before_close() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_drain_timeout(TIMEOUT);
+    let socket_io = connect_paced(&answerer, config, 20_000).await;
+
+    socket_io.close().await;
+    assert_eq!(
+        socket_io.stats().payload_bytes_sent,
+        MESSAGES * MESSAGE_SIZE as u64
+    );
+}
+
+#[tokio::test]
+async fn remaining_messages_are_abandoned_after_the_drain_timeout() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_drain_timeout(Duration::from_millis(200));
+    // sending everything would take 10s
+    let socket_io = connect_paced(&answerer, config, 1_000).await;
+
+    let started = Instant::now();
+    socket_io.close().await;
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert!(socket_io.stats().payload_bytes_sent < MESSAGES * MESSAGE_SIZE as u64);
+}
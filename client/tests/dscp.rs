@@ -0,0 +1,107 @@
+// Checks that the DSCP value set with SocketConfig::set_dscp is written to the IPv4 ToS and
+// the IPv6 Traffic Class of the ICE UDP sockets, by reading it back from the sockets, and
+// that a value wider than six bits is rejected
+#![cfg(unix)]
+
+use std::{
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    os::unix::io::{BorrowedFd, RawFd},
+    time::{Duration, Instant},
+};
+
+use nix::sys::socket::{getsockname, SockAddr};
+use socket2::{SockRef, Type};
+use webrtc_unreliable_client::{
+    EchoAnswerer, Socket, SocketConfig, SocketConnectionError, SocketIo,
+};
+
+use crate::common::{connect, TIMEOUT};
+
+// Expedited Forwarding, in the upper six bits of the ToS and Traffic Class fields
+const DSCP: u8 = 46;
+const TOS: u32 = (DSCP as u32) << 2;
+const MAX_FD: RawFd = 1024;
+
+// udp_socket finds the UDP socket of this process bound to an address `matches` accepts, by
+// asking every file descriptor for its local address
+fn udp_socket(matches: impl Fn(SocketAddr) -> bool) -> Option<RawFd> {
+    (0..MAX_FD).find(|&fd| {
+        let addr = match getsockname(fd) {
+            Ok(SockAddr::Inet(addr)) => addr.to_std(),
+            _ => return false,
+        };
+        matches(addr) && with_socket(fd, |socket| socket.r#type().ok()) == Some(Type::DGRAM)
+    })
+}
+
+fn with_socket<T>(fd: RawFd, f: impl FnOnce(SockRef<'_>) -> T) -> T {
+    // the connection owning the socket is kept up while it is looked at
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    f(SockRef::from(&fd))
+}
+
+// connect_with_socket connects to an echo answerer, and returns the socket of the selected candidate
+// pair along with what keeps the connection up
+async fn connect_with_socket(config: SocketConfig) -> (EchoAnswerer, SocketIo, RawFd) {
+    let (answerer, mut socket_io) = connect(config).await;
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    let local = socket_io.selected_candidate_pair().unwrap().local;
+    let fd = udp_socket(|addr| addr == local).unwrap();
+    (answerer, socket_io, fd)
+}
+
+#[tokio::test]
+async fn ipv4_tos_is_marked() {
+    let mut config = SocketConfig::default();
+    config.set_dscp(DSCP);
+    let (_answerer, _socket_io, fd) = connect_with_socket(config).await;
+    assert_eq!(with_socket(fd, |socket| socket.tos().unwrap()), TOS);
+}
+
+#[tokio::test]
+async fn dscp_wider_than_six_bits_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_dscp(64);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Err(SocketConnectionError::InvalidDscp { dscp }) => assert_eq!(dscp, 64),
+        Err(err) => panic!("failed with {}", err),
+        Ok(_) => panic!("a DSCP of 64 was accepted"),
+    }
+}
+
+#[tokio::test]
+async fn ipv4_tos_is_unmarked_by_default() {
+    let (_answerer, _socket_io, fd) = connect_with_socket(SocketConfig::default()).await;
+    assert_eq!(with_socket(fd, |socket| socket.tos().unwrap()), 0);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+#[tokio::test]
+async fn ipv6_traffic_class_is_marked() {
+    // the answerer only listens on IPv4, so this connection never completes, but its socket
+    // is bound and marked while it gathers
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_dscp(DSCP);
+    config.set_bind_address(IpAddr::V6(Ipv6Addr::LOCALHOST));
+    let url = answerer.url().to_owned();
+    let connecting = tokio::spawn(async move {
+        let _ = Socket::connect_with_config(&url, config).await;
+    });
+
+    let started = Instant::now();
+    let fd = loop {
+        if let Some(fd) = udp_socket(|addr| addr.ip() == Ipv6Addr::LOCALHOST) {
+            break fd;
+        }
+        assert!(
+            started.elapsed() < TIMEOUT,
+            "no ICE socket was bound to ::1"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(with_socket(fd, |socket| socket.tclass_v6().unwrap()), TOS);
+    connecting.abort();
+}
@@ -0,0 +1,42 @@
+// Checks that a DTLS MTU out of range is rejected before connecting, while the bounds of
+// the range connect
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::{connect_to, TIMEOUT};
+
+#[tokio::test]
+async fn mtu_out_of_range_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    for mtu in [0, 255, 1461] {
+        let mut config = SocketConfig::default();
+        config.set_dtls_mtu(mtu);
+        match Socket::connect_background_with_config(answerer.url(), config) {
+            Err(SocketConnectionError::InvalidDtlsMtu { mtu: rejected, .. }) => {
+                assert_eq!(rejected, mtu)
+            }
+            Err(err) => panic!("{} failed with {}", mtu, err),
+            Ok(_) => panic!("{} was accepted", mtu),
+        }
+    }
+}
+
+#[tokio::test]
+async fn mtu_bounds_connect() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    for mtu in [256, 1460] {
+        let mut config = SocketConfig::default();
+        config.set_dtls_mtu(mtu);
+        let mut socket_io = connect_to(&answerer, config).await;
+
+        socket_io.send(b"ping".to_vec().into()).await.unwrap();
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(
+            echo.as_deref(),
+            Some(&b"ping"[..]),
+            "with an MTU of {}",
+            mtu
+        );
+        socket_io.close().await;
+    }
+}
@@ -0,0 +1,36 @@
+// Checks that a Socket connected to an EchoAnswerer gets back every payload it sends,
+// unchanged, and that the answerer serves several clients at once
+
+use webrtc_unreliable_client::{EchoAnswerer, SocketConfig, SocketIo};
+
+use crate::common::{connect, connect_to, TIMEOUT};
+
+// round_trip sends `payload` and checks that the echo is identical
+async fn round_trip(socket_io: &mut SocketIo, payload: &[u8]) {
+    socket_io.send(payload.into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(payload));
+}
+
+#[tokio::test]
+async fn payload_round_trips() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+
+    round_trip(&mut socket_io, b"hello").await;
+    let binary: Vec<u8> = (0..=255).collect();
+    round_trip(&mut socket_io, &binary).await;
+    let large = vec![0xa5; 4096];
+    round_trip(&mut socket_io, &large).await;
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn clients_are_echoed_separately() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut first = connect_to(&answerer, SocketConfig::default()).await;
+    let mut second = connect_to(&answerer, SocketConfig::default()).await;
+
+    round_trip(&mut first, b"first").await;
+    round_trip(&mut second, b"second").await;
+    round_trip(&mut first, b"first again").await;
+}
@@ -0,0 +1,56 @@
+// Checks that zero-length messages are sent and received as distinct empty messages, not
+// dropped or merged with the messages around them
+
+use std::{sync::Arc, time::Duration};
+
+use webrtc_unreliable_client::{Compression, SocketConfig, SocketIo};
+
+use crate::common::{connect, TIMEOUT};
+
+struct Identity;
+
+impl Compression for Identity {
+    fn compress(&self, input: &[u8]) -> Vec<u8> {
+        input.to_vec()
+    }
+
+    fn decompress(&self, input: &[u8]) -> Option<Vec<u8>> {
+        Some(input.to_vec())
+    }
+}
+
+// echo_around_empty sends an empty message between two others, and checks that the three
+// are echoed back in order
+async fn echo_around_empty(socket_io: &mut SocketIo) {
+    socket_io.send(b"before".to_vec().into()).await.unwrap();
+    socket_io.send(Vec::new().into()).await.unwrap();
+    socket_io.send(b"after".to_vec().into()).await.unwrap();
+
+    for expected in [&b"before"[..], &b""[..], &b"after"[..]] {
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(echo.as_deref(), Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn empty_message_is_echoed() {
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    let (_answerer, mut socket_io) = connect(config).await;
+
+    echo_around_empty(&mut socket_io).await;
+    socket_io.send_reliable(&[]).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b""[..]));
+}
+
+#[tokio::test]
+async fn empty_message_survives_framing() {
+    let mut config = SocketConfig::default();
+    config.set_send_coalescing(Duration::from_millis(50), 1200);
+    config.set_receive_deframing(true);
+    config.set_compression(Arc::new(Identity), 0);
+    let (_answerer, mut socket_io) = connect(config).await;
+
+    echo_around_empty(&mut socket_io).await;
+}
@@ -0,0 +1,53 @@
+// Checks that an exported session survives the round trip through its blob, and that a
+// reconnection importing it offers the same credentials and still echoes
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, ExportedSession, SessionImportError, SocketConfig};
+
+use crate::common::{connect_to, TIMEOUT};
+
+#[tokio::test]
+async fn reconnects_with_an_imported_session() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let socket_io = connect_to(&answerer, SocketConfig::default()).await;
+    let exported = socket_io.export_session().expect("no session to export");
+    let blob = exported.to_bytes();
+    socket_io.close().await;
+
+    let imported = ExportedSession::from_bytes(&blob).unwrap();
+    assert_eq!(imported.candidate_pair(), exported.candidate_pair());
+    let credentials = imported.credentials();
+    assert_eq!(credentials.ice_ufrag(), exported.credentials().ice_ufrag());
+    assert_eq!(
+        credentials.certificate().fingerprint(),
+        exported.credentials().certificate().fingerprint()
+    );
+    assert!(credentials.age() >= exported.credentials().age());
+
+    let mut config = SocketConfig::default();
+    config.set_exported_session(imported, TIMEOUT, Duration::from_secs(60));
+    let mut socket_io = connect_to(&answerer, config).await;
+    let offer = socket_io.local_description().unwrap();
+    assert_eq!(offer.ice_ufrag, exported.credentials().ice_ufrag());
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+}
+
+#[tokio::test]
+async fn rejects_a_blob_it_cant_read() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let socket_io = connect_to(&answerer, SocketConfig::default()).await;
+    let mut blob = socket_io.export_session().unwrap().to_bytes();
+
+    assert_eq!(
+        ExportedSession::from_bytes(&blob[..blob.len() - 1]).err(),
+        Some(SessionImportError::Malformed)
+    );
+    blob[0] = 0;
+    assert_eq!(
+        ExportedSession::from_bytes(&blob).err(),
+        Some(SessionImportError::UnsupportedVersion(0))
+    );
+}
@@ -0,0 +1,54 @@
+// Checks that the offer falls over to the next signaling endpoint when one doesn't answer,
+// and that the error of the last one is returned when none does
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::TIMEOUT;
+
+// a url nothing answers on, once the answerer is gone
+async fn dead_url() -> String {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    answerer.url().to_owned()
+}
+
+fn config(fallback_server_urls: &[&str]) -> SocketConfig {
+    let mut config = SocketConfig::default();
+    config.set_signaling_retry(1, Duration::from_millis(10), Duration::from_millis(10));
+    config.set_fallback_server_urls(fallback_server_urls);
+    config
+}
+
+#[tokio::test]
+async fn falls_over_to_the_next_endpoint() {
+    let primary_url = dead_url().await;
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (_, mut socket_io) = tokio::time::timeout(
+        TIMEOUT,
+        Socket::connect_with_config(&primary_url, config(&[answerer.url()])),
+    )
+    .await
+    .expect("the connection attempt didn't end")
+    .unwrap();
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+}
+
+#[tokio::test]
+async fn fails_when_no_endpoint_answers() {
+    let primary_url = dead_url().await;
+    let fallback_url = dead_url().await;
+    let result = tokio::time::timeout(
+        TIMEOUT,
+        Socket::connect_with_config(&primary_url, config(&[&fallback_url])),
+    )
+    .await
+    .expect("the connection attempt didn't end");
+    assert!(matches!(
+        result,
+        Err(SocketConnectionError::Signaling { .. })
+    ));
+}
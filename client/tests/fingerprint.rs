@@ -0,0 +1,62 @@
+// Checks that the DTLS handshake only completes with the certificate whose fingerprint the
+// server advertised in its answer
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketIo};
+
+use crate::common::{signaling_proxy, TIMEOUT};
+
+const FINGERPRINT_PREFIX: &str = "a=fingerprint:sha-256 ";
+
+// mismatch_fingerprint changes the first byte of the fingerprint in the answer
+fn mismatch_fingerprint(answer: &str) -> String {
+    let start = answer
+        .find(FINGERPRINT_PREFIX)
+        .expect("the answer has no sha-256 fingerprint")
+        + FINGERPRINT_PREFIX.len();
+    let byte = &answer[start..start + 2];
+    let wrong_byte = if byte.eq_ignore_ascii_case("00") {
+        "01"
+    } else {
+        "00"
+    };
+    format!("{}{}{}", &answer[..start], wrong_byte, &answer[start + 2..])
+}
+
+// dtls_connected waits up to `timeout` for the DTLS handshake to complete
+async fn dtls_connected(socket_io: &SocketIo, timeout: Duration) -> bool {
+    tokio::time::timeout(timeout, async {
+        while socket_io.handshake_timings().dtls_handshake.is_none() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .is_ok()
+}
+
+#[tokio::test]
+async fn handshake_completes_with_the_advertised_certificate() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let url = signaling_proxy(&answerer, |answer| answer).await;
+
+    let (_, socket_io) = Socket::connect_with_config(&url, SocketConfig::default())
+        .await
+        .unwrap();
+    assert!(dtls_connected(&socket_io, TIMEOUT).await);
+    assert!(socket_io.remote_dtls_fingerprint().is_some());
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn handshake_fails_with_a_mismatched_fingerprint() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let url = signaling_proxy(&answerer, |answer| mismatch_fingerprint(&answer)).await;
+
+    let (_, socket_io) = Socket::connect_with_config(&url, SocketConfig::default())
+        .await
+        .unwrap();
+    assert!(!dtls_connected(&socket_io, Duration::from_secs(3)).await);
+    assert_eq!(socket_io.remote_dtls_fingerprint(), None);
+    socket_io.close().await;
+}
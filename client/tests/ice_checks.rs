@@ -0,0 +1,130 @@
+// Checks that ICE connectivity checks are paced and retried as set with
+// SocketConfig::set_ice_checks, and that settings out of range are rejected
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use tokio::net::UdpSocket;
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::signaling_proxy;
+
+const INTERVAL: Duration = Duration::from_millis(300);
+const RETRIES: u16 = 3;
+// leeway for the scheduling of the checks and of the socket recording them
+const TOLERANCE: Duration = Duration::from_millis(30);
+
+// silent_signaling serves the signaling endpoint of `answerer` on a url of its own, with
+// the candidate of every answer moved to the port of `silent`, which never answers
+async fn silent_signaling(answerer: &EchoAnswerer, silent: &UdpSocket) -> String {
+    let silent_port = silent.local_addr().unwrap().port();
+    signaling_proxy(answerer, move |answer| {
+        // the candidate ends with "<ip> <port> typ host"
+        let typ = answer.find(" typ host").unwrap();
+        let port_start = answer[..typ].rfind(' ').unwrap();
+        format!(
+            "{} {}{}",
+            &answer[..port_start],
+            silent_port,
+            &answer[typ..]
+        )
+    })
+    .await
+}
+
+// record_checks connects with `config` to a candidate which never answers, and returns the
+// times the checks arrived at, by the address they were sent from, once none came for
+// `quiet`
+async fn record_checks(config: SocketConfig, quiet: Duration) -> HashMap<SocketAddr, Vec<Instant>> {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let url = silent_signaling(&answerer, &silent).await;
+    let connecting = tokio::spawn(async move {
+        let _ = Socket::connect_with_config(&url, config).await;
+    });
+
+    let mut checks: HashMap<SocketAddr, Vec<Instant>> = HashMap::new();
+    let mut buf = [0u8; 1500];
+    while let Ok(received) = tokio::time::timeout(quiet, silent.recv_from(&mut buf)).await {
+        let (n, from) = received.unwrap();
+        // STUN binding requests, of message type 0x0001
+        if n >= 20 && buf[..2] == [0, 1] {
+            checks.entry(from).or_default().push(Instant::now());
+        }
+    }
+    connecting.abort();
+    checks
+}
+
+#[tokio::test]
+async fn checks_are_paced_and_retried() {
+    let mut config = SocketConfig::default();
+    config.set_ice_checks(INTERVAL, RETRIES);
+    let checks = record_checks(config, 3 * INTERVAL).await;
+
+    assert!(!checks.is_empty(), "no checks arrived");
+    for (from, times) in checks {
+        // the first check, then the retries
+        assert_eq!(times.len(), RETRIES as usize + 1, "checks from {}", from);
+        for pair in times.windows(2) {
+            let gap = pair[1] - pair[0];
+            assert!(
+                gap + TOLERANCE >= INTERVAL,
+                "checks from {} came {:?} apart",
+                from,
+                gap
+            );
+        }
+    }
+}
+
+// check_ice_checks starts connecting with `interval` and `retries`, and returns the error
+// the settings are rejected with, if any
+async fn check_ice_checks(interval: Duration, retries: u16) -> Option<String> {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_ice_checks(interval, retries);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Ok(_) => None,
+        Err(SocketConnectionError::InvalidIceChecks { reason }) => Some(reason),
+        Err(err) => panic!("{:?} and {} retries failed with {}", interval, retries, err),
+    }
+}
+
+#[tokio::test]
+async fn interval_bounds_are_accepted() {
+    assert_eq!(check_ice_checks(Duration::from_millis(20), 1).await, None);
+    assert_eq!(check_ice_checks(Duration::from_secs(5), 1).await, None);
+}
+
+#[tokio::test]
+async fn interval_below_min_is_rejected() {
+    let reason = check_ice_checks(Duration::from_millis(19), 7)
+        .await
+        .unwrap();
+    assert!(
+        reason.contains("the interval must be between"),
+        "{}",
+        reason
+    );
+}
+
+#[tokio::test]
+async fn interval_above_max_is_rejected() {
+    let interval = Duration::from_secs(5) + Duration::from_millis(1);
+    let reason = check_ice_checks(interval, 7).await.unwrap();
+    assert!(
+        reason.contains("the interval must be between"),
+        "{}",
+        reason
+    );
+}
+
+#[tokio::test]
+async fn zero_retries_are_rejected() {
+    let reason = check_ice_checks(INTERVAL, 0).await.unwrap();
+    assert!(reason.contains("retried at least once"), "{}", reason);
+}
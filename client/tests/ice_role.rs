@@ -0,0 +1,48 @@
+// Checks that the ICE agent is controlling by default, as the offering side, and takes the
+// role forced in the config instead
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, IceRole, Socket, SocketConfig, SocketIo};
+
+use crate::common::{connect, TIMEOUT};
+
+// ice_role waits for the connectivity checks to start and returns the role taken
+async fn ice_role(socket_io: &SocketIo) -> IceRole {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Some(role) = socket_io.ice_role() {
+                return role;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("the connectivity checks didn't start")
+}
+
+#[tokio::test]
+async fn controlling_by_default() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    assert_eq!(socket_io.ice_role(), Some(IceRole::Controlling));
+}
+
+#[tokio::test]
+async fn forced_role_is_taken() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_ice_role(IceRole::Controlled);
+    let (_, socket_io) = Socket::connect_with_config(answerer.url(), config)
+        .await
+        .unwrap();
+
+    assert_eq!(ice_role(&socket_io).await, IceRole::Controlled);
+    // the answerer is ICE-lite and never nominates, so no pair gets selected
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(socket_io.selected_candidate_pair(), None);
+    socket_io.close().await;
+}
@@ -0,0 +1,87 @@
+// Checks that a connection closes once idle, and that pings only keep it open when they count
+// as activity
+
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use webrtc_unreliable_client::{EchoAnswerer, SocketConfig, SocketEvent, SocketIo};
+
+use crate::common::connect_to;
+
+const IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// connect connects to `answerer` with an idle timeout, subscribed to the events before the
+// timeout can elapse
+async fn connect(
+    answerer: &EchoAnswerer,
+    pings_are_activity: bool,
+) -> (SocketIo, broadcast::Receiver<SocketEvent>) {
+    let mut config = SocketConfig::default();
+    config.set_idle_timeout(IDLE_TIMEOUT, pings_are_activity);
+    let socket_io = connect_to(answerer, config).await;
+    let events = socket_io.events();
+    (socket_io, events)
+}
+
+// pinged_for pings every tenth of the idle timeout for `duration`, returning whether the
+// connection timed out meanwhile
+async fn pinged_for(
+    socket_io: &SocketIo,
+    events: &mut broadcast::Receiver<SocketEvent>,
+    duration: Duration,
+) -> bool {
+    let until = tokio::time::Instant::now() + duration;
+    while tokio::time::Instant::now() < until {
+        let _ = socket_io.ping(IDLE_TIMEOUT / 10).await;
+        tokio::time::sleep(IDLE_TIMEOUT / 10).await;
+        while let Ok(event) = events.try_recv() {
+            if event == SocketEvent::IdleTimedOut {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[tokio::test]
+async fn idle_connection_closes() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (mut socket_io, mut events) = connect(&answerer, true).await;
+
+    // messages keep the connection open
+    for _ in 0..10 {
+        socket_io.send(b"hello".to_vec().into()).await.unwrap();
+        let echo = socket_io.recv_timeout(IDLE_TIMEOUT).await;
+        assert!(matches!(echo, Ok(Some(_))));
+        tokio::time::sleep(IDLE_TIMEOUT / 5).await;
+    }
+
+    let timed_out = tokio::time::timeout(IDLE_TIMEOUT * 4, async {
+        loop {
+            match events.recv().await {
+                Ok(SocketEvent::IdleTimedOut) => return,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => panic!("the events ended"),
+            }
+        }
+    })
+    .await;
+    assert!(timed_out.is_ok(), "the connection didn't time out");
+    // closed like any other teardown
+    assert!(matches!(
+        socket_io.recv_timeout(IDLE_TIMEOUT).await,
+        Ok(None)
+    ));
+}
+
+#[tokio::test]
+async fn pings_count_as_activity_if_configured() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+
+    let (socket_io, mut events) = connect(&answerer, true).await;
+    assert!(!pinged_for(&socket_io, &mut events, IDLE_TIMEOUT * 3).await);
+    socket_io.close().await;
+
+    let (socket_io, mut events) = connect(&answerer, false).await;
+    assert!(pinged_for(&socket_io, &mut events, IDLE_TIMEOUT * 3).await);
+}
@@ -0,0 +1,56 @@
+// Checks that connecting fails with IncompatibleAnswer, instead of stalling the handshake,
+// when the server's answer doesn't match the offer
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConnectionError};
+
+use crate::common::{signaling_proxy, TIMEOUT};
+
+// connect_rewritten connects through a proxy replacing `from` with `to` in the answer, and
+// returns the reason the answer was found incompatible
+async fn connect_rewritten(from: &'static str, to: &'static str) -> String {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let url = signaling_proxy(&answerer, move |answer| {
+        assert!(answer.contains(from), "the answer has no {:?}", from);
+        answer.replace(from, to)
+    })
+    .await;
+    match Socket::connect(&url).await {
+        Err(SocketConnectionError::IncompatibleAnswer { reason }) => reason,
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(_) => panic!("connected with an incompatible answer"),
+    }
+}
+
+#[tokio::test]
+async fn rejects_a_different_media_type() {
+    let reason = connect_rewritten("m=application ", "m=audio ").await;
+    assert!(reason.contains("`audio`"), "{}", reason);
+}
+
+#[tokio::test]
+async fn rejects_a_setup_role_not_answering_the_offer() {
+    let reason = connect_rewritten("a=setup:passive", "a=setup:actpass").await;
+    assert!(reason.contains("setup role `actpass`"), "{}", reason);
+}
+
+#[tokio::test]
+async fn rejects_a_missing_fingerprint() {
+    let reason = connect_rewritten("a=fingerprint:", "a=x-fingerprint:").await;
+    assert!(reason.contains("fingerprint"), "{}", reason);
+}
+
+#[tokio::test]
+async fn rejects_missing_ice_credentials() {
+    let reason = connect_rewritten("a=ice-pwd:", "a=x-ice-pwd:").await;
+    assert!(reason.contains("a=ice-pwd"), "{}", reason);
+}
+
+#[tokio::test]
+async fn accepts_a_matching_answer() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let url = signaling_proxy(&answerer, |answer| answer).await;
+    let (_, mut socket_io) = Socket::connect(&url).await.unwrap();
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+}
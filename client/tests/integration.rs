@@ -0,0 +1,54 @@
+// The tests which connect to an EchoAnswerer, built as a single test binary instead of one
+// per file
+
+mod common;
+
+mod background;
+mod blocking;
+mod cached_pair;
+mod cancel;
+mod candidates_stream;
+mod close_send;
+#[cfg(feature = "lz4")]
+mod compression;
+mod credentials;
+#[cfg(feature = "deterministic-rng")]
+mod deterministic_rng;
+mod drain;
+mod dscp;
+mod dtls_mtu;
+mod echo;
+mod empty_message;
+mod export_session;
+mod failover;
+mod fingerprint;
+mod ice_checks;
+mod ice_role;
+mod idle_timeout;
+mod incompatible_answer;
+mod large_transfer;
+mod message_size;
+mod offer;
+mod oversized;
+#[cfg(feature = "network-conditioner")]
+mod packet_loss;
+mod payload_type;
+mod ping;
+mod probe;
+mod psk;
+mod reliable_in_flight;
+mod restart_ice;
+mod rtt_history;
+mod sctp_association_events;
+mod sctp_port;
+mod sctp_rto;
+mod send_timeout;
+mod signaling_proxy;
+mod signaling_request;
+mod signaling_retry;
+mod signaling_timeout;
+mod split;
+mod stats;
+mod udp_buffer_size;
+mod user_agent;
+mod whip;
@@ -0,0 +1,60 @@
+// Checks that payloads sent with SocketIo::send_large come back whole from a server which
+// echoes the chunks of the large transfer data channel
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::time::timeout;
+use webrtc_unreliable_client::{LargeTransferError, SocketConfig, SocketConnectionError};
+
+use crate::common::connect;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn payload(len: usize, seed: u8) -> Bytes {
+    (0..len)
+        .map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed))
+        .collect()
+}
+
+#[tokio::test]
+async fn large_payloads_round_trip() {
+    let mut config = SocketConfig::default();
+    config.set_large_transfers(true);
+    let (_answerer, socket_io) = connect(config).await;
+    let (tx, mut rx) = socket_io.split();
+
+    let payloads = vec![payload(3 << 20, 1), Bytes::new(), payload(1500, 2)];
+    let sent = payloads.clone();
+    let sender = tx.clone();
+    tokio::spawn(async move {
+        for payload in sent {
+            sender.send_large(payload).await.unwrap();
+        }
+    });
+    // the unreliable data channel keeps working meanwhile
+    tx.send(vec![7; 100].into_boxed_slice()).await.unwrap();
+    let echo = rx.recv_timeout(TIMEOUT).await.unwrap().unwrap();
+    assert_eq!(*echo, [7; 100]);
+
+    for payload in payloads {
+        let echo = timeout(TIMEOUT, rx.recv_large()).await.unwrap().unwrap();
+        assert_eq!(echo, Some(payload));
+    }
+}
+
+#[tokio::test]
+async fn large_transfers_need_enabling() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+
+    assert!(matches!(
+        socket_io.send_large(payload(10, 0)).await,
+        Err(SocketConnectionError::NoLargeTransfers)
+    ));
+    assert!(matches!(
+        socket_io.recv_large().await,
+        Err(LargeTransferError::Connection(
+            SocketConnectionError::NoLargeTransfers
+        ))
+    ));
+}
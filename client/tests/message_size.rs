@@ -0,0 +1,32 @@
+// Checks that a message one byte over max_message_size is rejected with MessageTooLarge,
+// while one of exactly max_message_size bytes is sent
+
+use webrtc_unreliable_client::{SocketConfig, SocketConnectionError};
+
+use crate::common::{connect, TIMEOUT};
+
+#[tokio::test]
+async fn oversized_message_is_rejected() {
+    // the limit is only final once the data channel has opened, which connect waits for
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+    let max = socket_io.max_message_size();
+
+    let oversized = vec![0; max + 1];
+    match socket_io.send(oversized.clone().into()).await {
+        Err(SocketConnectionError::MessageTooLarge { size, max: limit }) => {
+            assert_eq!(size, max + 1);
+            assert_eq!(limit, max);
+        }
+        result => panic!("sending {} bytes returned {:?}", max + 1, result),
+    }
+    assert!(matches!(
+        socket_io.try_send(&oversized),
+        Err(SocketConnectionError::MessageTooLarge { size, max: limit })
+            if size == max + 1 && limit == max
+    ));
+
+    let largest = vec![7; max];
+    socket_io.send(largest.clone().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&largest[..]));
+}
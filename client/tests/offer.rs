@@ -0,0 +1,128 @@
+// Checks that the offer made for a connection is one browsers and webrtc-unreliable servers
+// accept: a single data channel media section with the ICE and DTLS attributes they need,
+// and that the external IP of a 1:1 NAT is offered in place of the local address
+
+use std::{future::poll_fn, net::Ipv4Addr, pin::Pin};
+
+use futures_core::Stream;
+use webrtc_unreliable_client::{LocalDescription, SocketConfig};
+
+use crate::common::{connect, TIMEOUT};
+
+// offer connects to an EchoAnswerer with `config` and returns the offer it was sent
+async fn offer(config: SocketConfig) -> LocalDescription {
+    let (_answerer, socket_io) = connect(config).await;
+    let description = socket_io.local_description().expect("no offer was made");
+    socket_io.close().await;
+    description
+}
+
+// attributes returns the values of the `a=<key>:` lines of an SDP
+fn attributes<'a>(sdp: &'a str, key: &str) -> Vec<&'a str> {
+    let prefix = format!("a={}:", key);
+    sdp.lines()
+        .filter_map(|line| line.strip_prefix(prefix.as_str()))
+        .collect()
+}
+
+fn assert_data_channel_offer(description: &LocalDescription) {
+    let sdp = &description.sdp;
+    assert!(sdp.starts_with("v=0\r\n"), "not an SDP: {:?}", sdp);
+    assert!(
+        sdp.lines().all(|line| !line.is_empty()),
+        "empty line in {:?}",
+        sdp
+    );
+
+    assert_eq!(description.media.len(), 1, "{:?}", description.media);
+    let media = &description.media[0];
+    assert_eq!(media.kind, "application");
+    assert_eq!(media.protocol, "UDP/DTLS/SCTP");
+    assert_eq!(media.formats, ["webrtc-datachannel"]);
+    assert!(media.mid.is_some(), "the media section has no a=mid");
+    assert_eq!(
+        sdp.lines()
+            .filter(|line| line.starts_with("m=application "))
+            .count(),
+        1
+    );
+
+    assert_eq!(attributes(sdp, "sctp-port"), ["5000"]);
+    assert_eq!(attributes(sdp, "setup"), ["actpass"]);
+
+    assert!(!description.ice_ufrag.is_empty());
+    assert!(!description.ice_pwd.is_empty());
+    assert_eq!(
+        attributes(sdp, "ice-ufrag"),
+        [description.ice_ufrag.as_str()]
+    );
+    assert_eq!(attributes(sdp, "ice-pwd"), [description.ice_pwd.as_str()]);
+
+    let (hash, fingerprint) = description
+        .fingerprint
+        .split_once(' ')
+        .expect("the fingerprint has no hash function");
+    assert_eq!(hash, "sha-256");
+    let bytes: Vec<&str> = fingerprint.split(':').collect();
+    assert_eq!(bytes.len(), 32, "{}", fingerprint);
+    assert!(bytes
+        .iter()
+        .all(|byte| byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit())));
+
+    assert_eq!(
+        attributes(sdp, "candidate").len(),
+        description.candidates.len()
+    );
+}
+
+#[tokio::test]
+async fn default_offer_is_a_data_channel_offer() {
+    let description = offer(SocketConfig::default()).await;
+    assert_data_channel_offer(&description);
+}
+
+#[tokio::test]
+async fn offer_with_reliable_channel_keeps_a_single_media_section() {
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    let description = offer(config).await;
+    assert_data_channel_offer(&description);
+}
+
+#[tokio::test]
+async fn nat_1to1_ip_replaces_the_host_candidate_address() {
+    const EXTERNAL_IP: &str = "203.0.113.9";
+    let mut config = SocketConfig::default();
+    config.set_nat_1to1_ips(vec![EXTERNAL_IP.to_owned()]);
+    // the offer doesn't wait for gathering, so the later candidates are trickled
+    let mut stream = config.candidates_stream();
+    let description = offer(config).await;
+    assert_data_channel_offer(&description);
+
+    // candidate:<foundation> <component> <transport> <priority> <address> <port> typ host
+    let mut addresses = Vec::new();
+    for candidate in &description.candidates {
+        let fields: Vec<&str> = candidate.split_whitespace().collect();
+        assert_eq!(fields.get(7), Some(&"host"), "{}", candidate);
+        addresses.extend(fields.get(4).map(|address| address.to_string()));
+    }
+    while let Some(candidate) =
+        tokio::time::timeout(TIMEOUT, poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)))
+            .await
+            .expect("gathering didn't complete")
+    {
+        addresses.push(candidate.address);
+    }
+    // the external IP is used for the local IPs of its family only
+    assert!(
+        addresses.iter().any(|address| address == EXTERNAL_IP),
+        "{} isn't among {:?}",
+        EXTERNAL_IP,
+        addresses
+    );
+    for address in &addresses {
+        if address.parse::<Ipv4Addr>().is_ok() {
+            assert_eq!(address, EXTERNAL_IP);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// Checks that a datagram larger than the path MTU is received in full, and that one larger
+// than the receive buffers is dropped and counted rather than handed on cut short
+
+use std::time::Duration;
+
+use tokio::net::UdpSocket;
+use webrtc_unreliable_client::{SocketConfig, SocketIo};
+
+use crate::common::{connect, TIMEOUT};
+
+// send_datagram sends a datagram of len bytes to the local candidate of the selected pair,
+// and waits for the socket to count its bytes
+async fn send_datagram(socket_io: &SocketIo, len: usize) {
+    let local = socket_io.selected_candidate_pair().unwrap().local;
+    let before = socket_io.stats().wire_bytes_received;
+    let stranger = UdpSocket::bind((local.ip(), 0)).await.unwrap();
+    stranger.send_to(&vec![0xaa; len], local).await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while socket_io.stats().wire_bytes_received == before {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the datagram wasn't received");
+}
+
+async fn assert_echoes(socket_io: &mut SocketIo) {
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+}
+
+#[tokio::test]
+async fn receives_a_datagram_over_the_path_mtu() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+
+    let before = socket_io.stats().wire_bytes_received;
+    send_datagram(&socket_io, 2000).await;
+    let stats = socket_io.stats();
+    assert!(stats.wire_bytes_received - before >= 2000);
+    assert_eq!(stats.oversized_datagrams_dropped, 0);
+    assert_echoes(&mut socket_io).await;
+}
+
+#[tokio::test]
+async fn drops_a_datagram_over_the_receive_buffer() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+
+    send_datagram(&socket_io, 9000).await;
+    assert_eq!(socket_io.stats().oversized_datagrams_dropped, 1);
+    assert_echoes(&mut socket_io).await;
+}
@@ -0,0 +1,66 @@
+// Checks that reliable messages are retransmitted until they arrive when the network
+// conditioner drops packets, while unreliable messages are lost along with their packets
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, NetworkConditioner, SocketConfig, SocketIo};
+
+use crate::common::{connect, TIMEOUT};
+
+const MESSAGES: usize = 100;
+const PACKET_LOSS: f64 = 0.3;
+
+async fn connect_lossy() -> (EchoAnswerer, SocketIo) {
+    let mut conditioner = NetworkConditioner::new(7);
+    conditioner.set_packet_loss(PACKET_LOSS);
+    let mut config = SocketConfig::default();
+    config.set_network_conditioner(conditioner);
+    config.set_reliable_channel(true);
+    // retransmit lost packets sooner than the default RTO.Min of a second
+    config.set_sctp_rto(
+        Duration::from_millis(200),
+        Duration::from_millis(100),
+        Duration::from_secs(1),
+    );
+    connect(config).await
+}
+
+fn message(i: usize) -> Vec<u8> {
+    format!("message {}", i).into_bytes()
+}
+
+#[tokio::test]
+async fn reliable_messages_survive_packet_loss() {
+    let (_answerer, mut socket_io) = connect_lossy().await;
+
+    for i in 0..MESSAGES {
+        socket_io.send_reliable(&message(i)).await.unwrap();
+    }
+    for i in 0..MESSAGES {
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(echo.as_deref(), Some(&message(i)[..]));
+    }
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn unreliable_messages_are_lost() {
+    let (_answerer, mut socket_io) = connect_lossy().await;
+    socket_io.ready().await.unwrap();
+
+    for i in 0..MESSAGES {
+        socket_io.send(message(i).into()).await.unwrap();
+    }
+    let mut received = 0;
+    while let Ok(echo) = socket_io.recv_timeout(Duration::from_secs(1)).await {
+        assert!(echo.is_some());
+        received += 1;
+    }
+    assert!(received > 0);
+    assert!(
+        received < MESSAGES,
+        "all {} messages arrived despite the packet loss",
+        MESSAGES
+    );
+    socket_io.close().await;
+}
@@ -0,0 +1,44 @@
+// Checks that messages are sent as the configured payload type, which the echo answerer
+// echoes back unchanged, and that received messages are delivered with their payload type
+
+use webrtc_unreliable_client::{EchoAnswerer, PayloadType, SocketConfig};
+
+use crate::common::{connect_to, TIMEOUT};
+
+// echoed_type sends a message as `payload_type` and returns the payload type of its echo
+async fn echoed_type(answerer: &EchoAnswerer, payload_type: Option<PayloadType>) -> PayloadType {
+    let mut config = SocketConfig::default();
+    if let Some(payload_type) = payload_type {
+        config.set_payload_type(payload_type);
+    }
+    config.set_payload_types(true);
+    let socket_io = connect_to(answerer, config).await;
+    socket_io.send(b"hello".to_vec().into()).await.unwrap();
+
+    let (_, mut socket_rx) = socket_io.split();
+    let (message, payload_type) = tokio::time::timeout(TIMEOUT, socket_rx.recv_typed())
+        .await
+        .expect("receiving timed out")
+        .expect("the data channel closed");
+    assert_eq!(&message[..], b"hello");
+    // the plain receiver is closed, as messages only go to the typed one
+    assert!(socket_rx.recv().await.is_none());
+    payload_type
+}
+
+#[tokio::test]
+async fn messages_are_binary_by_default() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    assert_eq!(echoed_type(&answerer, None).await, PayloadType::Binary);
+}
+
+#[tokio::test]
+async fn messages_are_sent_as_the_configured_type() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    assert_eq!(
+        echoed_type(&answerer, Some(PayloadType::String)).await,
+        PayloadType::String
+    );
+    assert_eq!(PayloadType::String.ppid(), 51);
+    assert_eq!(PayloadType::Binary.ppid(), 53);
+}
@@ -0,0 +1,33 @@
+// Checks that a ping is answered by the SCTP association of the server, without reaching the
+// message streams, and while reading is paused
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::SocketConfig;
+
+use crate::common::{connect, TIMEOUT};
+
+#[tokio::test]
+async fn ping_leaves_messages_alone() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+
+    socket_io.send(b"hello".to_vec().into()).await.unwrap();
+    let rtt = socket_io.ping(TIMEOUT).await.unwrap();
+    assert!(rtt < TIMEOUT);
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"hello"[..]));
+    assert!(socket_io
+        .recv_timeout(Duration::from_millis(200))
+        .await
+        .is_err());
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn ping_completes_while_reading_is_paused() {
+    let (_answerer, socket_io) = connect(SocketConfig::default()).await;
+
+    socket_io.pause_recv();
+    socket_io.ping(TIMEOUT).await.unwrap();
+    socket_io.close().await;
+}
@@ -0,0 +1,51 @@
+// Checks that a probe reports a connection which opened its data channel, and one which
+// got stuck, along with where it got stuck
+
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use webrtc_unreliable_client::{probe, EchoAnswerer, HandshakePhase, ProbeError, SocketConfig};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test]
+async fn probe_reports_an_open_connection() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+
+    let result = probe(answerer.url(), SocketConfig::default(), PROBE_TIMEOUT, true)
+        .await
+        .unwrap();
+    assert!(result.timings.sctp_association.is_some());
+    assert!(result.timings.total.is_some());
+    assert!(result.candidate_pair.is_some());
+    assert_eq!(result.local_candidate_type.as_deref(), Some("host"));
+    assert!(result.ping.is_some());
+}
+
+#[tokio::test]
+async fn probe_times_out_in_the_stuck_phase() {
+    // accepts the offer's connection, but never answers it
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/rtc_session", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut streams = Vec::new();
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            streams.push(stream);
+        }
+    });
+
+    let err = probe(&url, SocketConfig::default(), Duration::from_secs(1), false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProbeError::Timeout {
+                phase: Some(HandshakePhase::Signaling)
+            }
+        ),
+        "{:?}",
+        err
+    );
+}
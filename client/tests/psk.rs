@@ -0,0 +1,59 @@
+// Checks that the DTLS handshake completes with a pre-shared key both ends agree on, and
+// fails rather than falling back to certificates otherwise
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketIo};
+
+use crate::common::TIMEOUT;
+
+const IDENTITY: &[u8] = b"client-1";
+const KEY: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+async fn connect_with_psk(answerer: &EchoAnswerer, key: &[u8]) -> SocketIo {
+    let mut config = SocketConfig::default();
+    config.set_dtls_psk(IDENTITY, key);
+    let (_, socket_io) = Socket::connect_with_config(answerer.url(), config)
+        .await
+        .unwrap();
+    socket_io
+}
+
+// dtls_connected waits up to `timeout` for the DTLS handshake to complete
+async fn dtls_connected(socket_io: &SocketIo, timeout: Duration) -> bool {
+    tokio::time::timeout(timeout, async {
+        while socket_io.handshake_timings().dtls_handshake.is_none() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .is_ok()
+}
+
+#[tokio::test]
+async fn handshake_completes_with_the_shared_key() {
+    let answerer = EchoAnswerer::start_with_psk(IDENTITY, KEY).await.unwrap();
+    let mut socket_io = connect_with_psk(&answerer, KEY).await;
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    // no certificate was exchanged
+    assert_eq!(socket_io.remote_dtls_fingerprint(), None);
+}
+
+#[tokio::test]
+async fn handshake_fails_with_a_different_key() {
+    let answerer = EchoAnswerer::start_with_psk(IDENTITY, KEY).await.unwrap();
+    let socket_io = connect_with_psk(&answerer, b"fedcba9876543210fedcba9876543210").await;
+    assert!(!dtls_connected(&socket_io, Duration::from_secs(3)).await);
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn handshake_fails_with_a_certificate_server() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let socket_io = connect_with_psk(&answerer, KEY).await;
+    assert!(!dtls_connected(&socket_io, Duration::from_secs(3)).await);
+    socket_io.close().await;
+}
@@ -0,0 +1,69 @@
+// Checks that reading ICE datagrams in batches keeps the boundaries and the source address of
+// each datagram, when datagrams from two sources are queued together
+
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+use tokio::net::UdpSocket;
+use webrtc_unreliable_client::internals::recv_from_batch;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const BATCH_SIZE: usize = 16;
+const DATAGRAMS_PER_SOURCE: usize = 3;
+
+// datagram is the `i`th datagram sent by `source`, of a length all of its own
+fn datagram(source: u8, i: usize) -> Vec<u8> {
+    vec![source; 10 * (source as usize) + i + 1]
+}
+
+#[tokio::test]
+async fn batches_keep_datagram_boundaries_and_sources() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let receiver_addr = receiver.local_addr().unwrap();
+    let mut sources = HashMap::new();
+    let mut senders = Vec::new();
+    for source in [1u8, 2] {
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sources.insert(sender.local_addr().unwrap(), source);
+        senders.push((source, sender));
+    }
+    // interleave the two sources
+    for i in 0..DATAGRAMS_PER_SOURCE {
+        for (source, sender) in &senders {
+            sender
+                .send_to(&datagram(*source, i), receiver_addr)
+                .await
+                .unwrap();
+        }
+    }
+    // let them all queue up
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut bufs = vec![vec![0; 1500]; BATCH_SIZE];
+    let mut received = vec![(0, SocketAddr::from(([0, 0, 0, 0], 0))); BATCH_SIZE];
+    let mut next = HashMap::new();
+    let mut total = 0;
+    let mut batches = 0;
+    while total < 2 * DATAGRAMS_PER_SOURCE {
+        let n = tokio::time::timeout(
+            TIMEOUT,
+            recv_from_batch(&receiver, &mut bufs, &mut received),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(n > 0);
+        for (buf, &(len, addr)) in bufs.iter().zip(&received[..n]) {
+            let source = sources[&addr];
+            let i = next.entry(source).or_insert(0);
+            assert_eq!(&buf[..len], &datagram(source, *i)[..]);
+            *i += 1;
+        }
+        total += n;
+        batches += 1;
+    }
+    assert_eq!(total, 2 * DATAGRAMS_PER_SOURCE);
+    // recvmmsg reads everything queued at once
+    if cfg!(any(target_os = "linux", target_os = "android")) {
+        assert_eq!(batches, 1);
+    }
+}
@@ -0,0 +1,83 @@
+// Checks that a burst of reliable messages never has more of them written but
+// unacknowledged than set with SocketConfig::set_reliable_max_in_flight, and that a cap of
+// zero is rejected before connecting
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::{connect, TIMEOUT};
+
+const MAX_IN_FLIGHT: usize = 4;
+const MESSAGES: usize = 200;
+// several packets each, so that the messages take a while to be acknowledged
+const MESSAGE_SIZE: usize = 4000;
+
+// burst sends MESSAGES reliable messages at once, waits for their echoes, and returns the
+// most bytes seen written to the association but not acknowledged meanwhile
+async fn burst(max_in_flight: Option<usize>) -> usize {
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    if let Some(max_in_flight) = max_in_flight {
+        config.set_reliable_max_in_flight(max_in_flight);
+    }
+    let (_answerer, socket_io) = connect(config).await;
+    let (tx, mut rx) = socket_io.split();
+
+    let sender = tx.clone();
+    tokio::spawn(async move {
+        for i in 0..MESSAGES {
+            let message = vec![i as u8; MESSAGE_SIZE];
+            sender.send_reliable(&message).await.unwrap();
+        }
+    });
+    let mut echoed = tokio::spawn(async move {
+        for i in 0..MESSAGES {
+            let echo = rx.recv_timeout(TIMEOUT).await.unwrap().unwrap();
+            assert_eq!(*echo, vec![i as u8; MESSAGE_SIZE][..]);
+        }
+    });
+
+    let mut most_unacknowledged = 0;
+    loop {
+        tokio::select! {
+            result = &mut echoed => {
+                result.unwrap();
+                return most_unacknowledged;
+            }
+            _ = tokio::task::yield_now() => {
+                let congestion = tx.congestion().await.unwrap();
+                most_unacknowledged =
+                    most_unacknowledged.max(congestion.flight_size + congestion.pending);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn burst_stays_within_max_in_flight() {
+    let most_unacknowledged = burst(Some(MAX_IN_FLIGHT)).await;
+    assert!(
+        most_unacknowledged <= MAX_IN_FLIGHT * MESSAGE_SIZE,
+        "{} bytes were unacknowledged at once",
+        most_unacknowledged
+    );
+}
+
+#[tokio::test]
+async fn burst_without_cap_exceeds_it() {
+    // the burst is large enough that the cap above is tested at all
+    let most_unacknowledged = burst(None).await;
+    assert!(most_unacknowledged > MAX_IN_FLIGHT * MESSAGE_SIZE);
+}
+
+#[tokio::test]
+async fn zero_max_in_flight_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    config.set_reliable_max_in_flight(0);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Err(SocketConnectionError::InvalidReliableMaxInFlight) => {}
+        Err(err) => panic!("failed with {}", err),
+        Ok(_) => panic!("no messages in flight were accepted"),
+    }
+}
@@ -0,0 +1,28 @@
+// Checks that restart_ice connects again with a new DTLS certificate, while the same
+// SocketIo keeps sending and receiving
+
+use webrtc_unreliable_client::{SocketConfig, SocketIo};
+
+use crate::common::{connect, TIMEOUT};
+
+// round_trip sends `payload` and checks that the echo is identical
+async fn round_trip(socket_io: &mut SocketIo, payload: &[u8]) {
+    socket_io.send(payload.into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(payload));
+}
+
+#[tokio::test]
+async fn messages_are_echoed_across_a_restart() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+    round_trip(&mut socket_io, b"before").await;
+    let fingerprint = socket_io.local_dtls_fingerprint();
+
+    tokio::time::timeout(TIMEOUT, socket_io.restart_ice())
+        .await
+        .expect("restarting timed out")
+        .unwrap();
+    round_trip(&mut socket_io, b"after").await;
+    assert_ne!(socket_io.local_dtls_fingerprint(), fingerprint);
+    socket_io.close().await;
+}
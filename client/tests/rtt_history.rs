@@ -0,0 +1,41 @@
+// Checks that the round trip times of the connectivity checks on the selected candidate pair
+// are kept, up to the configured number
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, SocketConfig};
+
+use crate::common::connect_to;
+
+// long enough for two keepalives on an idle pair
+const KEEPALIVES: Duration = Duration::from_millis(4500);
+
+#[tokio::test]
+async fn keeps_the_latest_samples() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_rtt_history_size(2);
+    let socket_io = connect_to(&answerer, config).await;
+
+    // the check which nominated the pair
+    let nominated = socket_io.rtt_history();
+    assert_eq!(nominated.len(), 1);
+
+    tokio::time::sleep(KEEPALIVES).await;
+    let history = socket_io.rtt_history();
+    assert_eq!(history.len(), 2);
+    assert!(history[0].at > nominated[0].at);
+    assert!(history[1].at > history[0].at);
+    for sample in history {
+        assert!(sample.rtt < Duration::from_secs(1), "{:?}", sample);
+    }
+}
+
+#[tokio::test]
+async fn keeps_none_with_size_zero() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_rtt_history_size(0);
+    let socket_io = connect_to(&answerer, config).await;
+    assert!(socket_io.rtt_history().is_empty());
+}
@@ -0,0 +1,73 @@
+// Checks that the SCTP association reports coming up before the data channel opens, and
+// going down once the connection is closed
+
+use tokio::sync::broadcast;
+use webrtc_unreliable_client::{EchoAnswerer, HandshakePhase, Socket, SocketEvent};
+
+use crate::common::TIMEOUT;
+
+// next_event returns the next event other than handshake phases starting
+async fn next_event(events: &mut broadcast::Receiver<SocketEvent>) -> SocketEvent {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            match events.recv().await.unwrap() {
+                SocketEvent::HandshakePhaseStarted { .. } => {}
+                event => return event,
+            }
+        }
+    })
+    .await
+    .expect("no event was emitted")
+}
+
+#[tokio::test]
+async fn up_before_the_data_channel_opens() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (_, socket_io) = Socket::connect_background(answerer.url()).unwrap();
+    let mut events = socket_io.events();
+
+    loop {
+        match next_event(&mut events).await {
+            SocketEvent::HandshakePhaseFinished {
+                phase: HandshakePhase::SctpAssociation,
+                ..
+            } => panic!("the data channel opened before the association came up"),
+            SocketEvent::SctpAssociationUp { at } => {
+                match next_event(&mut events).await {
+                    SocketEvent::HandshakePhaseFinished {
+                        phase: HandshakePhase::SctpAssociation,
+                        at: opened_at,
+                    } => assert!(at <= opened_at),
+                    event => panic!("unexpected event {:?}", event),
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn down_once_closed() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (_, mut socket_io) = Socket::connect_background(answerer.url()).unwrap();
+    let mut events = socket_io.events();
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    socket_io.close().await;
+    let mut up = false;
+    loop {
+        match next_event(&mut events).await {
+            SocketEvent::SctpAssociationUp { .. } => {
+                assert!(!up, "the association came up twice");
+                up = true;
+            }
+            SocketEvent::SctpAssociationDown { .. } => break,
+            _ => {}
+        }
+    }
+    assert!(up, "the association went down without coming up");
+}
@@ -0,0 +1,26 @@
+// Checks that the reserved SCTP port 0 is rejected before connecting, while another port
+// is accepted
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::connect;
+
+#[tokio::test]
+async fn port_zero_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_sctp_port(0);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Err(SocketConnectionError::InvalidSctpPort { port }) => assert_eq!(port, 0),
+        Err(err) => panic!("failed with {}", err),
+        Ok(_) => panic!("SCTP port 0 was accepted"),
+    }
+}
+
+#[tokio::test]
+async fn other_ports_are_accepted() {
+    let mut config = SocketConfig::default();
+    config.set_sctp_port(5001);
+    let (_answerer, socket_io) = connect(config).await;
+    socket_io.close().await;
+}
@@ -0,0 +1,115 @@
+// Checks that the RTO bounds set with SocketConfig::set_sctp_rto reach the SCTP association,
+// and that bounds out of order are rejected
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{
+    EchoAnswerer, Socket, SocketConfig, SocketConnectionError, SocketIo,
+};
+
+use crate::common::{connect, TIMEOUT};
+
+const MESSAGES: usize = 20;
+
+async fn connect_with_rto(
+    initial: Duration,
+    min: Duration,
+    max: Duration,
+) -> (EchoAnswerer, SocketIo) {
+    let mut config = SocketConfig::default();
+    config.set_sctp_rto(initial, min, max);
+    connect(config).await
+}
+
+// echo_messages sends messages one at a time, so that the acknowledgements of each measure
+// the round trip time
+async fn echo_messages(socket_io: &mut SocketIo) {
+    for _ in 0..MESSAGES {
+        socket_io.send(b"measure".to_vec().into()).await.unwrap();
+        socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn rto_is_pinned_by_equal_bounds() {
+    let rto = Duration::from_millis(1234);
+    let (_answerer, mut socket_io) = connect_with_rto(rto, rto, rto).await;
+
+    echo_messages(&mut socket_io).await;
+    let congestion = socket_io.congestion().await.unwrap();
+    assert_eq!(congestion.rto, rto);
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn rto_is_raised_to_min() {
+    let min = Duration::from_millis(1500);
+    let (_answerer, mut socket_io) =
+        connect_with_rto(Duration::from_millis(2500), min, Duration::from_secs(5)).await;
+
+    // the round trip over loopback is far below min
+    echo_messages(&mut socket_io).await;
+    let congestion = socket_io.congestion().await.unwrap();
+    assert_eq!(congestion.rto, min);
+    socket_io.close().await;
+}
+
+// check_rto starts connecting with the RTO bounds, and returns the error they are
+// rejected with, if any
+async fn check_rto(initial: Duration, min: Duration, max: Duration) -> Option<String> {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_sctp_rto(initial, min, max);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Ok(_) => None,
+        Err(SocketConnectionError::InvalidSctpRto { reason }) => Some(reason),
+        Err(err) => panic!("{:?}, {:?}, {:?} failed with {}", initial, min, max, err),
+    }
+}
+
+#[tokio::test]
+async fn min_above_initial_is_rejected() {
+    let reason = check_rto(
+        Duration::from_secs(1),
+        Duration::from_secs(2),
+        Duration::from_secs(3),
+    )
+    .await
+    .unwrap();
+    assert!(reason.contains("min <= initial <= max"), "{}", reason);
+}
+
+#[tokio::test]
+async fn initial_above_max_is_rejected() {
+    let reason = check_rto(
+        Duration::from_secs(4),
+        Duration::from_secs(1),
+        Duration::from_secs(3),
+    )
+    .await
+    .unwrap();
+    assert!(reason.contains("min <= initial <= max"), "{}", reason);
+}
+
+#[tokio::test]
+async fn zero_min_is_rejected() {
+    let reason = check_rto(Duration::ZERO, Duration::ZERO, Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert!(reason.contains("at least a millisecond"), "{}", reason);
+}
+
+#[tokio::test]
+async fn ordered_bounds_are_accepted() {
+    let rto = Duration::from_secs(1);
+    assert_eq!(check_rto(rto, rto, rto).await, None);
+    assert_eq!(
+        check_rto(
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            Duration::from_secs(3),
+        )
+        .await,
+        None
+    );
+}
@@ -0,0 +1,56 @@
+// Checks that a reliable message whose timeout passes while it is queued is never sent while
+// the messages behind it still are, and that one sent in time arrives, coalescing included
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketIo};
+
+use crate::common::{connect, TIMEOUT};
+
+async fn assert_echoes(socket_io: &mut SocketIo, expected: &[&[u8]]) {
+    for &expected in expected {
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(echo.as_deref(), Some(expected));
+    }
+}
+
+#[tokio::test]
+async fn late_message_is_not_sent() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    let (_, mut socket_io) =
+        Socket::connect_background_with_config(answerer.url(), config).unwrap();
+
+    // queued until the data channels open, which takes longer than the timeout
+    socket_io.send_reliable(b"first").await.unwrap();
+    socket_io
+        .send_reliable_timeout(b"late", Duration::from_millis(1))
+        .await
+        .unwrap();
+    socket_io.send_reliable(b"next").await.unwrap();
+
+    tokio::time::timeout(TIMEOUT, socket_io.ready())
+        .await
+        .expect("the data channel didn't open")
+        .unwrap();
+    // reliable messages are echoed in order, so the late one would come in between
+    assert_echoes(&mut socket_io, &[b"first", b"next"]).await;
+}
+
+#[tokio::test]
+async fn message_in_time_is_sent_on_its_own() {
+    let mut config = SocketConfig::default();
+    config.set_reliable_channel(true);
+    config.set_send_coalescing(Duration::from_millis(50), 1200);
+    config.set_receive_deframing(true);
+    let (_answerer, mut socket_io) = connect(config).await;
+
+    socket_io.send_reliable(b"before").await.unwrap();
+    socket_io
+        .send_reliable_timeout(b"timed", TIMEOUT)
+        .await
+        .unwrap();
+    socket_io.send_reliable(b"after").await.unwrap();
+    assert_echoes(&mut socket_io, &[b"before", b"timed", b"after"]).await;
+}
@@ -0,0 +1,48 @@
+// Checks that malformed server urls are rejected before connecting, with the url and a
+// reason pointing at the mistake
+
+use webrtc_unreliable_client::{Socket, SocketConnectionError};
+
+// rejection connects to `server_url` and returns the reason it was rejected for
+async fn rejection(server_url: &str) -> String {
+    match Socket::connect(server_url).await {
+        Err(SocketConnectionError::InvalidServerUrl { url, reason }) => {
+            assert_eq!(url, server_url);
+            reason
+        }
+        Err(err) => panic!("{:?} failed with {}", server_url, err),
+        Ok(_) => panic!("{:?} was accepted", server_url),
+    }
+}
+
+#[tokio::test]
+async fn missing_scheme() {
+    let reason = rejection("localhost:3000").await;
+    assert!(reason.contains("http://localhost:3000"), "{}", reason);
+}
+
+#[tokio::test]
+async fn unsupported_scheme() {
+    let reason = rejection("ftp://host").await;
+    assert!(reason.contains("\"ftp\""), "{}", reason);
+}
+
+#[tokio::test]
+async fn missing_host() {
+    rejection("http://").await;
+}
+
+#[tokio::test]
+async fn unparsable() {
+    rejection("http://[::1").await;
+    rejection("not a url").await;
+    rejection("").await;
+}
+
+#[tokio::test]
+async fn invalid_url_in_background() {
+    assert!(matches!(
+        Socket::connect_background("localhost:3000"),
+        Err(SocketConnectionError::InvalidServerUrl { .. })
+    ));
+}
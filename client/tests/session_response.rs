@@ -0,0 +1,95 @@
+// Checks that both parsers of the server's JSON answer reject malformed responses with an
+// error rather than a panic, and agree on the responses they accept
+
+use webrtc_unreliable_client::internals::tinyjson_session_response;
+
+type Parser = fn(&str) -> Result<(String, Vec<String>), String>;
+
+// parsers are the JSON parsers built into this test run
+fn parsers() -> Vec<(&'static str, Parser)> {
+    let mut parsers: Vec<(&'static str, Parser)> = vec![("tinyjson", tinyjson_session_response)];
+    #[cfg(feature = "serde-json")]
+    parsers.push((
+        "serde_json",
+        webrtc_unreliable_client::internals::serde_json_session_response,
+    ));
+    parsers
+}
+
+#[test]
+fn malformed_responses_are_errors() {
+    let malformed = [
+        "",
+        "not json",
+        "[]",
+        "\"answer\"",
+        "{}",
+        r#"{"candidate": "candidate:1 1 UDP 1 127.0.0.1 5000 typ host"}"#,
+        r#"{"answer": null}"#,
+        r#"{"answer": "v=0"}"#,
+        r#"{"answer": {}}"#,
+        r#"{"answer": {"sdp": 0}}"#,
+    ];
+    for (name, parse) in parsers() {
+        for body in malformed {
+            assert!(parse(body).is_err(), "{} accepted {:?}", name, body);
+        }
+    }
+}
+
+#[test]
+fn missing_sdp_is_reported() {
+    for (name, parse) in parsers() {
+        let err = parse(r#"{"answer": {"type": "answer"}}"#).unwrap_err();
+        assert!(err.contains("sdp"), "{}: {}", name, err);
+    }
+}
+
+#[test]
+fn missing_candidate_is_no_candidates() {
+    for (name, parse) in parsers() {
+        for body in [
+            r#"{"answer": {"sdp": "v=0"}}"#,
+            r#"{"answer": {"sdp": "v=0"}, "candidate": null}"#,
+            r#"{"answer": {"sdp": "v=0"}, "candidate": 7}"#,
+        ] {
+            assert_eq!(
+                parse(body),
+                Ok(("v=0".to_owned(), vec![])),
+                "{} on {:?}",
+                name,
+                body
+            );
+        }
+    }
+}
+
+#[test]
+fn candidates_are_read_in_every_form() {
+    let candidate = "candidate:1 1 UDP 1 127.0.0.1 5000 typ host";
+    let bodies = [
+        format!(
+            r#"{{"answer": {{"sdp": "v=0"}}, "candidate": "{}"}}"#,
+            candidate
+        ),
+        format!(
+            r#"{{"answer": {{"sdp": "v=0"}}, "candidate": {{"candidate": "{}"}}}}"#,
+            candidate
+        ),
+        format!(
+            r#"{{"answer": {{"sdp": "v=0"}}, "candidate": [{{"candidate": "{}"}}, {{}}, 3]}}"#,
+            candidate
+        ),
+    ];
+    for (name, parse) in parsers() {
+        for body in &bodies {
+            assert_eq!(
+                parse(body),
+                Ok(("v=0".to_owned(), vec![candidate.to_owned()])),
+                "{} on {}",
+                name,
+                body
+            );
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Checks that signaling goes through the configured HTTP proxy, and that a proxy url which
+// can't be used fails connecting right away
+
+use tokio::{
+    io::{copy_bidirectional, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use webrtc_unreliable_client::{
+    EchoAnswerer, SignalingProxy, Socket, SocketConfig, SocketConnectionError,
+};
+
+use crate::common::{connect_to, TIMEOUT};
+
+// http_proxy forwards every connection to the signaling endpoint of `answerer`, reporting the
+// request line each one starts with
+async fn http_proxy(answerer: &EchoAnswerer) -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let answerer_addr = answerer
+        .url()
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap()
+        .to_owned();
+    let (request_lines, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = client.read(&mut buf).await.unwrap();
+            let head = String::from_utf8_lossy(&buf[..n]).into_owned();
+            let _ = request_lines.send(head.lines().next().unwrap_or_default().to_owned());
+            let mut server = TcpStream::connect(&answerer_addr).await.unwrap();
+            server.write_all(&buf[..n]).await.unwrap();
+            tokio::spawn(async move {
+                let _ = copy_bidirectional(&mut client, &mut server).await;
+            });
+        }
+    });
+    (url, receiver)
+}
+
+#[tokio::test]
+async fn signaling_goes_through_the_proxy() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (proxy_url, mut request_lines) = http_proxy(&answerer).await;
+    let mut config = SocketConfig::default();
+    config.set_signaling_proxy(SignalingProxy::Url(proxy_url));
+    let mut socket_io = connect_to(&answerer, config).await;
+
+    // a proxied request names the whole url of the server
+    let request_line = request_lines.recv().await.unwrap();
+    assert_eq!(request_line, format!("POST {} HTTP/1.1", answerer.url()));
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+}
+
+#[tokio::test]
+async fn unusable_proxy_url_fails_connecting() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_signaling_proxy(SignalingProxy::Url("ftp://proxy.example.com".to_owned()));
+    let result = Socket::connect_with_config(answerer.url(), config).await;
+    assert!(matches!(
+        result,
+        Err(SocketConnectionError::InvalidSignalingProxy { .. })
+    ));
+}
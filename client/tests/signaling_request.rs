@@ -0,0 +1,36 @@
+// Checks that a signaling method which isn't a valid HTTP method is rejected before
+// connecting, while a valid one is accepted
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+fn config(method: &str) -> SocketConfig {
+    let mut config = SocketConfig::default();
+    config.set_signaling_request(method, "application/json", |sdp| sdp.as_bytes().to_vec());
+    config
+}
+
+#[tokio::test]
+async fn invalid_method_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    for method in ["", "PO ST", "PUT\n"] {
+        match Socket::connect_background_with_config(answerer.url(), config(method)) {
+            Err(SocketConnectionError::InvalidSignalingMethod {
+                method: rejected, ..
+            }) => assert_eq!(rejected, method),
+            Err(err) => panic!("{:?} failed with {}", method, err),
+            Ok(_) => panic!("{:?} was accepted", method),
+        }
+    }
+}
+
+#[tokio::test]
+async fn valid_methods_are_accepted() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    for method in ["PUT", "PATCH", "X-OFFER"] {
+        assert!(
+            Socket::connect_background_with_config(answerer.url(), config(method)).is_ok(),
+            "{:?} was rejected",
+            method
+        );
+    }
+}
@@ -0,0 +1,60 @@
+// Checks that signaling retry bounds which leave no attempt, or are out of order, are
+// rejected before connecting, as are retry statuses which aren't HTTP status codes
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+// check_retry starts connecting with the retry bounds, and returns the error they are
+// rejected with, if any
+async fn check_retry(attempts: u32, base_delay: Duration, max_delay: Duration) -> Option<String> {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let mut config = SocketConfig::default();
+    config.set_signaling_retry(attempts, base_delay, max_delay);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Ok(_) => None,
+        Err(SocketConnectionError::InvalidSignalingRetry { reason }) => Some(reason),
+        Err(err) => panic!(
+            "{}, {:?}, {:?} failed with {}",
+            attempts, base_delay, max_delay, err
+        ),
+    }
+}
+
+#[tokio::test]
+async fn zero_attempts_are_rejected() {
+    let delay = Duration::from_millis(100);
+    let reason = check_retry(0, delay, delay).await.unwrap();
+    assert!(reason.contains("at least one attempt"), "{}", reason);
+}
+
+#[tokio::test]
+async fn base_delay_above_max_delay_is_rejected() {
+    let reason = check_retry(3, Duration::from_secs(2), Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert!(reason.contains("exceeds the maximum delay"), "{}", reason);
+}
+
+#[tokio::test]
+async fn ordered_bounds_are_accepted() {
+    let delay = Duration::from_millis(100);
+    assert_eq!(check_retry(1, delay, delay).await, None);
+    assert_eq!(check_retry(3, Duration::ZERO, delay).await, None);
+}
+
+#[tokio::test]
+async fn invalid_status_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let delay = Duration::from_millis(100);
+    let mut config = SocketConfig::default();
+    config.set_signaling_retry(3, delay, delay);
+    config.set_signaling_retry_statuses(&[503, 1000]);
+    match Socket::connect_background_with_config(answerer.url(), config) {
+        Err(SocketConnectionError::InvalidSignalingRetry { reason }) => {
+            assert!(reason.contains("1000"), "{}", reason)
+        }
+        Err(err) => panic!("failed with {}", err),
+        Ok(_) => panic!("status 1000 was accepted"),
+    }
+}
@@ -0,0 +1,145 @@
+// Checks that an answer which is delayed and streamed in chunks is read whole, and that a
+// signaling response which stalls fails with the signaling timeout instead of hanging
+
+use std::time::{Duration, Instant};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::{fetch_answer, read_request, TIMEOUT};
+
+const SIGNALING_TIMEOUT: Duration = Duration::from_secs(1);
+const CHUNK_SIZE: usize = 64;
+
+// Answer is how the mock server responds to the offer
+#[derive(Clone, Copy)]
+enum Answer {
+    // the answer of the echo answerer, after `delay`, in chunks `chunk_delay` apart
+    Chunked {
+        delay: Duration,
+        chunk_delay: Duration,
+    },
+    // the response head and the first chunk of the answer, then nothing
+    StalledBody,
+    // nothing at all
+    StalledHead,
+}
+
+// mock_server answers every offer as `answer` says, with the answers of `answerer`, and
+// returns its url
+async fn mock_server(answerer: &EchoAnswerer, answer: Answer) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let answerer_url = answerer.url().to_owned();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let answerer_url = answerer_url.clone();
+            tokio::spawn(async move {
+                let (_, offer) = read_request(&mut stream).await;
+                let body = fetch_answer(&answerer_url, offer).await.into_bytes();
+                let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+                match answer {
+                    Answer::Chunked { delay, chunk_delay } => {
+                        tokio::time::sleep(delay).await;
+                        stream.write_all(head.as_bytes()).await.unwrap();
+                        for chunk in body.chunks(CHUNK_SIZE) {
+                            write_chunk(&mut stream, chunk).await;
+                            tokio::time::sleep(chunk_delay).await;
+                        }
+                        stream.write_all(b"0\r\n\r\n").await.unwrap();
+                    }
+                    Answer::StalledBody => {
+                        stream.write_all(head.as_bytes()).await.unwrap();
+                        write_chunk(&mut stream, &body[..CHUNK_SIZE]).await;
+                    }
+                    Answer::StalledHead => {}
+                }
+                // hold the connection open
+                let _ = stream.read(&mut [0; 1]).await;
+            });
+        }
+    });
+    url
+}
+
+async fn write_chunk(stream: &mut TcpStream, chunk: &[u8]) {
+    stream
+        .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+        .await
+        .unwrap();
+    stream.write_all(chunk).await.unwrap();
+    stream.write_all(b"\r\n").await.unwrap();
+}
+
+fn config() -> SocketConfig {
+    let mut config = SocketConfig::default();
+    config.set_signaling_timeout(SIGNALING_TIMEOUT);
+    config
+}
+
+#[tokio::test]
+async fn delayed_chunked_answer_is_assembled() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let server_url = mock_server(
+        &answerer,
+        Answer::Chunked {
+            delay: Duration::from_millis(300),
+            chunk_delay: Duration::from_millis(10),
+        },
+    )
+    .await;
+    let mut config = SocketConfig::default();
+    config.set_signaling_timeout(TIMEOUT);
+    let (_, mut socket_io) = Socket::connect_with_config(&server_url, config)
+        .await
+        .unwrap();
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    socket_io.close().await;
+}
+
+#[tokio::test]
+async fn stalled_answer_times_out() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let server_url = mock_server(&answerer, Answer::StalledBody).await;
+
+    let started = Instant::now();
+    let result = tokio::time::timeout(TIMEOUT, Socket::connect_with_config(&server_url, config()))
+        .await
+        .expect("connecting hung on the stalled answer");
+    match result {
+        Err(SocketConnectionError::Signaling { reason }) => {
+            assert!(reason.contains("could not read the answer"), "{}", reason)
+        }
+        result => panic!("connecting returned {:?}", result.map(|_| ())),
+    }
+    assert!(started.elapsed() >= SIGNALING_TIMEOUT);
+}
+
+#[tokio::test]
+async fn stalled_response_times_out() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let server_url = mock_server(&answerer, Answer::StalledHead).await;
+    // without a response, the request is retried like one which couldn't be sent, which is
+    // forever by default
+    let mut config = config();
+    config.set_signaling_retry(2, Duration::from_millis(100), Duration::from_millis(100));
+
+    let started = Instant::now();
+    let result = tokio::time::timeout(TIMEOUT, Socket::connect_with_config(&server_url, config))
+        .await
+        .expect("connecting hung on the stalled response");
+    match result {
+        Err(SocketConnectionError::Signaling { reason }) => {
+            assert!(reason.ends_with("after 2 attempts"), "{}", reason)
+        }
+        result => panic!("connecting returned {:?}", result.map(|_| ())),
+    }
+    assert!(started.elapsed() >= 2 * SIGNALING_TIMEOUT);
+}
@@ -0,0 +1,43 @@
+// Checks that the halves made by SocketIo::split send and receive from tasks of their own
+
+use webrtc_unreliable_client::SocketConfig;
+
+use crate::common::{connect, TIMEOUT};
+
+const MESSAGES: u8 = 8;
+
+#[tokio::test]
+async fn halves_round_trip_from_separate_tasks() {
+    let (_answerer, socket_io) = connect(SocketConfig::default()).await;
+    let (tx, mut rx) = socket_io.split();
+
+    let receiver = tokio::spawn(async move {
+        let mut echoes = Vec::new();
+        for _ in 0..2 * MESSAGES {
+            let echo = rx.recv_timeout(TIMEOUT).await.unwrap();
+            echoes.push(echo.expect("receiving ended before every echo arrived"));
+        }
+        echoes
+    });
+    // the sending half is cloned to send from two tasks
+    let senders: Vec<_> = [0, MESSAGES]
+        .into_iter()
+        .map(|first| {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for i in first..first + MESSAGES {
+                    tx.send(vec![i].into()).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.await.unwrap();
+    }
+
+    let mut echoes = receiver.await.unwrap();
+    echoes.sort();
+    let sent: Vec<Box<[u8]>> = (0..2 * MESSAGES).map(|i| vec![i].into()).collect();
+    assert_eq!(echoes, sent);
+    tx.close().await;
+}
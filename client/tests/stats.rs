@@ -0,0 +1,58 @@
+// Checks that the stats count the payload of the messages apart from the bytes on the wire,
+// which carry the SCTP and DTLS headers on top
+
+use std::time::Duration;
+
+use webrtc_unreliable_client::SocketConfig;
+
+use crate::common::{connect, TIMEOUT};
+
+const MESSAGES: u64 = 50;
+const MESSAGE_SIZE: u64 = 200;
+// the SCTP common and DATA chunk headers, and the DTLS record header with the AES-GCM nonce
+// and tag
+const HEADERS: u64 = 12 + 16 + 13 + 8 + 16;
+
+#[tokio::test]
+async fn payload_and_wire_bytes_differ_by_the_headers() {
+    let (_answerer, mut socket_io) = connect(SocketConfig::default()).await;
+    let message = vec![7; MESSAGE_SIZE as usize];
+
+    // the handshakes are over once the first message has been echoed
+    socket_io.send(message.clone().into()).await.unwrap();
+    socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let before = socket_io.stats();
+    assert!(before.wire_bytes_sent > before.payload_bytes_sent);
+    assert!(before.wire_bytes_received > before.payload_bytes_received);
+
+    // one message at a time, so that each goes out in a packet of its own
+    for _ in 0..MESSAGES {
+        socket_io.send(message.clone().into()).await.unwrap();
+        let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(echo.as_deref(), Some(&message[..]));
+    }
+    let after = socket_io.stats();
+
+    let payload = MESSAGES * MESSAGE_SIZE;
+    assert_eq!(
+        after.payload_bytes_sent - before.payload_bytes_sent,
+        payload
+    );
+    assert_eq!(
+        after.payload_bytes_received - before.payload_bytes_received,
+        payload
+    );
+    // every message adds the headers, and at most a packet acknowledging the other side's
+    for overhead in [
+        after.overhead_bytes_sent() - before.overhead_bytes_sent(),
+        after.overhead_bytes_received() - before.overhead_bytes_received(),
+    ] {
+        let per_message = overhead / MESSAGES;
+        assert!(
+            (HEADERS..=2 * HEADERS + 16).contains(&per_message),
+            "{} bytes of overhead per message",
+            per_message
+        );
+    }
+}
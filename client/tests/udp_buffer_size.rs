@@ -0,0 +1,39 @@
+// Checks that a UDP buffer size of zero is rejected before connecting, while a positive one
+// connects
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::{connect, TIMEOUT};
+
+#[tokio::test]
+async fn zero_buffer_sizes_are_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let setters: [(&str, fn(&mut SocketConfig, usize)); 2] = [
+        ("receive", SocketConfig::set_udp_recv_buffer_size),
+        ("send", SocketConfig::set_udp_send_buffer_size),
+    ];
+    for (buffer, set_buffer_size) in setters {
+        let mut config = SocketConfig::default();
+        set_buffer_size(&mut config, 0);
+        match Socket::connect_background_with_config(answerer.url(), config) {
+            Err(SocketConnectionError::InvalidUdpBufferSize { reason }) => {
+                assert!(reason.contains(buffer), "{}", reason)
+            }
+            Err(err) => panic!("the {} buffer failed with {}", buffer, err),
+            Ok(_) => panic!("a {} buffer of 0 bytes was accepted", buffer),
+        }
+    }
+}
+
+#[tokio::test]
+async fn positive_buffer_sizes_connect() {
+    let mut config = SocketConfig::default();
+    config.set_udp_recv_buffer_size(1 << 20);
+    config.set_udp_send_buffer_size(1 << 20);
+    let (_answerer, mut socket_io) = connect(config).await;
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+    socket_io.close().await;
+}
@@ -0,0 +1,34 @@
+// Checks that a user agent which can't be sent as a header is rejected before connecting,
+// while a custom one which can is used
+
+use webrtc_unreliable_client::{EchoAnswerer, Socket, SocketConfig, SocketConnectionError};
+
+use crate::common::{connect, TIMEOUT};
+
+#[tokio::test]
+async fn invalid_user_agent_is_rejected() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    for user_agent in ["game/1.0\nX-Injected: 1", "game/1.0\r", "game\u{7f}/1.0"] {
+        let mut config = SocketConfig::default();
+        config.set_user_agent(user_agent);
+        match Socket::connect_with_config(answerer.url(), config).await {
+            Err(SocketConnectionError::InvalidUserAgent {
+                user_agent: rejected,
+                ..
+            }) => assert_eq!(rejected, user_agent),
+            Err(err) => panic!("{:?} failed with {}", user_agent, err),
+            Ok(_) => panic!("{:?} was accepted", user_agent),
+        }
+    }
+}
+
+#[tokio::test]
+async fn custom_user_agent_connects() {
+    let mut config = SocketConfig::default();
+    config.set_user_agent("game/1.0 (build 42)");
+    let (_answerer, mut socket_io) = connect(config).await;
+
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+}
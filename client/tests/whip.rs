@@ -0,0 +1,84 @@
+// Checks that WHIP signaling posts the offer as application/sdp, resolves a relative
+// Location against the endpoint, and deletes that resource on close
+
+use tokio::{io::AsyncWriteExt, net::TcpListener, sync::mpsc};
+use webrtc_unreliable_client::{
+    internals::tinyjson_session_response, EchoAnswerer, Signaling, Socket, SocketConfig,
+};
+
+use crate::common::{fetch_answer, read_request, TIMEOUT};
+
+// whip_endpoint stands in for a WHIP endpoint in front of `answerer`, answering offers with
+// its answers and a relative resource location, and reporting the head of every request
+async fn whip_endpoint(answerer: &EchoAnswerer) -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/whip/endpoint", listener.local_addr().unwrap());
+    let answerer_url = answerer.url().to_owned();
+    let (heads, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (head, offer) = read_request(&mut stream).await;
+            let response = if head.starts_with("POST ") {
+                // a WHIP answer carries the candidates in its SDP
+                let (mut sdp, candidates) =
+                    tinyjson_session_response(&fetch_answer(&answerer_url, offer).await).unwrap();
+                for candidate in candidates {
+                    sdp.push_str(&format!("a={}\r\n", candidate));
+                }
+                format!(
+                    "HTTP/1.1 201 Created\r\nContent-Type: application/sdp\r\nLocation: session/1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    sdp.len(),
+                    sdp
+                )
+            } else {
+                "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_owned()
+            };
+            let _ = heads.send(head);
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+    (url, receiver)
+}
+
+#[tokio::test]
+async fn offer_is_posted_and_the_resource_deleted_on_close() {
+    let answerer = EchoAnswerer::start().await.unwrap();
+    let (url, mut heads) = whip_endpoint(&answerer).await;
+    let mut config = SocketConfig::default();
+    config.set_signaling(Signaling::Whip);
+    let (_, mut socket_io) =
+        tokio::time::timeout(TIMEOUT, Socket::connect_with_config(&url, config))
+            .await
+            .expect("connecting timed out")
+            .unwrap();
+
+    let post = heads.recv().await.unwrap();
+    assert!(
+        post.starts_with("POST /whip/endpoint HTTP/1.1\r\n"),
+        "{}",
+        post
+    );
+    assert!(
+        post.to_lowercase()
+            .contains("\r\ncontent-type: application/sdp\r\n"),
+        "{}",
+        post
+    );
+    socket_io.send(b"ping".to_vec().into()).await.unwrap();
+    let echo = socket_io.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(echo.as_deref(), Some(&b"ping"[..]));
+
+    socket_io.close().await;
+    // the location is relative to the endpoint's path
+    let delete = tokio::time::timeout(TIMEOUT, heads.recv())
+        .await
+        .expect("the resource wasn't deleted")
+        .unwrap();
+    assert!(
+        delete.starts_with("DELETE /whip/session/1 HTTP/1.1\r\n"),
+        "{}",
+        delete
+    );
+}
@@ -1,7 +1,7 @@
 use anyhow::{Error, Result};
 use tokio::{sync::mpsc, time::Duration};
 
-use webrtc_unreliable_client::{AddrCell, ServerAddr, Socket};
+use webrtc_unreliable_client::{AddrCell, ServerAddr, Socket, SocketIo};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -15,8 +15,12 @@ async fn main() -> Result<()> {
     let server_address = "127.0.0.1";
     let server_url = format!("http://{}:14191/rtc_session", server_address);
 
-    let (addr_cell, to_server_sender, to_client_receiver) =
-        Socket::connect(server_url.as_str()).await;
+    let (addr_cell, socket_io) = Socket::connect(server_url.as_str()).await?;
+    let SocketIo {
+        to_server_sender,
+        to_client_receiver,
+        ..
+    } = socket_io;
 
     let addr_cell_1 = addr_cell.clone();
     let addr_cell_2 = addr_cell.clone();